@@ -1,3 +1,7 @@
+/**
+ * user-editable notes, tags and comments
+ */
+export type Annotations = { notes: string; tags: string[]; comments: Comment[] }
 export type AudioSource = 
 /**
  * no audio
@@ -16,21 +20,193 @@ export type AudioSource =
  */
 "ALL"
 export type BuildingType = { buildingType: "INHIBITOR_BUILDING"; lane_type: LaneType } | { buildingType: "TOWER_BUILDING"; lane_type: LaneType; tower_type: TowerType }
-export type Deferred = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights: number[] }
+/**
+ * part of a recording that got cut into its own video file
+ * keeps the data of the source recording that is needed to show the clip like a recording
+ */
+export type Clip = { favorite: boolean; 
+/**
+ * video id of the recording the clip was cut from
+ */
+sourceVideoId: string; 
+/**
+ * start of the clip in seconds of the source video
+ */
+start: number; 
+/**
+ * end of the clip in seconds of the source video
+ */
+end: number; matchId: MatchId | null; 
+/**
+ * `None` if the ingame time of the source recording is unknown
+ */
+ingameTimeRecStartOffset: number | null; queue: Queue | null; player: Player | null; championName: string | null; participantId: number | null; 
+/**
+ * the events of the source recording inside of the clip
+ */
+events: GameEvent[]; 
+/**
+ * the highlights of the source recording inside of the clip
+ */
+highlights: number[]; annotations: Annotations; review: ReviewState }
+export type Comment = { 
+/**
+ * unique inside of a metadata file
+ */
+id: number; 
+/**
+ * ingame time in milliseconds (same as `highlights`)
+ * for `NoData` recordings the ingame time is unknown so this is the time in the video instead
+ */
+gameTime: number; text: string }
+/**
+ * how much of the recording around a death is part of the death review
+ */
+export type DeathReviewWindow = { secondsBefore: number; secondsAfter: number }
+export type Deferred = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights: number[]; 
+/**
+ * the account that was logged in when the recording started
+ */
+player: Player | null; 
+/**
+ * lineup from the gameflow session when the recording started
+ */
+roster: Roster | null; 
+/**
+ * rank in the queue of the game before it started (only ranked queues)
+ */
+rankBefore: RankedQueueStats | null; 
+/**
+ * download of the replay of the League client (only if replays get downloaded)
+ */
+replay: ReplayState | null; annotations: Annotations; review: ReviewState }
 export type DragonType = "FIRE_DRAGON" | "EARTH_DRAGON" | "WATER_DRAGON" | "AIR_DRAGON" | "HEXTECH_DRAGON" | "CHEMTECH_DRAGON" | "ELDER_DRAGON"
 export type Framerate = [number, number]
-export type GameEvent = ({ ChampionKill: { victim_id: number; killer_id: number; assisting_participant_ids: number[]; position: Position } } | { BuildingKill: { team_id: Team; killer_id: number; building_type: BuildingType; assisting_participant_ids: number[] } } | { EliteMonsterKill: { killer_id: number; monster_type: MonsterType; assisting_participant_ids: number[] } }) & { timestamp: number }
-export type GameMetadata = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights: number[]; queue: Queue; player: Player; championName: string; stats: Stats; participantId: number; events: GameEvent[] }
+export type GameEvent = ({ ChampionKill: { victim_id: number; killer_id: number; assisting_participant_ids: number[]; position: Position } } | { BuildingKill: { team_id: number; killer_id: number; building_type: BuildingType; assisting_participant_ids: number[] } } | { EliteMonsterKill: { killer_id: number; monster_type: MonsterType; assisting_participant_ids: number[] } }) & { timestamp: number }
+export type GameMetadata = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights: number[]; queue: Queue; player: Player; championName: string; stats: Stats; participantId: number; events: GameEvent[]; 
+/**
+ * all players of the game with their participant ids (from match history)
+ */
+participants: RosterEntry[]; gameVersion: string | null; 
+/**
+ * major.minor part of `game_version` (e.g. "15.14")
+ */
+patch: string | null; 
+/**
+ * in seconds
+ */
+gameDuration: number | null; 
+/**
+ * unix timestamp in milliseconds
+ */
+gameCreation: number | null; mapId: number | null; gameMode: string | null; 
+/**
+ * rank in the queue of the game before it started (only ranked queues)
+ */
+rankBefore: RankedQueueStats | null; 
+/**
+ * rank in the queue of the game after it ended (only ranked queues)
+ */
+rankAfter: RankedQueueStats | null; 
+/**
+ * download of the replay of the League client (only if replays get downloaded)
+ */
+replay: ReplayState | null; annotations: Annotations; review: ReviewState }
+export type GameResult = "Win" | "Loss" | "Remake"
 export type LaneType = "TOP_LANE" | "MID_LANE" | "BOT_LANE"
 export type MarkerFlags = { kill: boolean; death: boolean; assist: boolean; structure: boolean; dragon: boolean; herald: boolean; atakhan: boolean; baron: boolean }
 export type MatchId = { gameId: number; platformId: string }
-export type MetadataFile = { Metadata: GameMetadata } | { Deferred: Deferred } | { NoData: NoData }
+export type MetadataFile = { Metadata: GameMetadata } | { Deferred: Deferred } | { NoData: NoData } | { Clip: Clip } | { Reel: Reel }
+/**
+ * content of the metadata file of a recording (`{videoName}.json`)
+ */
+export type MetadataFileJson = ({ Metadata: GameMetadata } | { Deferred: Deferred } | { NoData: NoData } | { Clip: Clip } | { Reel: Reel }) & { 
+/**
+ * version of the format of the metadata file, older files get migrated when LeagueRecord reads them
+ */
+schemaVersion: number }
 export type MonsterType = { monsterType: "HORDE" } | { monsterType: "RIFTHERALD" } | { monsterType: "ATAKHAN" } | { monsterType: "BARON_NASHOR" } | { monsterType: "DRAGON"; monsterSubType: DragonType }
-export type NoData = { favorite: boolean }
-export type Player = { gameName: string; tagLine: string; summonerId?: number | null }
+export type NoData = { favorite: boolean; annotations: Annotations; review: ReviewState }
+export type Player = { gameName: string; tagLine: string; summonerId?: number | null; 
+/**
+ * missing in metadata files that were created before the PUUID was saved
+ */
+puuid?: string | null }
 export type Position = { x: number; y: number }
 export type Queue = { id: number; name: string; isRanked: boolean }
-export type Settings = { markerFlags: MarkerFlags; checkForUpdates: boolean; debugLog: boolean; recordingsFolder: string; filenameFormat: string; encodingQuality: number; outputResolution: StdResolution | null; framerate: Framerate; recordAudio: AudioSource; onlyRecordRanked: boolean; autostart: boolean; maxRecordingAgeDays: number | null; maxRecordingsSizeGb: number | null; confirmDelete: boolean; hightlightHotkey: string | null }
+export type RankedQueueStats = { queueType: string; 
+/**
+ * e.g. "GOLD" (empty or "NONE" if unranked)
+ */
+tier?: string; 
+/**
+ * "I" - "IV" ("NA" for apex tiers)
+ */
+division?: string; leaguePoints?: number }
+/**
+ * parts of one or more recordings that got joined into one video (e.g. all pentakills of a season)
+ */
+export type Reel = { favorite: boolean; 
+/**
+ * in the order they are played in the reel
+ */
+segments: ReelSegment[]; annotations: Annotations; review: ReviewState }
+export type ReelSegment = { 
+/**
+ * video id of the recording the segment was copied from
+ */
+sourceVideoId: string; 
+/**
+ * start of the segment in seconds of the source video
+ */
+start: number; 
+/**
+ * end of the segment in seconds of the source video
+ */
+end: number; 
+/**
+ * start of the segment in seconds of the reel
+ */
+reelStart: number; matchId: MatchId | null; championName: string | null }
+/**
+ * the League client downloads its replay of the game (`.rofl` file) after the game ended
+ */
+export type ReplayState = "Downloading" | { Downloaded: { path: string } } | 
+/**
+ * e.g. because the replay expired or the client was closed before the replay was available
+ */
+{ Failed: { reason: string } }
+/**
+ * which recordings show up in the review queue (all set conditions have to match)
+ */
+export type ReviewRule = { 
+/**
+ * empty for all queues
+ */
+queueIds: number[]; result: GameResult | null; maxAgeDays: number | null }
+/**
+ * progress of the user reviewing the recording
+ */
+export type ReviewState = { watched: boolean; reviewed: boolean; 
+/**
+ * time in the video in seconds where playback stopped last time
+ */
+position: number | null }
+export type Roster = { mapId: number | null; mapName: string | null; gameMode: string | null; players: RosterEntry[] }
+export type RosterEntry = { 
+/**
+ * only known after the game from match history
+ */
+participantId?: number | null; 
+/**
+ * missing if the match history has no (valid) team id for the participant
+ */
+team?: number | null; championId: number; championName: string | null; 
+/**
+ * missing for bots
+ */
+player?: Player | null; position?: string | null }
+export type Settings = { markerFlags: MarkerFlags; checkForUpdates: boolean; debugLog: boolean; recordingsFolder: string; filenameFormat: string; encodingQuality: number; outputResolution: StdResolution | null; framerate: Framerate; recordAudio: AudioSource; onlyRecordRanked: boolean; autostart: boolean; maxRecordingAgeDays: number | null; maxRecordingsSizeGb: number | null; accountSubfolders: boolean; retentionPerAccount: boolean; cleanupClips: boolean; embedChapters: boolean; streamerMode: boolean; replaysFolder: string | null; downloadReplays: boolean; sessionGapMinutes: number; reviewQueue: ReviewRule; deathReviewWindow: DeathReviewWindow; confirmDelete: boolean; hightlightHotkey: string | null }
 export type Stats = { kills: number; deaths: number; assists: number; largestMultiKill: number; neutralMinionsKilled: number; neutralMinionsKilledEnemyJungle: number; neutralMinionsKilledTeamJungle: number; totalMinionsKilled: number; visionScore: number; visionWardsBoughtInGame: number; wardsPlaced: number; wardsKilled: number; 
 /**
  * remake
@@ -138,5 +314,4 @@ export type StdResolution =
  * 32:10 3840x1200p
  */
 "3840x1200p"
-export type Team = "BLUE" | "RED"
 export type TowerType = "OUTER_TURRET" | "INNER_TURRET" | "BASE_TURRET" | "NEXUS_TURRET"
\ No newline at end of file
@@ -11,6 +11,8 @@ pub type SummonerId = i64;
 pub type ChampionId = i64;
 pub type Timestamp = i64;
 pub type SpellId = i64;
+pub type ItemId = i64;
+pub type PerkId = i64;
 
 #[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -158,15 +160,21 @@ pub enum DragonType {
     ElderDragon,
 }
 
-#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// gets serialized as the numeric team id
 #[derive(Debug, Clone, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[repr(u32)]
 pub enum Team {
     Blue = 100,
     Red = 200,
 }
 
+#[cfg(feature = "specta")]
+impl specta::Type for Team {
+    fn inline(type_map: &mut specta::TypeCollection, generics: specta::Generics) -> specta::DataType {
+        <u32 as specta::Type>::inline(type_map, generics)
+    }
+}
+
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
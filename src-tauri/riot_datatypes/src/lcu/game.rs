@@ -1,6 +1,6 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::{ChampionId, GameId, MapId, ParticipantId, QueueId, SpellId, SummonerId, Timestamp};
+use crate::{ChampionId, GameId, MapId, ParticipantId, QueueId, SpellId, SummonerId, Team, Timestamp};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -9,7 +9,13 @@ pub struct Game {
     pub game_id: GameId,
     pub map_id: MapId,
     pub queue_id: QueueId,
+    /// in seconds
     pub game_duration: Timestamp,
+    /// unix timestamp in milliseconds
+    #[serde(default)]
+    pub game_creation: Timestamp,
+    #[serde(default)]
+    pub game_mode: String,
     pub participant_identities: Vec<ParticipantIdentity>,
     pub participants: Vec<Participant>,
 }
@@ -22,18 +28,35 @@ pub struct ParticipantIdentity {
 }
 
 #[cfg_attr(feature = "specta", derive(specta::Type))]
-#[derive(Debug, Clone, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Player {
     pub game_name: String,
     pub tag_line: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub summoner_id: Option<SummonerId>,
+    /// missing in metadata files that were created before the PUUID was saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub puuid: Option<String>,
 }
 
-impl PartialEq for Player {
-    fn eq(&self, other: &Self) -> bool {
-        self.game_name == other.game_name && self.tag_line == other.tag_line
+impl Player {
+    /// the PUUID is the only identifier of an account that never changes
+    pub fn puuid(&self) -> Option<&str> {
+        self.puuid.as_deref().filter(|puuid| !puuid.is_empty())
+    }
+
+    /// compares the PUUIDs if both players have one, otherwise the Riot IDs
+    /// (not transitive when players with and without PUUID are mixed, so this is no `PartialEq`)
+    pub fn is_same_account(&self, other: &Self) -> bool {
+        match (self.puuid(), other.puuid()) {
+            (Some(puuid), Some(other_puuid)) => puuid == other_puuid,
+            // fall back to comparing the Riot ID (case-insensitive, same as the Riot ID itself)
+            _ => {
+                self.game_name.to_lowercase() == other.game_name.to_lowercase()
+                    && self.tag_line.to_lowercase() == other.tag_line.to_lowercase()
+            }
+        }
     }
 }
 
@@ -41,12 +64,23 @@ impl PartialEq for Player {
 #[serde(rename_all = "camelCase")]
 pub struct Participant {
     pub participant_id: ParticipantId,
+    /// `None` if the team id is missing or isn't one of the two teams
+    #[serde(default, deserialize_with = "team_or_none")]
+    pub team_id: Option<Team>,
     pub champion_id: ChampionId,
     pub spell1_id: SpellId,
     pub spell2_id: SpellId,
     pub stats: Stats,
 }
 
+fn team_or_none<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Team>, D::Error> {
+    Ok(match Option::<i64>::deserialize(deserializer)? {
+        Some(100) => Some(Team::Blue),
+        Some(200) => Some(Team::Red),
+        _ => None,
+    })
+}
+
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
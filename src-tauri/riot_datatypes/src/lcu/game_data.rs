@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ChampionId, ItemId, PerkId, QueueId, SpellId};
+
+/// entry of `/lol-game-data/assets/v1/champion-summary.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionSummary {
+    pub id: ChampionId,
+    pub name: String,
+    #[serde(default)]
+    pub alias: String,
+}
+
+/// entry of `/lol-game-data/assets/v1/queues.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSummary {
+    pub id: QueueId,
+    pub name: String,
+    #[serde(default)]
+    pub short_name: String,
+}
+
+/// `/lol-game-data/assets/v1/queues.json`
+/// depending on the client version this is either a list of queues or a map of queue-id to queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QueueSummaries {
+    List(Vec<QueueSummary>),
+    Map(HashMap<String, QueueSummaryEntry>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSummaryEntry {
+    pub name: String,
+    #[serde(default)]
+    pub short_name: String,
+}
+
+impl From<QueueSummaries> for Vec<QueueSummary> {
+    fn from(value: QueueSummaries) -> Self {
+        match value {
+            QueueSummaries::List(queues) => queues,
+            QueueSummaries::Map(queues) => queues
+                .into_iter()
+                .filter_map(|(id, entry)| {
+                    Some(QueueSummary {
+                        id: id.parse().ok()?,
+                        name: entry.name,
+                        short_name: entry.short_name,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// entry of `/lol-game-data/assets/v1/summoner-spells.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummonerSpellSummary {
+    pub id: SpellId,
+    pub name: String,
+}
+
+/// entry of `/lol-game-data/assets/v1/items.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemSummary {
+    pub id: ItemId,
+    pub name: String,
+}
+
+/// entry of `/lol-game-data/assets/v1/perks.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerkSummary {
+    pub id: PerkId,
+    pub name: String,
+}
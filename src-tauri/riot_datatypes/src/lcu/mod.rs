@@ -1,5 +1,9 @@
 mod game;
+mod game_data;
+mod ranked;
 mod session;
 
 pub use game::*;
+pub use game_data::*;
+pub use ranked::*;
 pub use session::*;
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::QueueId;
+
+/// ids of the ranked queues and their `queue_type` in the ranked stats
+const RANKED_QUEUES: [(QueueId, &str); 2] = [(420, "RANKED_SOLO_5x5"), (440, "RANKED_FLEX_SR")];
+
+/// '/lol-ranked/v1/current-ranked-stats'
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedStats {
+    #[serde(default)]
+    pub queues: Vec<RankedQueueStats>,
+}
+
+impl RankedStats {
+    /// rank of the player in the ranked queue with the given id
+    pub fn queue(&self, queue_id: QueueId) -> Option<&RankedQueueStats> {
+        let (_, queue_type) = RANKED_QUEUES.iter().find(|(id, _)| *id == queue_id)?;
+        self.queues
+            .iter()
+            .find(|queue| queue.queue_type == *queue_type)
+            .filter(|queue| !queue.tier.is_empty() && queue.tier != "NONE")
+    }
+}
+
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedQueueStats {
+    pub queue_type: String,
+    /// e.g. "GOLD" (empty or "NONE" if unranked)
+    #[serde(default)]
+    pub tier: String,
+    /// "I" - "IV" ("NA" for apex tiers)
+    #[serde(default)]
+    pub division: String,
+    #[serde(default)]
+    pub league_points: i64,
+}
+
+impl RankedQueueStats {
+    const TIERS: [&'static str; 7] = ["IRON", "BRONZE", "SILVER", "GOLD", "PLATINUM", "EMERALD", "DIAMOND"];
+    const DIVISIONS: [&'static str; 4] = ["IV", "III", "II", "I"];
+
+    /// id of the queue these stats are for
+    pub fn queue_id(&self) -> Option<QueueId> {
+        RANKED_QUEUES
+            .iter()
+            .find(|(_, queue_type)| *queue_type == self.queue_type)
+            .map(|(id, _)| *id)
+    }
+
+    /// LP counted from Iron IV 0LP so ranks in different divisions can be compared
+    /// master, grandmaster and challenger share one LP ladder on top of Diamond I
+    pub fn total_league_points(&self) -> Option<i64> {
+        let base = match Self::TIERS.iter().position(|tier| *tier == self.tier) {
+            Some(tier) => {
+                let division = Self::DIVISIONS.iter().position(|division| *division == self.division)?;
+                (tier * Self::DIVISIONS.len() + division) as i64 * 100
+            }
+            None if matches!(self.tier.as_str(), "MASTER" | "GRANDMASTER" | "CHALLENGER") => {
+                (Self::TIERS.len() * Self::DIVISIONS.len()) as i64 * 100
+            }
+            None => return None,
+        };
+        Some(base + self.league_points)
+    }
+}
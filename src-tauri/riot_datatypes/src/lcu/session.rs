@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{GameId, Queue};
+use crate::{ChampionId, GameId, MapId, Queue, SpellId, SummonerId};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -14,6 +14,8 @@ pub enum SubscriptionResponse {
 pub struct SessionEventData {
     pub game_data: GameData,
     pub phase: GamePhase,
+    #[serde(default)]
+    pub map: Option<MapInfo>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,4 +42,60 @@ pub struct GameData {
     /// this queue does not have a valid 'name' field for some reason
     pub queue: Queue,
     pub game_id: GameId,
+    /// blue side
+    #[serde(default)]
+    pub team_one: Vec<TeamMember>,
+    /// red side
+    #[serde(default)]
+    pub team_two: Vec<TeamMember>,
+    #[serde(default)]
+    pub player_champion_selections: Vec<PlayerChampionSelection>,
+}
+
+// all fields are optional because they are empty or null for bots or in some queues (e.g. practicetool)
+// a single unexpected value must not break the deserialization of the whole session
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapInfo {
+    #[serde(default)]
+    pub id: Option<MapId>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub game_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMember {
+    #[serde(default)]
+    pub champion_id: Option<ChampionId>,
+    #[serde(default)]
+    pub puuid: Option<String>,
+    #[serde(default)]
+    pub game_name: Option<String>,
+    #[serde(default)]
+    pub tag_line: Option<String>,
+    #[serde(default)]
+    pub summoner_name: Option<String>,
+    #[serde(default)]
+    pub summoner_id: Option<SummonerId>,
+    #[serde(default)]
+    pub selected_position: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerChampionSelection {
+    #[serde(default)]
+    pub champion_id: Option<ChampionId>,
+    #[serde(default)]
+    pub puuid: Option<String>,
+    #[serde(default)]
+    pub summoner_internal_name: Option<String>,
+    #[serde(default)]
+    pub spell1_id: Option<SpellId>,
+    #[serde(default)]
+    pub spell2_id: Option<SpellId>,
 }
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use riot_datatypes::lcu::Player;
+
+use super::account_id;
+use crate::recorder::{MetadataFile, ReplayState};
+
+/// tag line of all pseudonymized players
+const ANONYMOUS_TAG_LINE: &str = "ANON";
+
+/// replaces players with pseudonyms ("Player 1", "Player 2", ...) in the order they are first seen
+/// the same player always gets the same pseudonym, so references between files or events stay intact
+#[derive(Debug, Default)]
+pub struct Pseudonyms(HashMap<String, usize>);
+
+impl Pseudonyms {
+    /// the pseudonym has no Riot ID, PUUID or summoner ID of the player
+    pub fn player(&mut self, player: &Player) -> Player {
+        // same key as the recordings index uses for accounts, so an account never gets two pseudonyms
+        let next = self.0.len() + 1;
+        let number = *self.0.entry(account_id(player)).or_insert(next);
+        Player {
+            game_name: format!("Player {number}"),
+            tag_line: ANONYMOUS_TAG_LINE.to_owned(),
+            summoner_id: None,
+            puuid: None,
+        }
+    }
+}
+
+/// pseudonymizes every player in the metadata file, players that already have a pseudonym keep it
+/// a downloaded replay only keeps its filename because the path contains the user folder
+pub fn anonymize_metadata(metadata_file: &mut MetadataFile, pseudonyms: &mut Pseudonyms) {
+    for player in metadata_file.players_mut() {
+        *player = pseudonyms.player(player);
+    }
+
+    if let Some(ReplayState::Downloaded { path }) = metadata_file.replay() {
+        let path = path.file_name().map(PathBuf::from).unwrap_or_default();
+        metadata_file.set_replay(ReplayState::Downloaded { path });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(game_name: &str, tag_line: &str, puuid: Option<&str>) -> Player {
+        Player {
+            game_name: game_name.to_owned(),
+            tag_line: tag_line.to_owned(),
+            summoner_id: None,
+            puuid: puuid.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn same_account_same_pseudonym() {
+        let mut pseudonyms = Pseudonyms::default();
+        let mut pseudonym = |player: &Player| {
+            let pseudonym = pseudonyms.player(player);
+            assert_eq!(pseudonym.tag_line, ANONYMOUS_TAG_LINE);
+            assert!(pseudonym.puuid.is_none() && pseudonym.summoner_id.is_none());
+            pseudonym.game_name
+        };
+
+        assert_eq!(pseudonym(&player("Name", "EUW", Some("puuid-1"))), "Player 1");
+        // the Riot ID changed but it's still the same account
+        assert_eq!(pseudonym(&player("New Name", "EUW", Some("puuid-1"))), "Player 1");
+        // a different account with the old Riot ID of the first one
+        assert_eq!(pseudonym(&player("Name", "EUW", Some("puuid-2"))), "Player 2");
+
+        // old metadata files without PUUID only have the Riot ID
+        assert_eq!(pseudonym(&player("Old", "NA1", None)), "Player 3");
+        assert_eq!(pseudonym(&player("Old", "NA1", Some(""))), "Player 3");
+        assert_eq!(pseudonym(&player("Other", "NA1", None)), "Player 4");
+    }
+}
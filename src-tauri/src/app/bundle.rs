@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const BUNDLE_VERSION: u64 = 1;
+const MANIFEST: &str = "manifest.json";
+const RECORDING: &str = "recording.mp4";
+/// the metadata file of the recording (with all notes, highlights and comments) in the `MetadataFile` json format
+const METADATA: &str = "metadata.json";
+
+/// first file of every bundle
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    version: u64,
+    /// video id of the recording that got bundled
+    video_id: String,
+    /// sha256 checksum (hex) of every other file in the bundle
+    checksums: BTreeMap<String, String>,
+}
+
+/// contents of a bundle after the recording got extracted
+#[derive(Debug)]
+pub struct Bundle {
+    /// video id of the recording when it got bundled (just the filename, never a path)
+    pub video_id: String,
+    pub metadata: Value,
+}
+
+/// writes the recording and its metadata together with a manifest into a zip archive at `output`
+/// everything is stored uncompressed because the video is already compressed
+pub fn write_bundle(recording_path: &Path, metadata: &Value, output: &Path) -> Result<()> {
+    let video_id = recording_path
+        .file_name()
+        .context("recording has no name")?
+        .to_string_lossy()
+        .into_owned();
+    let metadata = serde_json::to_vec(metadata)?;
+
+    // hashing the recording before writing it means reading it twice, but the manifest has to be the first file
+    let mut checksums = BTreeMap::new();
+    checksums.insert(
+        RECORDING.to_owned(),
+        copy_hashed(&mut BufReader::new(File::open(recording_path)?), &mut std::io::sink())?,
+    );
+    checksums.insert(METADATA.to_owned(), hex_sha256(&metadata));
+    let manifest = Manifest {
+        version: BUNDLE_VERSION,
+        video_id,
+        checksums,
+    };
+
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(output)?));
+    zip.start_file(MANIFEST, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    zip.start_file(METADATA, options)?;
+    zip.write_all(&metadata)?;
+    zip.start_file(RECORDING, options)?;
+    std::io::copy(&mut BufReader::new(File::open(recording_path)?), &mut zip)?;
+    zip.finish()?.into_inner()?.sync_all()?;
+    Ok(())
+}
+
+/// checks the bundle at `path` and extracts the recording to `video_output`
+/// fails if a file is missing or any checksum doesn't match
+pub fn read_bundle(path: &Path, video_output: &Path) -> Result<Bundle> {
+    let mut zip = ZipArchive::new(BufReader::new(File::open(path)?)).context("not a recording bundle")?;
+
+    let manifest = serde_json::from_reader::<_, Manifest>(zip.by_name(MANIFEST).context("missing manifest")?)?;
+    if manifest.version > BUNDLE_VERSION {
+        bail!("bundle was created by a newer version of the app");
+    }
+    let checksum = |name: &str| {
+        manifest
+            .checksums
+            .get(name)
+            .context(format!("missing checksum of {name}"))
+    };
+
+    let mut metadata = Vec::new();
+    zip.by_name(METADATA)
+        .context("missing metadata")?
+        .read_to_end(&mut metadata)?;
+    if &hex_sha256(&metadata) != checksum(METADATA)? {
+        bail!("metadata is corrupted");
+    }
+
+    let mut output = BufWriter::new(File::create(video_output)?);
+    let hash = copy_hashed(&mut zip.by_name(RECORDING).context("missing recording")?, &mut output)?;
+    if &hash != checksum(RECORDING)? {
+        bail!("recording is corrupted");
+    }
+    output.into_inner()?.sync_all()?;
+
+    // the video id ends up in a path, so only the filename is allowed
+    let video_id = Path::new(&manifest.video_id)
+        .file_name()
+        .context("invalid video id")?
+        .to_string_lossy()
+        .into_owned();
+
+    Ok(Bundle {
+        video_id,
+        metadata: serde_json::from_slice(&metadata)?,
+    })
+}
+
+/// copies everything from `reader` to `writer` and returns the sha256 checksum (hex) of the data
+fn copy_hashed(reader: &mut impl Read, writer: &mut impl Write) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        let len = reader.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+        writer.write_all(&buffer[..len])?;
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::*;
+
+    const VIDEO: &[u8] = b"not really a video but good enough";
+
+    /// bundles a fake recording named `game.mp4`
+    fn bundle(dir: &Path) -> Result<PathBuf> {
+        let recording = dir.join("game.mp4");
+        fs::write(&recording, VIDEO)?;
+        let bundle = dir.join("bundle.zip");
+        write_bundle(&recording, &json!({ "favorite": true }), &bundle)?;
+        Ok(bundle)
+    }
+
+    /// copies every file of the bundle to `output`, `change` can replace the contents of a file or leave it out
+    fn rewrite(bundle: &Path, output: &Path, change: impl Fn(&str, Vec<u8>) -> Option<Vec<u8>>) -> Result<()> {
+        let mut zip = ZipArchive::new(File::open(bundle)?)?;
+        let mut out = ZipWriter::new(File::create(output)?);
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+            let name = file.name().to_owned();
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+
+            if let Some(data) = change(&name, data) {
+                out.start_file(name, SimpleFileOptions::default())?;
+                out.write_all(&data)?;
+            }
+        }
+        out.finish()?;
+        Ok(())
+    }
+
+    fn rewrite_manifest(bundle: &Path, output: &Path, change: impl Fn(&mut Manifest)) -> Result<()> {
+        rewrite(bundle, output, |name, data| {
+            if name != MANIFEST {
+                return Some(data);
+            }
+            let mut manifest = serde_json::from_slice::<Manifest>(&data).unwrap();
+            change(&mut manifest);
+            Some(serde_json::to_vec(&manifest).unwrap())
+        })
+    }
+
+    fn read_error(bundle: &Path, video_output: &Path) -> String {
+        read_bundle(bundle, video_output).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bundle_test_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let bundle = bundle(&dir)?;
+
+        let video_output = dir.join("imported.mp4");
+        let imported = read_bundle(&bundle, &video_output)?;
+        assert_eq!(imported.video_id, "game.mp4");
+        assert_eq!(imported.metadata, json!({ "favorite": true }));
+        assert_eq!(fs::read(&video_output)?, VIDEO);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_bundles() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bundle_test_tampered_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let bundle = bundle(&dir)?;
+        let tampered = dir.join("tampered.zip");
+        let video_output = dir.join("imported.mp4");
+
+        rewrite(&bundle, &tampered, |name, mut data| {
+            if name == RECORDING {
+                data[0] ^= 1;
+            }
+            Some(data)
+        })?;
+        assert_eq!(read_error(&tampered, &video_output), "recording is corrupted");
+
+        rewrite(&bundle, &tampered, |name, data| match name {
+            METADATA => Some(br#"{"favorite":false}"#.to_vec()),
+            _ => Some(data),
+        })?;
+        assert_eq!(read_error(&tampered, &video_output), "metadata is corrupted");
+
+        for missing in [MANIFEST, METADATA, RECORDING] {
+            rewrite(&bundle, &tampered, |name, data| (name != missing).then_some(data))?;
+            assert!(read_error(&tampered, &video_output).starts_with("missing"));
+        }
+
+        rewrite_manifest(&bundle, &tampered, |manifest| {
+            manifest.checksums.remove(RECORDING);
+        })?;
+        assert_eq!(
+            read_error(&tampered, &video_output),
+            format!("missing checksum of {RECORDING}")
+        );
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn video_id_is_a_filename() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("bundle_test_video_id_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let bundle = bundle(&dir)?;
+        let tampered = dir.join("tampered.zip");
+
+        rewrite_manifest(&bundle, &tampered, |manifest| {
+            manifest.video_id = "../../outside/game.mp4".to_owned();
+        })?;
+        assert_eq!(read_bundle(&tampered, &dir.join("imported.mp4"))?.video_id, "game.mp4");
+
+        rewrite_manifest(&bundle, &tampered, |manifest| manifest.video_id = "..".to_owned())?;
+        assert_eq!(read_error(&tampered, &dir.join("imported.mp4")), "invalid video id");
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use super::markers::{markers, MarkerKind};
+use crate::mp4::{Chapter, Tags};
+use crate::recorder::GameMetadata;
+
+/// a chapter for every marker of the recording, everything before the first marker is part of the "Start" chapter
+/// comments are left out because they can still change after the chapters got embedded
+pub fn chapters(metadata: &GameMetadata) -> Vec<Chapter> {
+    let start = Chapter {
+        start: 0.0,
+        title: "Start".to_owned(),
+    };
+    let markers = markers(metadata)
+        .into_iter()
+        .filter(|marker| marker.kind != MarkerKind::Comment)
+        .map(|marker| Chapter {
+            start: marker.time,
+            title: marker.text,
+        });
+    std::iter::once(start).chain(markers).collect()
+}
+
+/// title, match id, champion, queue and result of the game
+pub fn tags(metadata: &GameMetadata) -> Tags {
+    let result = metadata.result().as_str();
+
+    Tags {
+        title: Some(format!(
+            "{} - {} - {result}",
+            metadata.champion_name, metadata.queue.name
+        )),
+        comment: Some(format!("Match {}", metadata.match_id)),
+        custom: vec![
+            ("matchId".to_owned(), metadata.match_id.to_string()),
+            ("champion".to_owned(), metadata.champion_name.clone()),
+            ("queue".to_owned(), metadata.queue.name.clone()),
+            ("result".to_owned(), result.to_owned()),
+        ],
+    }
+}
@@ -0,0 +1,159 @@
+use riot_datatypes::{ParticipantId, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::recorder::{Event, GameMetadata};
+use crate::state::{DeathReviewWindow, TimeRange};
+
+/// part of a recording around a death of the player
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeathWindow {
+    pub video_id: String,
+    pub champion_name: String,
+    /// ingame time of the death in milliseconds
+    pub game_time: Timestamp,
+    /// in seconds of the video
+    pub range: TimeRange,
+    /// champion of the killer, `None` if the killer wasn't a champion (e.g. turret or minions) or is unknown
+    pub killer: Option<String>,
+    /// champions of the assisters (empty if unknown)
+    pub assisters: Vec<String>,
+}
+
+/// every death of the player in the recording, sorted by time
+/// the events only reference participant ids, so for metadata without participants (created before they got saved)
+/// the champions of killer and assisters stay unknown
+pub fn death_windows(video_id: &str, metadata: &GameMetadata, window: &DeathReviewWindow) -> Vec<DeathWindow> {
+    let champion = |participant_id: ParticipantId| {
+        metadata
+            .participants
+            .iter()
+            .find(|participant| participant.participant_id == Some(participant_id))
+            .and_then(|participant| participant.champion_name.clone())
+    };
+
+    let mut deaths = metadata
+        .events
+        .iter()
+        .filter_map(|game_event| match &game_event.event {
+            Event::ChampionKill {
+                victim_id,
+                killer_id,
+                assisting_participant_ids,
+                ..
+            } if *victim_id == metadata.participant_id => {
+                let death = game_event.timestamp as f64 / 1000.0 - metadata.ingame_time_rec_start_offset;
+                Some(DeathWindow {
+                    video_id: video_id.to_owned(),
+                    champion_name: metadata.champion_name.clone(),
+                    game_time: game_event.timestamp,
+                    range: TimeRange {
+                        start: (death - window.seconds_before as f64).max(0.0),
+                        end: death + window.seconds_after as f64,
+                    },
+                    killer: champion(*killer_id),
+                    assisters: assisting_participant_ids.iter().copied().filter_map(champion).collect(),
+                })
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    deaths.sort_by_key(|death| death.game_time);
+    deaths
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{bail, Result};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::recorder::migration::{self, Migrated};
+    use crate::recorder::MetadataFile;
+
+    const WINDOW: DeathReviewWindow = DeathReviewWindow {
+        seconds_before: 15,
+        seconds_after: 10,
+    };
+
+    fn kill(
+        timestamp: Timestamp,
+        victim_id: ParticipantId,
+        killer_id: ParticipantId,
+        assists: &[ParticipantId],
+    ) -> Value {
+        json!({
+            "ChampionKill": {
+                "victim_id": victim_id,
+                "killer_id": killer_id,
+                "assisting_participant_ids": assists,
+                "position": { "x": 7000, "y": 7200 }
+            },
+            "timestamp": timestamp
+        })
+    }
+
+    /// the player is participant 3 and the recording started 10 seconds into the game
+    fn metadata() -> Result<GameMetadata> {
+        let fixture = include_str!("../../fixtures/metadata/v7_metadata_with_rank.json");
+        let Migrated::Yes(MetadataFile::Metadata(mut metadata)) = migration::from_json(serde_json::from_str(fixture)?)?
+        else {
+            bail!("expected Metadata");
+        };
+        metadata.participant_id = 3;
+        metadata.ingame_time_rec_start_offset = 10.0;
+        metadata.events = serde_json::from_value(json!([
+            kill(300_000, 3, 0, &[7, 8]),
+            kill(200_000, 7, 3, &[]),
+            kill(5_000, 3, 7, &[8]),
+        ]))?;
+        metadata.participants = serde_json::from_value(json!([
+            { "participantId": 7, "championId": 238, "championName": "Zed" },
+            { "participantId": 8, "championId": 99, "championName": "Lux" },
+        ]))?;
+        Ok(metadata)
+    }
+
+    #[test]
+    fn windows_around_deaths() -> Result<()> {
+        let deaths = death_windows("game.mp4", &metadata()?, &WINDOW);
+        // only deaths of the player, sorted by time
+        assert_eq!(
+            deaths.iter().map(|death| death.game_time).collect::<Vec<_>>(),
+            [5_000, 300_000]
+        );
+
+        // the window gets clamped to the start of the video
+        assert_eq!((deaths[0].range.start, deaths[0].range.end), (0.0, 5.0));
+        assert_eq!((deaths[1].range.start, deaths[1].range.end), (275.0, 300.0));
+        assert_eq!(deaths[0].video_id, "game.mp4");
+        assert_eq!(deaths[0].champion_name, "Ahri");
+        Ok(())
+    }
+
+    #[test]
+    fn killers_and_assisters() -> Result<()> {
+        let deaths = death_windows("game.mp4", &metadata()?, &WINDOW);
+        assert_eq!(deaths[0].killer.as_deref(), Some("Zed"));
+        assert_eq!(deaths[0].assisters, ["Lux"]);
+        // killed by minions
+        assert_eq!(deaths[1].killer, None);
+        assert_eq!(deaths[1].assisters, ["Zed", "Lux"]);
+        Ok(())
+    }
+
+    #[test]
+    fn without_participants() -> Result<()> {
+        let mut metadata = metadata()?;
+        metadata.participants.clear();
+
+        let deaths = death_windows("game.mp4", &metadata, &WINDOW);
+        assert_eq!(deaths.len(), 2);
+        assert!(deaths
+            .iter()
+            .all(|death| death.killer.is_none() && death.assisters.is_empty()));
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::Session;
+
 #[allow(clippy::enum_variant_names)]
 #[cfg_attr(test, derive(specta::Type, tauri_specta::Event))]
 #[derive(Debug, Clone, strum_macros::IntoStaticStr, Serialize, Deserialize)]
@@ -8,6 +10,7 @@ pub enum AppEvent {
     RecordingsChanged { payload: () },
     MetadataChanged { payload: Vec<String> },
     MarkerflagsChanged { payload: () },
+    SessionUpdated { payload: Session },
 }
 
 pub trait EventManager {
@@ -30,6 +33,9 @@ impl EventManager for tauri::AppHandle {
             MarkerflagsChanged { payload } => {
                 self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
             }
+            SessionUpdated { payload } => {
+                self.emit_to(EventTarget::webview_window(AppWindow::Main), (&event).into(), payload)?
+            }
         };
 
         Ok(())
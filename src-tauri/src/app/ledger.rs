@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::recorder::GameResult;
+use crate::state::RecordingSummary;
+use crate::util;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerColumn {
+    VideoId,
+    Date,
+    MatchId,
+    Queue,
+    Champion,
+    Result,
+    Kills,
+    Deaths,
+    Assists,
+    Kda,
+    Cs,
+    CsPerMinute,
+    VisionScore,
+    /// in seconds
+    Duration,
+    Patch,
+    LpDelta,
+}
+
+impl LedgerColumn {
+    pub const ALL: [LedgerColumn; 16] = [
+        LedgerColumn::VideoId,
+        LedgerColumn::Date,
+        LedgerColumn::MatchId,
+        LedgerColumn::Queue,
+        LedgerColumn::Champion,
+        LedgerColumn::Result,
+        LedgerColumn::Kills,
+        LedgerColumn::Deaths,
+        LedgerColumn::Assists,
+        LedgerColumn::Kda,
+        LedgerColumn::Cs,
+        LedgerColumn::CsPerMinute,
+        LedgerColumn::VisionScore,
+        LedgerColumn::Duration,
+        LedgerColumn::Patch,
+        LedgerColumn::LpDelta,
+    ];
+
+    /// header of the column in CSV files and key in JSON Lines files
+    fn name(&self) -> &'static str {
+        match self {
+            LedgerColumn::VideoId => "videoId",
+            LedgerColumn::Date => "date",
+            LedgerColumn::MatchId => "matchId",
+            LedgerColumn::Queue => "queue",
+            LedgerColumn::Champion => "champion",
+            LedgerColumn::Result => "result",
+            LedgerColumn::Kills => "kills",
+            LedgerColumn::Deaths => "deaths",
+            LedgerColumn::Assists => "assists",
+            LedgerColumn::Kda => "kda",
+            LedgerColumn::Cs => "cs",
+            LedgerColumn::CsPerMinute => "csPerMinute",
+            LedgerColumn::VisionScore => "visionScore",
+            LedgerColumn::Duration => "duration",
+            LedgerColumn::Patch => "patch",
+            LedgerColumn::LpDelta => "lpDelta",
+        }
+    }
+
+    /// `Null` if the value is unknown (e.g. recordings without game data)
+    fn value(&self, summary: &RecordingSummary) -> Value {
+        let stats = summary.stats.as_ref();
+        let cs = stats.map(|stats| stats.total_minions_killed + stats.neutral_minions_killed);
+        let minutes = summary
+            .game_duration
+            .map(|seconds| seconds as f64 / 60.0)
+            .filter(|&minutes| minutes > 0.0);
+        let rounded = |value: f64| (value * 100.0).round() / 100.0;
+
+        match self {
+            LedgerColumn::VideoId => summary.video_id.clone().into(),
+            LedgerColumn::Date => chrono::DateTime::from_timestamp_millis(summary.timestamp)
+                .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                .into(),
+            LedgerColumn::MatchId => summary.match_id.as_ref().map(ToString::to_string).into(),
+            LedgerColumn::Queue => summary.queue.as_ref().map(|queue| queue.name.clone()).into(),
+            LedgerColumn::Champion => summary.champion_name.clone().into(),
+            LedgerColumn::Result => summary.result.map(GameResult::as_str).into(),
+            LedgerColumn::Kills => stats.map(|stats| stats.kills).into(),
+            LedgerColumn::Deaths => stats.map(|stats| stats.deaths).into(),
+            LedgerColumn::Assists => stats.map(|stats| stats.assists).into(),
+            LedgerColumn::Kda => stats
+                .map(|stats| rounded((stats.kills + stats.assists) as f64 / stats.deaths.max(1) as f64))
+                .into(),
+            LedgerColumn::Cs => cs.into(),
+            LedgerColumn::CsPerMinute => cs.zip(minutes).map(|(cs, minutes)| rounded(cs as f64 / minutes)).into(),
+            LedgerColumn::VisionScore => stats.map(|stats| rounded(stats.vision_score)).into(),
+            LedgerColumn::Duration => summary.game_duration.into(),
+            LedgerColumn::Patch => summary.patch.clone().into(),
+            LedgerColumn::LpDelta => summary.lp_delta.into(),
+        }
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerFormat {
+    Csv,
+    JsonLines,
+}
+
+impl LedgerFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LedgerFormat::Csv => "csv",
+            LedgerFormat::JsonLines => "jsonl",
+        }
+    }
+}
+
+/// one row per recording with the selected columns (all columns if `columns` is empty)
+/// clips and highlight reels are left out because they are not games of their own
+pub fn ledger(recordings: &[RecordingSummary], columns: &[LedgerColumn], format: LedgerFormat) -> String {
+    let columns = if columns.is_empty() {
+        &LedgerColumn::ALL[..]
+    } else {
+        columns
+    };
+    let rows = recordings
+        .iter()
+        .filter(|summary| !summary.clip)
+        .map(|summary| columns.iter().map(|column| column.value(summary)));
+
+    let mut out = String::new();
+    match format {
+        LedgerFormat::Csv => {
+            let header = columns.iter().map(LedgerColumn::name).collect::<Vec<_>>();
+            out.push_str(&header.join(","));
+            out.push('\n');
+            for row in rows {
+                out.push_str(&row.map(|value| csv_field(&value)).collect::<Vec<_>>().join(","));
+                out.push('\n');
+            }
+        }
+        LedgerFormat::JsonLines => {
+            for row in rows {
+                let object = columns
+                    .iter()
+                    .map(|column| column.name().to_owned())
+                    .zip(row)
+                    .collect::<Map<_, _>>();
+                out.push_str(&Value::Object(object).to_string());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => util::csv_field(text).into_owned(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use riot_datatypes::{lcu, MatchId};
+    use serde_json::json;
+
+    use super::*;
+
+    fn game(video_id: &str) -> RecordingSummary {
+        let mut summary = RecordingSummary::without_metadata(video_id, 0);
+        summary.match_id = Some(MatchId {
+            game_id: 7012345678,
+            platform_id: "EUW1".into(),
+        });
+        summary.champion_name = Some("Ahri".into());
+        summary.result = Some(GameResult::Win);
+        summary.game_duration = Some(20 * 60);
+        summary.stats = Some(lcu::Stats {
+            kills: 3,
+            deaths: 0,
+            assists: 5,
+            largest_multi_kill: 1,
+            neutral_minions_killed: 10,
+            neutral_minions_killed_enemy_jungle: 0,
+            neutral_minions_killed_team_jungle: 10,
+            total_minions_killed: 150,
+            vision_score: 12.346,
+            vision_wards_bought_in_game: 0,
+            wards_placed: 0,
+            wards_killed: 0,
+            game_ended_in_early_surrender: false,
+            game_ended_in_surrender: false,
+            win: true,
+        });
+        summary
+    }
+
+    #[test]
+    fn selected_columns() {
+        let columns = [
+            LedgerColumn::VideoId,
+            LedgerColumn::MatchId,
+            LedgerColumn::Result,
+            LedgerColumn::Kda,
+            LedgerColumn::Cs,
+            LedgerColumn::CsPerMinute,
+            LedgerColumn::VisionScore,
+        ];
+        assert_eq!(
+            ledger(&[game("game.mp4")], &columns, LedgerFormat::Csv),
+            "videoId,matchId,result,kda,cs,csPerMinute,visionScore\ngame.mp4,EUW1_7012345678,Win,8.0,160,8.0,12.35\n"
+        );
+    }
+
+    #[test]
+    fn all_columns_by_default() {
+        let csv = ledger(&[game("game.mp4")], &[], LedgerFormat::Csv);
+        let header = LedgerColumn::ALL.iter().map(LedgerColumn::name).collect::<Vec<_>>();
+        assert_eq!(csv.lines().next(), Some(header.join(",").as_str()));
+        assert_eq!(
+            csv.lines().nth(1).map(|row| row.split(',').count()),
+            Some(LedgerColumn::ALL.len())
+        );
+    }
+
+    #[test]
+    fn csv_cells() {
+        // unknown values are empty cells
+        let no_data = RecordingSummary::without_metadata("no data.mp4", 0);
+        // text with a separator or quotes gets quoted
+        let mut quoted = game("quoted.mp4");
+        quoted.champion_name = Some("Nunu, \"Willump\"".into());
+
+        let columns = [LedgerColumn::VideoId, LedgerColumn::Champion, LedgerColumn::Kills];
+        assert_eq!(
+            ledger(&[no_data, quoted], &columns, LedgerFormat::Csv),
+            "videoId,champion,kills\nno data.mp4,,\nquoted.mp4,\"Nunu, \"\"Willump\"\"\",3\n"
+        );
+    }
+
+    #[test]
+    fn json_lines() -> serde_json::Result<()> {
+        let no_data = RecordingSummary::without_metadata("no_data.mp4", 0);
+        let columns = [LedgerColumn::VideoId, LedgerColumn::Champion, LedgerColumn::LpDelta];
+        let jsonl = ledger(&[game("game.mp4"), no_data], &columns, LedgerFormat::JsonLines);
+
+        let rows = jsonl
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<serde_json::Result<Vec<Value>>>()?;
+        assert_eq!(
+            rows,
+            [
+                json!({ "videoId": "game.mp4", "champion": "Ahri", "lpDelta": null }),
+                json!({ "videoId": "no_data.mp4", "champion": null, "lpDelta": null }),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn without_clips() {
+        let mut clip = game("clip.mp4");
+        clip.clip = true;
+        let columns = [LedgerColumn::VideoId];
+        assert_eq!(
+            ledger(&[clip.clone(), game("game.mp4")], &columns, LedgerFormat::Csv),
+            "videoId\ngame.mp4\n"
+        );
+        assert_eq!(ledger(&[clip], &columns, LedgerFormat::JsonLines), "");
+    }
+}
@@ -10,9 +10,9 @@ use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_log::{Target, TargetKind};
 use tauri_plugin_updater::UpdaterExt;
 
-use super::{RecordingManager, SystemTrayManager};
+use super::{AppEvent, EventManager, RecordingManager, SystemTrayManager};
 use crate::constants::{APP_NAME, CURRENT_VERSION};
-use crate::state::{SettingsFile, SettingsWrapper};
+use crate::state::{Collections, RecordingsIndex, SettingsFile, SettingsWrapper, StaticData};
 use crate::{filewatcher, recorder::LeagueRecorder};
 
 pub trait AppManager {
@@ -86,19 +86,42 @@ impl AppManager for AppHandle {
 
         self.update_hightlight_hotkey();
 
+        // load the cached list of recordings (has to be managed before the filewatcher starts)
+        let index_file = self.path().app_cache_dir()?.join("recordings_index.json");
+        self.manage(RecordingsIndex::new(index_file));
+
         // start watching recordings folder for changes
         let recordings_path = settings.get_recordings_path();
+        self.manage(Collections::new(&recordings_path));
         log::info!("recordings folder: {recordings_path:?}");
+        // temporary and backup files of file operations that got interrupted when the app crashed last time
+        // (before anything else can start a new file operation)
+        self.remove_leftover_files();
         filewatcher::replace(self, &recordings_path);
 
+        // load cached champion/queue/... names so ids can be resolved without the LCU API
+        let static_data_dir = self.path().app_cache_dir()?.join("static_data");
+        self.manage(StaticData::new(static_data_dir));
+
         // start checking for LoL games to record
         self.manage(LeagueRecorder::new(self.clone()));
 
-        // cleanup recordings if they are too old or the total size of the recordings gets too big
-        // this only happens if 'maxRecordingAge' or 'maxRecordingsSize' is configured in the settings
         async_runtime::spawn_blocking({
             let app_handle = self.clone();
-            move || app_handle.cleanup_recordings()
+            move || {
+                // pick up recordings that changed while the app wasn't running
+                app_handle.state::<RecordingsIndex>().sync(app_handle.get_recordings());
+                if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
+                    log::error!("failed to emit 'recordings_changed' event: {e}");
+                }
+
+                // games that weren't in the match history yet when they got recorded
+                app_handle.process_deferred_recordings();
+
+                // cleanup recordings if they are too old or the total size of the recordings gets too big
+                // this only happens if 'maxRecordingAge' or 'maxRecordingsSize' is configured in the settings
+                app_handle.cleanup_recordings()
+            }
         });
 
         Ok(())
@@ -0,0 +1,259 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::markers::{Marker, MarkerKind};
+use crate::util;
+
+/// how long a marker is shown as a subtitle (in seconds)
+const SUBTITLE_DURATION: f64 = 4.0;
+
+/// sidecar file formats the markers of a recording can be exported as
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerFormat {
+    WebVtt,
+    Srt,
+    Csv,
+    /// CMX3600 EDL with DaVinci Resolve marker comments
+    Edl,
+    /// Final Cut Pro 7 XML sequence with markers (e.g. for Adobe Premiere)
+    Xml,
+}
+
+impl MarkerFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MarkerFormat::WebVtt => "vtt",
+            MarkerFormat::Srt => "srt",
+            MarkerFormat::Csv => "csv",
+            MarkerFormat::Edl => "edl",
+            MarkerFormat::Xml => "xml",
+        }
+    }
+
+    /// the timecodes of editor formats are in frames of the video
+    pub fn needs_frame_rate(&self) -> bool {
+        matches!(self, MarkerFormat::Edl | MarkerFormat::Xml)
+    }
+}
+
+/// `frame_rate` is only used by formats that `needs_frame_rate()`
+pub fn export_markers(markers: &[Marker], format: MarkerFormat, name: &str, frame_rate: f64) -> String {
+    match format {
+        MarkerFormat::WebVtt => web_vtt(markers),
+        MarkerFormat::Srt => srt(markers),
+        MarkerFormat::Csv => csv(markers),
+        MarkerFormat::Edl => edl(markers, name, frame_rate),
+        MarkerFormat::Xml => xml(markers, name, frame_rate),
+    }
+}
+
+fn web_vtt(markers: &[Marker]) -> String {
+    let mut out = String::from("WEBVTT\n");
+    for marker in markers {
+        _ = write!(
+            out,
+            "\n{} --> {}\n{}\n",
+            timestamp(marker.time, '.'),
+            timestamp(marker.time + SUBTITLE_DURATION, '.'),
+            single_line(&marker.text)
+        );
+    }
+    out
+}
+
+fn srt(markers: &[Marker]) -> String {
+    let mut out = String::new();
+    for (i, marker) in markers.iter().enumerate() {
+        _ = write!(
+            out,
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            timestamp(marker.time, ','),
+            timestamp(marker.time + SUBTITLE_DURATION, ','),
+            single_line(&marker.text)
+        );
+    }
+    out
+}
+
+fn csv(markers: &[Marker]) -> String {
+    let mut out = String::from("seconds,timestamp,type,text\n");
+    for marker in markers {
+        _ = writeln!(
+            out,
+            "{:.3},{},{:?},{}",
+            marker.time,
+            timestamp(marker.time, '.'),
+            marker.kind,
+            util::csv_field(&marker.text)
+        );
+    }
+    out
+}
+
+/// every marker is a one frame event, DaVinci Resolve imports the `|M:` comments as timeline markers
+fn edl(markers: &[Marker], name: &str, frame_rate: f64) -> String {
+    let mut out = format!("TITLE: {name}\nFCM: NON-DROP FRAME\n");
+    for (i, marker) in markers.iter().enumerate() {
+        let frame = frame(marker.time, frame_rate);
+        let start = timecode(frame, frame_rate);
+        let end = timecode(frame + 1, frame_rate);
+        let color = match marker.kind {
+            MarkerKind::Kill => "ResolveColorGreen",
+            MarkerKind::Death => "ResolveColorRed",
+            MarkerKind::Assist => "ResolveColorCyan",
+            MarkerKind::Objective => "ResolveColorPurple",
+            MarkerKind::Highlight => "ResolveColorYellow",
+            MarkerKind::Comment => "ResolveColorBlue",
+        };
+        let text = single_line(&marker.text);
+        _ = write!(
+            out,
+            "\n{:03}  001      V     C        {start} {end} {start} {end}\n |C:{color} |M:{text} |D:1\n",
+            i + 1
+        );
+    }
+    out
+}
+
+fn xml(markers: &[Marker], name: &str, frame_rate: f64) -> String {
+    let timebase = frame_rate.round().max(1.0) as u64;
+    let ntsc = if (frame_rate - timebase as f64).abs() > 0.01 {
+        "TRUE"
+    } else {
+        "FALSE"
+    };
+    let duration = markers.last().map_or(0, |marker| frame(marker.time, frame_rate) + 1);
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE xmeml>\n<xmeml version=\"4\">\n");
+    _ = writeln!(out, "  <sequence>");
+    _ = writeln!(out, "    <name>{}</name>", escape_xml(name));
+    _ = writeln!(out, "    <duration>{duration}</duration>");
+    _ = writeln!(
+        out,
+        "    <rate><timebase>{timebase}</timebase><ntsc>{ntsc}</ntsc></rate>"
+    );
+    _ = writeln!(out, "    <media><video><track></track></video></media>");
+    for marker in markers {
+        _ = writeln!(
+            out,
+            "    <marker><name>{}</name><comment>{:?}</comment><in>{}</in><out>-1</out></marker>",
+            escape_xml(&marker.text),
+            marker.kind,
+            frame(marker.time, frame_rate)
+        );
+    }
+    out.push_str("  </sequence>\n</xmeml>\n");
+    out
+}
+
+/// `HH:MM:SS.mmm` with `separator` in front of the milliseconds
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+fn frame(seconds: f64, frame_rate: f64) -> u64 {
+    (seconds.max(0.0) * frame_rate).round() as u64
+}
+
+/// non drop frame `HH:MM:SS:FF` timecode
+fn timecode(frame: u64, frame_rate: f64) -> String {
+    let timebase = frame_rate.round().max(1.0) as u64;
+    let seconds = frame / timebase;
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        frame % timebase
+    )
+}
+
+/// an empty line ends a subtitle and a line break ends an EDL comment
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(time: f64, kind: MarkerKind, text: &str) -> Marker {
+        Marker {
+            time,
+            kind,
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(timestamp(0.0, '.'), "00:00:00.000");
+        assert_eq!(timestamp(3723.4567, ','), "01:02:03,457");
+        // markers from before the recording started
+        assert_eq!(timestamp(-1.0, '.'), "00:00:00.000");
+    }
+
+    #[test]
+    fn timecodes() {
+        assert_eq!(frame(1.5, 60.0), 90);
+        assert_eq!(timecode(90, 60.0), "00:00:01:30");
+        assert_eq!(timecode(frame(3723.5, 30.0), 30.0), "01:02:03:15");
+        // NTSC frame rates count with the rounded timebase (non drop frame)
+        assert_eq!(timecode(frame(100.0, 29.97), 29.97), "00:01:39:27");
+    }
+
+    #[test]
+    fn csv_quoting() {
+        let markers = [marker(61.25, MarkerKind::Comment, "said \"gg\", then left")];
+        assert_eq!(
+            csv(&markers),
+            "seconds,timestamp,type,text\n61.250,00:01:01.250,Comment,\"said \"\"gg\"\", then left\"\n"
+        );
+    }
+
+    #[test]
+    fn edl_lines() {
+        let markers = [
+            marker(1.0, MarkerKind::Kill, "Killed Ahri"),
+            marker(2.5, MarkerKind::Comment, "first line\nsecond line"),
+        ];
+        assert_eq!(
+            edl(&markers, "game.mp4", 30.0),
+            "TITLE: game.mp4\nFCM: NON-DROP FRAME\n\
+             \n001  001      V     C        00:00:01:00 00:00:01:01 00:00:01:00 00:00:01:01\n\
+             \x20|C:ResolveColorGreen |M:Killed Ahri |D:1\n\
+             \n002  001      V     C        00:00:02:15 00:00:02:16 00:00:02:15 00:00:02:16\n\
+             \x20|C:ResolveColorBlue |M:first line second line |D:1\n"
+        );
+    }
+
+    #[test]
+    fn xml_escaping() {
+        let markers = [marker(2.0, MarkerKind::Comment, "<b>\"Baron\" & Dragon</b>")];
+        let xml = xml(&markers, "A & B", 60.0);
+
+        assert!(xml.contains("<name>A &amp; B</name>"));
+        assert!(xml.contains(
+            "<marker><name>&lt;b&gt;&quot;Baron&quot; &amp; Dragon&lt;/b&gt;</name><comment>Comment</comment><in>120</in>"
+        ));
+        assert!(xml.contains("<duration>121</duration>"));
+        assert!(xml.contains("<timebase>60</timebase><ntsc>FALSE</ntsc>"));
+    }
+}
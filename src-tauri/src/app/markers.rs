@@ -0,0 +1,128 @@
+use riot_datatypes::{BuildingType, DragonType, MonsterType, ParticipantId, Team};
+
+use crate::recorder::{Event, GameMetadata};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    Kill,
+    Death,
+    Assist,
+    Objective,
+    Highlight,
+    Comment,
+}
+
+/// an event of the game, a highlight or a comment at a point in time of the video
+#[derive(Debug, Clone)]
+pub struct Marker {
+    /// in seconds of the video
+    pub time: f64,
+    pub kind: MarkerKind,
+    pub text: String,
+}
+
+/// the kills, deaths and assists of the player, all objectives, the highlights and the comments of the recording
+/// sorted by time, markers from before the recording started are left out
+pub fn markers(metadata: &GameMetadata) -> Vec<Marker> {
+    let participant = |participant_id: ParticipantId| {
+        metadata
+            .participants
+            .iter()
+            .find(|participant| participant.participant_id == Some(participant_id))
+    };
+    let champion = |participant_id: ParticipantId| {
+        participant(participant_id)
+            .and_then(|participant| participant.champion_name.clone())
+            .unwrap_or_else(|| "an enemy".to_owned())
+    };
+    let own_team = metadata.team();
+    let team_name = |team: Option<&Team>| match (team, own_team) {
+        (Some(team), Some(own_team)) if team == own_team => "Your team",
+        (Some(_), Some(_)) => "The enemy team",
+        _ => "A team",
+    };
+    // ingame times are in milliseconds
+    let video_time = |game_time: f64| game_time / 1000.0 - metadata.ingame_time_rec_start_offset;
+
+    let events = metadata.events.iter().filter_map(|game_event| {
+        let (kind, text) = match &game_event.event {
+            Event::ChampionKill {
+                victim_id,
+                killer_id,
+                assisting_participant_ids,
+                ..
+            } => {
+                if *killer_id == metadata.participant_id {
+                    (MarkerKind::Kill, format!("You killed {}", champion(*victim_id)))
+                } else if *victim_id == metadata.participant_id {
+                    match participant(*killer_id).and_then(|killer| killer.champion_name.as_deref()) {
+                        Some(killer) => (MarkerKind::Death, format!("{killer} killed you")),
+                        None => (MarkerKind::Death, "You died".to_owned()),
+                    }
+                } else if assisting_participant_ids.contains(&metadata.participant_id) {
+                    (MarkerKind::Assist, format!("You helped kill {}", champion(*victim_id)))
+                } else {
+                    return None;
+                }
+            }
+            Event::BuildingKill { team_id, building_type, .. } => {
+                let building = match building_type {
+                    BuildingType::TowerBuilding { .. } => "turret",
+                    BuildingType::InhibitorBuilding { .. } => "inhibitor",
+                };
+                // `team_id` is the team that lost the building
+                let text = match own_team {
+                    Some(own_team) if own_team == team_id => format!("The enemy team destroyed a {building}"),
+                    Some(_) => format!("Your team destroyed a {building}"),
+                    None => format!("A {building} was destroyed"),
+                };
+                (MarkerKind::Objective, text)
+            }
+            Event::EliteMonsterKill { killer_id, monster_type, .. } => {
+                let monster = match monster_type {
+                    MonsterType::Horde => "Voidgrubs",
+                    MonsterType::Riftherald => "the Rift Herald",
+                    MonsterType::Atakhan => "Atakhan",
+                    MonsterType::BaronNashor => "Baron Nashor",
+                    MonsterType::Dragon { dragon_type } => match dragon_type {
+                        DragonType::FireDragon => "the Infernal Drake",
+                        DragonType::EarthDragon => "the Mountain Drake",
+                        DragonType::WaterDragon => "the Ocean Drake",
+                        DragonType::AirDragon => "the Cloud Drake",
+                        DragonType::HextechDragon => "the Hextech Drake",
+                        DragonType::ChemtechDragon => "the Chemtech Drake",
+                        DragonType::ElderDragon => "the Elder Dragon",
+                    },
+                };
+                let team = participant(*killer_id).and_then(|killer| killer.team.as_ref());
+                (MarkerKind::Objective, format!("{} killed {monster}", team_name(team)))
+            }
+        };
+
+        Some(Marker {
+            time: video_time(game_event.timestamp as f64),
+            kind,
+            text,
+        })
+    });
+
+    let highlights = metadata.highlights.iter().map(|&highlight| Marker {
+        time: video_time(highlight),
+        kind: MarkerKind::Highlight,
+        text: "Highlight".to_owned(),
+    });
+
+    let comments = metadata.annotations.comments.iter().map(|comment| Marker {
+        time: video_time(comment.game_time),
+        kind: MarkerKind::Comment,
+        text: comment.text.clone(),
+    });
+
+    let mut markers = events
+        .chain(highlights)
+        .chain(comments)
+        .filter(|marker| marker.time >= 0.0)
+        .collect::<Vec<_>>();
+    markers.sort_by(|a, b| a.time.total_cmp(&b.time));
+    markers
+}
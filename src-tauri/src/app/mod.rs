@@ -1,17 +1,40 @@
+mod anonymize;
+mod bundle;
+mod chapters;
+mod deaths;
 mod event;
+mod ledger;
 mod manager;
+mod marker_export;
+mod markers;
+mod protocol;
 mod recordings;
+mod replays;
+mod report;
+mod sessions;
+mod statistics;
 mod system_tray;
+mod transaction;
 mod window;
 
+pub use anonymize::{anonymize_metadata, Pseudonyms};
+pub use deaths::{death_windows, DeathWindow};
 pub use event::{AppEvent, EventManager};
+pub use ledger::{LedgerColumn, LedgerFormat};
 pub use manager::AppManager;
-pub use recordings::{action, RecordingManager};
+pub use marker_export::MarkerFormat;
+pub use protocol::{recording_protocol, RECORDING_PROTOCOL};
+pub use recordings::{account_folder_name, account_id, action, free_video_id, RecordingManager};
+pub use replays::{scan_replays, ReplayFile};
+pub use report::ReportFormat;
+pub use sessions::{account_sessions, sessions, Session};
+pub use statistics::{statistics, GroupStatistics, Statistics, StatisticsFilter};
 pub use system_tray::SystemTrayManager;
+pub use transaction::write_atomic;
 pub use window::{AppWindow, WindowManager};
 
 pub fn process_app_event(app_handle: &tauri::AppHandle, event: tauri::RunEvent) {
-    use crate::state::Shutdown;
+    use crate::state::{RecordingsIndex, Shutdown};
     use tauri::{Manager, RunEvent, WindowEvent};
     use window::WindowManager;
 
@@ -32,6 +55,12 @@ pub fn process_app_event(app_handle: &tauri::AppHandle, event: tauri::RunEvent)
                 api.prevent_exit();
             }
         }
+        RunEvent::Exit => {
+            // don't lose the changes of the last filewatcher events
+            if let Some(recordings_index) = app_handle.try_state::<RecordingsIndex>() {
+                recordings_index.flush();
+            }
+        }
         _ => {}
     }
 }
@@ -0,0 +1,104 @@
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use percent_encoding::percent_decode_str;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{async_runtime, AppHandle, UriSchemeContext, UriSchemeResponder, Wry};
+
+use super::RecordingManager;
+
+/// plays recordings by their video id (e.g. `recording://localhost/2025-01-01_12-00.mp4`) instead of their path
+/// in streamer mode the frontend doesn't get the paths of the recordings because the account subfolder contains the
+/// Riot ID
+pub const RECORDING_PROTOCOL: &str = "recording";
+
+/// max size of a response, the video player requests the rest of the recording with further range requests
+const MAX_CHUNK_SIZE: u64 = 1024 * 1024;
+
+pub fn recording_protocol(ctx: UriSchemeContext<'_, Wry>, request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let app_handle = ctx.app_handle().clone();
+    async_runtime::spawn_blocking(move || {
+        let response = recording_response(&app_handle, &request).unwrap_or_else(|e| {
+            log::warn!("failed to load recording {}: {e:#}", request.uri());
+            status_response(StatusCode::NOT_FOUND)
+        });
+        responder.respond(response);
+    });
+}
+
+fn recording_response(app_handle: &AppHandle, request: &Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+    let video_id = percent_decode_str(request.uri().path().trim_start_matches('/'))
+        .decode_utf8()?
+        .into_owned();
+    // only recordings and no other files
+    let is_file_name = Path::new(&video_id).file_name() == Some(OsStr::new(&video_id));
+    if !is_file_name || !video_id.ends_with(".mp4") {
+        bail!("invalid video id");
+    }
+
+    let mut file = File::open(app_handle.get_recording_path(&video_id))?;
+    let len = file.metadata()?.len();
+
+    // the video player always sends a range, without one the response starts at the beginning of the recording
+    let range = match request.headers().get(header::RANGE) {
+        Some(range) => parse_range(range.to_str()?, len),
+        None => parse_range("bytes=0-", len),
+    };
+    let Some((start, end)) = range else { return Ok(status_response(StatusCode::RANGE_NOT_SATISFIABLE)) };
+    let end = end.min(start + MAX_CHUNK_SIZE - 1);
+
+    let mut data = Vec::with_capacity((end - start + 1) as usize);
+    file.seek(SeekFrom::Start(start))?;
+    file.take(end - start + 1).read_to_end(&mut data)?;
+
+    Ok(Response::builder()
+        .status(StatusCode::PARTIAL_CONTENT)
+        .header(header::CONTENT_TYPE, "video/mp4")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}"))
+        .header(header::CONTENT_LENGTH, data.len())
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(data)?)
+}
+
+/// first and last byte (inclusive) of a single range `Range` header (e.g. `bytes=0-`, `bytes=100-199` or `bytes=-500`)
+/// `None` if the range isn't inside of the `len` bytes of the file
+fn parse_range(range: &str, len: u64) -> Option<(u64, u64)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let last = len.checked_sub(1)?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => (len.saturating_sub(suffix.parse().ok()?), last),
+        (start, "") => (start.parse().ok()?, last),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(last)),
+    };
+    (start <= end).then_some((start, end))
+}
+
+fn status_response(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("bytes=0-", 1000), Some((0, 999)));
+        assert_eq!(parse_range("bytes=100-199", 1000), Some((100, 199)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        // the end gets clamped to the end of the file
+        assert_eq!(parse_range("bytes=900-2000", 1000), Some((900, 999)));
+
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=0-", 0), None);
+        assert_eq!(parse_range("bytes=200-100", 1000), None);
+        assert_eq!(parse_range("bytes=0-99,200-299", 1000), None);
+        assert_eq!(parse_range("items=0-", 1000), None);
+    }
+}
@@ -1,44 +1,157 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use riot_datatypes::lcu::Player;
 use tauri::{AppHandle, Manager};
 
-use crate::state::{CurrentlyRecording, SettingsWrapper};
+use super::transaction;
+use crate::state::{CurrentlyRecording, RecordingsIndex, SettingsWrapper};
 use crate::util;
 
 pub trait RecordingManager {
     fn get_recordings(&self) -> Vec<PathBuf>;
+    fn get_recording_path(&self, video_id: &str) -> PathBuf;
+
+    fn process_deferred_recordings(&self);
 
     fn cleanup_recordings(&self);
     fn cleanup_recordings_by_size(&self);
     fn cleanup_recordings_by_age(&self);
+
+    fn remove_leftover_files(&self);
+}
+
+/// name of the subfolder of the recordings folder that contains the recordings of an account
+pub fn account_folder_name(player: &Player) -> String {
+    sanitize_file_name(&format!("{}#{}", player.game_name, player.tag_line))
+}
+
+/// replaces the characters that aren't allowed in file names (on Windows)
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// video ids are the file names of the recordings so they have to be unique across the recordings folder and all of
+/// its subfolders (account folders, clips), a metadata file with the same name counts as taken as well
+fn is_video_id_taken(recordings_path: &Path, video_id: &str) -> bool {
+    recording_folders(recordings_path)
+        .map(|folder| folder.join(video_id))
+        .any(|path| path.exists() || path.with_extension("json").exists())
+}
+
+/// `{name}.mp4` or `{name} (n).mp4` with the lowest `n` that isn't taken in any folder of `recordings_path`
+pub fn free_video_id(recordings_path: &Path, name: &str) -> Result<String> {
+    (1..)
+        .map(|n| match n {
+            1 => format!("{name}.mp4"),
+            n => format!("{name} ({n}).mp4"),
+        })
+        .find(|video_id| !is_video_id_taken(recordings_path, video_id))
+        .context("no free filename")
+}
+
+/// the recordings folder and its subfolders that aren't hidden (account folders, clips)
+fn recording_folders(recordings_path: &Path) -> impl Iterator<Item = PathBuf> {
+    let subfolders = recordings_path
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            path.is_dir() && !hidden
+        });
+    std::iter::once(recordings_path.to_owned()).chain(subfolders)
+}
+
+/// identifies an account across Riot ID changes (falls back to the Riot ID for old metadata without PUUID)
+pub fn account_id(player: &Player) -> String {
+    match player.puuid() {
+        Some(puuid) => puuid.to_owned(),
+        None => format!("{}#{}", player.game_name, player.tag_line),
+    }
 }
 
 impl RecordingManager for AppHandle {
     fn get_recordings(&self) -> Vec<PathBuf> {
-        // get all mp4 files in ~/Videos/%folder-name%
-        let mut recordings = Vec::<PathBuf>::new();
-        let Ok(read_dir) = self.state::<SettingsWrapper>().get_recordings_path().read_dir() else {
-            return vec![];
-        };
+        // get all mp4 files in ~/Videos/%folder-name% and the account subfolders in it
+        fn mp4_files(dir: &Path, currently_recording: Option<&PathBuf>, recordings: &mut Vec<PathBuf>) {
+            let Ok(read_dir) = dir.read_dir() else { return };
 
+            for entry in read_dir.flatten() {
+                let path = entry.path();
+
+                if !path.is_file() || Some(&path) == currently_recording {
+                    continue;
+                }
+
+                if let Some(ext) = path.extension() {
+                    if ext == "mp4" {
+                        recordings.push(path);
+                    }
+                }
+            }
+        }
+
+        let recordings_path = self.state::<SettingsWrapper>().get_recordings_path();
         let currently_recording = self.state::<CurrentlyRecording>().get();
 
-        for entry in read_dir.flatten() {
-            let path = entry.path();
+        let mut recordings = Vec::<PathBuf>::new();
+        for folder in recording_folders(&recordings_path) {
+            mp4_files(&folder, currently_recording.as_ref(), &mut recordings);
+        }
+        recordings
+    }
+
+    fn get_recording_path(&self, video_id: &str) -> PathBuf {
+        // the recording might be in one of the account subfolders
+        // the index has no recording for names that exist in several folders
+        if let Some(summary) = self.state::<RecordingsIndex>().get(video_id) {
+            if summary.path.is_file() {
+                return summary.path;
+            }
+        }
 
-            if !path.is_file() || Some(&path) == currently_recording.as_ref() {
-                continue;
+        // not indexed (yet)
+        // if several folders contain a recording with this name there is no way to tell which one is meant
+        // => return an empty path so that the caller fails instead of using the wrong one
+        let mut matches = self
+            .get_recordings()
+            .into_iter()
+            .filter(|path| path.file_name().is_some_and(|name| name == video_id));
+        match (matches.next(), matches.next()) {
+            (Some(path), None) => path,
+            (Some(_), Some(_)) => {
+                log::warn!("found several recordings named {video_id}");
+                PathBuf::new()
             }
+            (None, _) => self.state::<SettingsWrapper>().get_recordings_path().join(video_id),
+        }
+    }
 
-            if let Some(ext) = path.extension() {
-                if ext == "mp4" {
-                    recordings.push(path);
-                }
+    /// collects the game data of all recordings whose game wasn't in the match history yet when it got recorded
+    /// the re-processed metadata files get picked up by the filewatcher, which updates the recordings index
+    fn process_deferred_recordings(&self) {
+        let deferred = self
+            .state::<RecordingsIndex>()
+            .recordings()
+            .into_iter()
+            .filter(|summary| summary.deferred);
+        for summary in deferred {
+            if let Err(e) = action::get_recording_metadata(&summary.path, Some(self)) {
+                log::info!("unable to collect game data of {}: {e}", summary.video_id);
             }
         }
-        recordings
     }
 
     fn cleanup_recordings(&self) {
@@ -49,54 +162,38 @@ impl RecordingManager for AppHandle {
     fn cleanup_recordings_by_size(&self) {
         use std::cmp::Ordering;
 
-        let Some(max_gb) = self.state::<SettingsWrapper>().max_recordings_size() else { return };
+        fn account(recording: &Path) -> Option<String> {
+            action::get_recording_metadata(recording, None)
+                .ok()
+                .and_then(|metadata_file| metadata_file.account().map(account_id))
+        }
+
+        let settings = self.state::<SettingsWrapper>();
+        let Some(max_gb) = settings.max_recordings_size() else { return };
         let max_size = max_gb * 1_000_000_000; // convert to bytes
 
         let mut recordings = self.get_recordings();
         recordings.sort_by(|a, b| util::compare_time(a, b).unwrap_or(Ordering::Equal));
 
-        let mut total_size = 0;
-
-        // add size from video thats currently being recorded to the total (in case there is one)
-        // so the total size of all videos stays below the threshhold set in settings
-        if let Some(currently_recording_metadata) = self
-            .state::<CurrentlyRecording>()
-            .get()
-            .and_then(|pb| pb.metadata().ok())
-        {
-            total_size += currently_recording_metadata.len();
-        }
-
-        // split recordings into 'favorites' and 'others' by json metadata 'favorite' value
-        // in case reading the metadata fails put the recording into favorites so it doesn't get deleted
-        let (favorites, others): (Vec<_>, Vec<_>) = recordings.into_iter().partition(|recording| {
-            action::get_recording_metadata(recording, false)
-                .map(|metadata_file| metadata_file.is_favorite())
-                .unwrap_or(true)
-        });
-
-        // get sum of sizes of recordings marked as favorites
-        for recording in favorites {
-            match recording.metadata() {
-                Ok(metadata) => total_size += metadata.len(),
-                Err(e) => log::warn!(
-                    "failed to get size of recording (favorite) {}: {e}",
-                    recording.display(),
-                ),
-            }
-        }
+        let currently_recording = self.state::<CurrentlyRecording>().get();
+        let cleanup_clips = settings.cleanup_clips();
 
-        for recording in others {
-            match recording.metadata() {
-                Ok(metadata) => total_size += metadata.len(),
-                Err(e) => log::warn!("failed to get size of recording {}: {e}", recording.display(),),
+        if settings.retention_per_account() {
+            let mut accounts = HashMap::<Option<String>, Vec<PathBuf>>::new();
+            for recording in recordings {
+                accounts.entry(account(&recording)).or_default().push(recording);
             }
 
-            if total_size > max_size {
-                if let Err(e) = action::delete_recording(recording) {
-                    log::error!("failed to delete file due to size limit: {e}");
-                }
+            // the video that is currently being recorded only counts towards the limit of its own account
+            let currently_recording_account = currently_recording.as_deref().and_then(account);
+            for (account, recordings) in accounts {
+                let currently_recording = currently_recording
+                    .as_deref()
+                    .filter(|_| account == currently_recording_account);
+                delete_recordings_above_size(recordings, currently_recording, max_size, cleanup_clips);
             }
+        } else {
+            delete_recordings_above_size(recordings, currently_recording.as_deref(), max_size, cleanup_clips);
         }
     }
 
@@ -107,38 +204,134 @@ impl RecordingManager for AppHandle {
             Ok(time_passed > max_age)
         }
 
-        fn is_favorite(file: &Path) -> Result<bool> {
-            action::get_recording_metadata(file, false).map(|metadata_file| metadata_file.is_favorite())
-        }
-
-        let Some(max_days) = self.state::<SettingsWrapper>().max_recording_age() else { return };
+        let settings = self.state::<SettingsWrapper>();
+        let Some(max_days) = settings.max_recording_age() else { return };
         let max_age = Duration::from_secs(max_days * 24 * 60 * 60);
+        let cleanup_clips = settings.cleanup_clips();
         let now = SystemTime::now();
         for recording in self.get_recordings() {
-            // in case checking 'too_old(...)' or 'is_favorite(...)' fails default to not deleting the file
-            if too_old(&recording, max_age, now).unwrap_or(false) && !is_favorite(&recording).unwrap_or(true) {
+            // in case checking 'too_old(...)' or 'is_protected(...)' fails default to not deleting the file
+            if too_old(&recording, max_age, now).unwrap_or(false)
+                && !is_protected(&recording, cleanup_clips).unwrap_or(true)
+            {
                 if let Err(e) = action::delete_recording(recording) {
                     log::error!("failed to delete file due to age limit: {e}");
                 }
             }
         }
     }
+
+    fn remove_leftover_files(&self) {
+        let recordings_path = self.state::<SettingsWrapper>().get_recordings_path();
+        for folder in recording_folders(&recordings_path) {
+            transaction::remove_leftovers(&folder);
+        }
+    }
+}
+
+/// favorites never get deleted by the cleanup, clips only if `cleanup_clips` is set
+fn is_protected(recording: &Path, cleanup_clips: bool) -> Result<bool> {
+    action::get_recording_metadata(recording, None)
+        .map(|metadata_file| metadata_file.is_favorite() || (metadata_file.is_clip() && !cleanup_clips))
+}
+
+/// deletes the oldest recordings that aren't protected (favorites and clips) until the total size is below `max_size`
+/// `recordings` have to be sorted by time created (index 0 is newest)
+fn delete_recordings_above_size(
+    recordings: Vec<PathBuf>,
+    currently_recording: Option<&Path>,
+    max_size: u64,
+    cleanup_clips: bool,
+) {
+    let mut total_size = 0;
+
+    // add size from video thats currently being recorded to the total (in case there is one)
+    // so the total size of all videos stays below the threshhold set in settings
+    if let Some(currently_recording_metadata) = currently_recording.and_then(|pb| pb.metadata().ok()) {
+        total_size += currently_recording_metadata.len();
+    }
+
+    // split recordings into 'favorites' (and clips) and 'others' by json metadata
+    // in case reading the metadata fails put the recording into favorites so it doesn't get deleted
+    let (favorites, others): (Vec<_>, Vec<_>) = recordings
+        .into_iter()
+        .partition(|recording| is_protected(recording, cleanup_clips).unwrap_or(true));
+
+    // get sum of sizes of recordings marked as favorites
+    for recording in favorites {
+        match recording.metadata() {
+            Ok(metadata) => total_size += metadata.len(),
+            Err(e) => log::warn!(
+                "failed to get size of recording (favorite) {}: {e}",
+                recording.display(),
+            ),
+        }
+    }
+
+    for recording in others {
+        match recording.metadata() {
+            Ok(metadata) => total_size += metadata.len(),
+            Err(e) => log::warn!("failed to get size of recording {}: {e}", recording.display(),),
+        }
+
+        if total_size > max_size {
+            if let Err(e) = action::delete_recording(recording) {
+                log::error!("failed to delete file due to size limit: {e}");
+            }
+        }
+    }
 }
 
 pub mod action {
-    use std::fs::{self, File};
-    use std::io::{BufReader, BufWriter};
+    use std::fs;
     use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+    use std::time::{SystemTime, UNIX_EPOCH};
 
     use anyhow::{bail, Context, Result};
-    use tauri::async_runtime;
-
+    use riot_datatypes::lcu::Player;
+    use serde_json::Value;
+    use tauri::{async_runtime, AppHandle, Manager};
+
+    use super::super::anonymize::{anonymize_metadata, Pseudonyms};
+    use super::super::ledger::{self, LedgerColumn, LedgerFormat};
+    use super::super::marker_export::{self, MarkerFormat};
+    use super::super::report::{self, ReportFormat};
+    use super::super::transaction::{write_atomic, Transaction};
+    use super::super::{bundle, chapters, markers};
+    use super::{free_video_id, is_video_id_taken, sanitize_file_name};
+    use crate::recorder::migration::{self, Migrated};
     use crate::recorder::MetadataFile;
-    use crate::recorder::{self, Deferred, NoData};
+    use crate::recorder::{self, Annotations, Clip, NoData, Reel, ReelSegment, ReviewState};
+    use crate::state::{Collections, RecordingSummary, SettingsWrapper, StaticData};
+    use crate::{mp4, rofl};
+
+    const QUARANTINE_FOLDER: &str = ".quarantine";
+    /// subfolder of the recordings folder that contains the exported clips and highlight reels
+    pub const CLIPS_FOLDER: &str = "clips";
+    /// subfolder of the recordings folder that contains the exported marker files
+    pub const MARKERS_FOLDER: &str = "markers";
+    /// subfolder of the recordings folder that contains the exported recording bundles
+    pub const BUNDLES_FOLDER: &str = "bundles";
+    /// name of the exported ledger in the recordings folder (without extension)
+    const LEDGER_FILE_NAME: &str = "ledger";
+
+    /// held during the read-modify-write of `update_recording_metadata(...)`
+    static METADATA_UPDATE: Mutex<()> = Mutex::new(());
+
+    /// collections reference recordings by their video id so they get renamed together with the recording
+    /// fails if any folder of `recordings_path` already contains a recording with the new name
+    pub fn rename_recording(
+        recording_path: PathBuf,
+        new_name: String,
+        recordings_path: &Path,
+        collections: &Collections,
+    ) -> Result<bool> {
+        let new_file_name = PathBuf::from(new_name);
+        let new_file_name = new_file_name.file_name().context("invalid new filename")?;
 
-    pub fn rename_recording(recording_path: PathBuf, new_name: String) -> Result<bool> {
         let mut new_recording_path = recording_path.clone();
-        new_recording_path.set_file_name(PathBuf::from(new_name).file_name().context("invalid new filename")?);
+        new_recording_path.set_file_name(new_file_name);
 
         let mut metadata_path = recording_path.clone();
         metadata_path.set_extension("json");
@@ -146,70 +339,468 @@ pub mod action {
         let mut new_metadata_path = new_recording_path.clone();
         new_metadata_path.set_extension("json");
 
-        if new_recording_path.is_file() || new_metadata_path.is_file() {
+        if is_video_id_taken(recordings_path, &new_file_name.to_string_lossy())
+            || new_recording_path.is_file()
+            || new_metadata_path.is_file()
+        {
             return Ok(false);
         }
 
-        fs::rename(&recording_path, &new_recording_path)?;
-        fs::rename(&metadata_path, &new_metadata_path)?;
+        // if renaming the metadata file fails the recording gets renamed back
+        let mut transaction = Transaction::new();
+        transaction.rename(&recording_path, &new_recording_path)?;
+        transaction.rename_optional(&metadata_path, &new_metadata_path)?;
+
+        let video_id = recording_path.file_name().unwrap_or_default().to_string_lossy();
+        let new_video_id = new_recording_path.file_name().unwrap_or_default().to_string_lossy();
+        collections.rename_video(&video_id, &new_video_id, |file, json| transaction.write(file, json))?;
 
+        transaction.commit();
         Ok(true)
     }
 
+    /// cuts the part between `start` and `end` (in seconds of the video) out of the recording without re-encoding
+    /// the clip starts at the keyframe before `start` and gets saved in the clips folder of `recordings_path`
+    pub fn export_clip(recording_path: &Path, recordings_path: &Path, start: f64, end: f64) -> Result<PathBuf> {
+        let source = get_recording_metadata(recording_path, None)?;
+        let source_video_id = recording_path.file_name().context("recording has no name")?;
+        let stem = recording_path
+            .file_stem()
+            .context("recording has no name")?
+            .to_string_lossy();
+
+        let clips_folder = recordings_path.join(CLIPS_FOLDER);
+        fs::create_dir_all(&clips_folder)?;
+
+        // hidden so the filewatcher ignores the clip while it's being written
+        let tmp_path = clips_folder.join(format!(".{stem}.clip.tmp"));
+        let range = mp4::cut(recording_path, &tmp_path, start, end).inspect_err(|_| {
+            _ = fs::remove_file(&tmp_path);
+        })?;
+
+        let name = format!("{stem}_{}-{}", range.start.floor(), range.end.ceil());
+        let clip = Clip::new(
+            source_video_id.to_string_lossy().into_owned(),
+            &source,
+            range.start,
+            range.end,
+        );
+        save_export(
+            &tmp_path,
+            recordings_path,
+            &clips_folder,
+            &name,
+            &MetadataFile::Clip(clip),
+        )
+    }
+
+    /// joins the parts of the recordings into one video in the clips folder of `recordings_path` without re-encoding
+    /// all recordings need the same encoder settings, every part starts at the last keyframe before its start
+    pub fn export_highlight_reel(name: &str, parts: &[mp4::Segment], recordings_path: &Path) -> Result<PathBuf> {
+        let sources = parts
+            .iter()
+            .map(|part| get_recording_metadata(&part.path, None))
+            .collect::<Result<Vec<_>>>()?;
+        let name = sanitize_file_name(name);
+        let name = Path::new(&name)
+            .file_stem()
+            .context("invalid name for the highlight reel")?
+            .to_string_lossy();
+
+        let clips_folder = recordings_path.join(CLIPS_FOLDER);
+        fs::create_dir_all(&clips_folder)?;
+
+        let tmp_path = clips_folder.join(format!(".{name}.reel.tmp"));
+        let ranges = mp4::concat(parts, &tmp_path).inspect_err(|_| {
+            _ = fs::remove_file(&tmp_path);
+        })?;
+
+        let mut reel_start = 0.0;
+        let mut segments = Vec::with_capacity(parts.len());
+        for ((part, source), range) in parts.iter().zip(&sources).zip(ranges) {
+            let source_video_id = part.path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            segments.push(ReelSegment::new(
+                source_video_id,
+                source,
+                range.start,
+                range.end,
+                reel_start,
+            ));
+            reel_start += range.end - range.start;
+        }
+
+        let reel = Reel {
+            favorite: false,
+            segments,
+            annotations: Annotations::default(),
+            review: ReviewState::default(),
+        };
+        save_export(
+            &tmp_path,
+            recordings_path,
+            &clips_folder,
+            &name,
+            &MetadataFile::Reel(reel),
+        )
+    }
+
+    /// post-game report of the recording, only recordings with game data have a report
+    pub fn report(recording_path: &Path, format: ReportFormat) -> Result<String> {
+        let MetadataFile::Metadata(metadata) = get_recording_metadata(recording_path, None)? else {
+            bail!("recording has no game data");
+        };
+        Ok(report::report(&metadata, format))
+    }
+
+    /// writes the post-game report next to the recording (e.g. `2025-01-01_12-00.md`)
+    pub fn save_report(recording_path: &Path, format: ReportFormat) -> Result<PathBuf> {
+        let contents = report(recording_path, format)?;
+        let path = recording_path.with_extension(format.extension());
+        write_atomic(&path, contents.as_bytes())?;
+        Ok(path)
+    }
+
+    /// packages the recording and its metadata (with all notes, highlights and comments) into a zip archive in the
+    /// bundles folder of `recordings_path`, an older bundle of the same recording gets replaced
+    /// if `anonymize` is true the Riot IDs, PUUIDs and summoner IDs of all players are replaced by pseudonyms
+    /// the client replay isn't part of the bundle
+    pub fn export_bundle(recording_path: &Path, recordings_path: &Path, anonymize: bool) -> Result<PathBuf> {
+        let mut metadata_file = get_recording_metadata(recording_path, None)?;
+        metadata_file.clear_replay();
+        if anonymize {
+            anonymize_metadata(&mut metadata_file, &mut Pseudonyms::default());
+        }
+        let stem = recording_path
+            .file_stem()
+            .context("recording has no name")?
+            .to_string_lossy();
+
+        let bundles_folder = recordings_path.join(BUNDLES_FOLDER);
+        fs::create_dir_all(&bundles_folder)?;
+
+        let path = bundles_folder.join(format!("{stem}.zip"));
+        let tmp_path = bundles_folder.join(format!(".{stem}.zip.tmp"));
+        let commit = || -> Result<()> {
+            bundle::write_bundle(recording_path, &migration::to_json(&metadata_file)?, &tmp_path)?;
+            // the older bundle comes back if the new one can't be moved into its place
+            let mut transaction = Transaction::new();
+            transaction.remove_optional(&path)?;
+            transaction.rename(&tmp_path, &path)?;
+            transaction.commit();
+            Ok(())
+        };
+        commit().inspect_err(|_| {
+            _ = fs::remove_file(&tmp_path);
+        })?;
+
+        Ok(path)
+    }
+
+    /// checks the bundle and adds its recording to the recordings folder (`recordings_path`)
+    /// if a recording with the same name already exists the imported one gets a new name
+    pub fn import_bundle(bundle_path: &Path, recordings_path: &Path) -> Result<PathBuf> {
+        let bundle_name = bundle_path.file_stem().context("bundle has no name")?.to_string_lossy();
+
+        // hidden so the filewatcher ignores the recording while it's being extracted
+        let tmp_path = recordings_path.join(format!(".{bundle_name}.import.tmp"));
+        let result = bundle::read_bundle(bundle_path, &tmp_path).and_then(|bundle| {
+            // metadata of older versions of the app gets upgraded like the metadata files in the recordings folder
+            let mut metadata_file = match migration::from_json(bundle.metadata)? {
+                Migrated::No(metadata_file) | Migrated::Yes(metadata_file) => metadata_file,
+            };
+            // bundles of older versions contain the path of the replay on the computer they got exported from
+            metadata_file.clear_replay();
+            let name = Path::new(&bundle.video_id)
+                .file_stem()
+                .context("invalid video id")?
+                .to_string_lossy()
+                .into_owned();
+            Ok((name, metadata_file))
+        });
+        let (name, metadata_file) = result.inspect_err(|_| {
+            _ = fs::remove_file(&tmp_path);
+        })?;
+
+        save_export(&tmp_path, recordings_path, recordings_path, &name, &metadata_file)
+    }
+
+    /// moves the finished video from `tmp_path` to `folder` and writes its metadata
+    /// the filename is free in every folder of `recordings_path` so the video id stays unique
+    /// `tmp_path` gets deleted if that fails
+    fn save_export(
+        tmp_path: &Path,
+        recordings_path: &Path,
+        folder: &Path,
+        name: &str,
+        metadata_file: &MetadataFile,
+    ) -> Result<PathBuf> {
+        let path = folder.join(free_video_id(recordings_path, name)?);
+
+        let commit = || -> Result<()> {
+            let mut transaction = Transaction::new();
+            write_recording_metadata(&mut transaction, &path, metadata_file)?;
+            transaction.rename(tmp_path, &path)?;
+            transaction.commit();
+            Ok(())
+        };
+        commit().inspect_err(|_| {
+            _ = fs::remove_file(tmp_path);
+        })?;
+
+        Ok(path)
+    }
+
     pub fn delete_recording(recording: PathBuf) -> Result<()> {
-        fs::remove_file(&recording)?;
+        let mut metadata_path = recording.clone();
+        metadata_path.set_extension("json");
 
-        let mut metadata_file = recording;
-        metadata_file.set_extension("json");
-        fs::remove_file(metadata_file)?;
+        let mut transaction = Transaction::new();
+        transaction.remove(&recording)?;
+        transaction.remove_optional(&metadata_path)?;
+        transaction.commit();
 
         Ok(())
     }
 
-    pub fn get_recording_metadata(video_path: &Path, fetch: bool) -> Result<MetadataFile> {
-        let video_path = video_path.to_owned();
+    /// writes the events of the game as chapters and the game info as tags into the recording (without re-encoding)
+    pub fn embed_game_metadata(video_path: &Path, metadata: &recorder::GameMetadata) -> Result<()> {
+        mp4::embed(video_path, &chapters::chapters(metadata), &chapters::tags(metadata))
+    }
+
+    /// writes the events, highlights and comments of the recording as a sidecar file (e.g. subtitles or an EDL) into
+    /// the markers folder of `recordings_path`, only recordings with game data have markers
+    pub fn export_markers(recording_path: &Path, recordings_path: &Path, format: MarkerFormat) -> Result<PathBuf> {
+        let MetadataFile::Metadata(metadata) = get_recording_metadata(recording_path, None)? else {
+            bail!("recording has no game data");
+        };
+        let stem = recording_path
+            .file_stem()
+            .context("recording has no name")?
+            .to_string_lossy();
+
+        let frame_rate = if format.needs_frame_rate() {
+            mp4::frame_rate(recording_path)?
+        } else {
+            0.0
+        };
+        let contents = marker_export::export_markers(&markers::markers(&metadata), format, &stem, frame_rate);
+
+        let markers_folder = recordings_path.join(MARKERS_FOLDER);
+        fs::create_dir_all(&markers_folder)?;
+        let path = markers_folder.join(format!("{stem}.{}", format.extension()));
+        write_atomic(&path, contents.as_bytes())?;
+        Ok(path)
+    }
+
+    /// writes the ledger of `recordings` (one row per game) into `recordings_path` (e.g. `ledger.csv`)
+    /// an older ledger in the same format gets replaced
+    pub fn export_ledger(
+        recordings: &[RecordingSummary],
+        recordings_path: &Path,
+        columns: &[LedgerColumn],
+        format: LedgerFormat,
+    ) -> Result<PathBuf> {
+        let contents = ledger::ledger(recordings, columns, format);
+        let path = recordings_path.join(format!("{LEDGER_FILE_NAME}.{}", format.extension()));
+        write_atomic(&path, contents.as_bytes())?;
+        Ok(path)
+    }
+
+    /// replaces the `Deferred` or `NoData` metadata of the recording with the game data of the replay
+    /// `accounts` are the accounts that could have played the game, in case the metadata doesn't know the account
+    pub fn backfill_from_replay(
+        recording_path: &Path,
+        replay_path: &Path,
+        accounts: &[Player],
+        static_data: &StaticData,
+        embed_chapters: bool,
+    ) -> Result<()> {
+        let replay = rofl::read(replay_path)?;
+
+        // converted while holding the metadata lock so nothing the user changed in the meantime gets lost
+        let mut backfilled = None;
+        update_recording_metadata(recording_path, None, |metadata_file| {
+            let metadata = recorder::from_replay(metadata_file.clone(), replay, accounts, static_data);
+            if let Ok(metadata) = &metadata {
+                *metadata_file = MetadataFile::Metadata(metadata.clone());
+            }
+            let replaced = metadata.is_ok();
+            backfilled = Some(metadata);
+            replaced
+        })?;
+
+        // only if the `Deferred` or `NoData` metadata actually got replaced
+        if let (Some(metadata), true) = (backfilled.transpose()?, embed_chapters) {
+            let result = embed_game_metadata(recording_path, &metadata);
+            log::info!("embedding chapters into ({recording_path:?}): {result:?}");
+        }
+        Ok(())
+    }
+
+    /// if `fetch` is given, `Deferred` metadata gets re-processed with the help of the static game data
+    /// (and embedded into the recording if `embedChapters` is set)
+    pub fn get_recording_metadata(video_path: &Path, fetch: Option<&AppHandle>) -> Result<MetadataFile> {
         if !video_path.is_file() {
             bail!("no such video");
         }
 
-        let mut metadata_path = video_path;
+        let mut metadata_path = video_path.to_owned();
         metadata_path.set_extension("json");
 
-        let filedata = if metadata_path.exists() && fs::metadata(&metadata_path)?.is_file() {
-            let reader = BufReader::new(File::open(&metadata_path)?);
-            serde_json::from_reader::<_, MetadataFile>(reader)?
-        } else {
-            let metadata_file = MetadataFile::NoData(NoData { favorite: false });
-            save_recording_metadata(&metadata_path, &metadata_file)?;
-            metadata_file
+        let filedata = match find_recording_metadata(&metadata_path)? {
+            Some(metadata_file) => metadata_file,
+            None => {
+                let metadata_file = MetadataFile::NoData(NoData {
+                    favorite: false,
+                    annotations: Annotations::default(),
+                    review: ReviewState::default(),
+                });
+                save_recording_metadata(&metadata_path, &metadata_file)?;
+                metadata_file
+            }
         };
 
-        match filedata {
-            MetadataFile::Deferred(Deferred {
-                match_id,
-                ingame_time_rec_start_offset,
-                favorite,
-                highlights,
-            }) if fetch => {
-                let mut metadata =
-                    async_runtime::block_on(recorder::process_data(ingame_time_rec_start_offset, match_id))?;
-                metadata.favorite = favorite;
-                metadata.highlights = highlights;
-                let metadata_file = MetadataFile::Metadata(metadata);
-                if let Err(e) = save_recording_metadata(&metadata_path, &metadata_file) {
-                    log::error!("failed to save re-processed game metadata: {e}");
+        match (filedata, fetch) {
+            (MetadataFile::Deferred(deferred), Some(app_handle)) => {
+                let metadata = async_runtime::block_on(recorder::process_data(
+                    deferred.ingame_time_rec_start_offset,
+                    deferred.match_id,
+                    deferred.player,
+                    &app_handle.state::<StaticData>(),
+                ))?;
+
+                // keep what the user changed while the game data was being collected
+                let mut processed = false;
+                let metadata_file = update_recording_metadata(video_path, None, |metadata_file| {
+                    // already processed in the meantime (e.g. by the recorder after the game)
+                    let MetadataFile::Deferred(deferred) = metadata_file else {
+                        return false;
+                    };
+                    let mut metadata = metadata;
+                    // the rank after the game is unknown because it's not the rank that the account has right now
+                    metadata.keep_deferred(deferred);
+                    *metadata_file = MetadataFile::Metadata(metadata);
+                    processed = true;
+                    true
+                })?;
+
+                if let MetadataFile::Metadata(metadata) = &metadata_file {
+                    if processed && app_handle.state::<SettingsWrapper>().embed_chapters() {
+                        let result = embed_game_metadata(video_path, metadata);
+                        log::info!("embedding chapters into ({video_path:?}): {result:?}");
+                    }
                 }
                 Ok(metadata_file)
             }
-            metadata_file => Ok(metadata_file),
+            (metadata_file, _) => Ok(metadata_file),
+        }
+    }
+
+    /// same as `get_recording_metadata(...)` but doesn't create a metadata file if there is none
+    pub fn find_recording_metadata(video_path: &Path) -> Result<Option<MetadataFile>> {
+        let mut metadata_path = video_path.to_owned();
+        metadata_path.set_extension("json");
+
+        if !metadata_path.is_file() {
+            return Ok(None);
+        }
+
+        // I/O errors (e.g. the file is locked by another program) are only temporary => leave the file as is
+        let json = fs::read(&metadata_path)?;
+        match parse_recording_metadata(&metadata_path, &json) {
+            Ok(metadata_file) => Ok(Some(metadata_file)),
+            // written by a newer version of the app => leave the file as is
+            Err(e) if e.is::<migration::UnsupportedVersion>() => Err(e),
+            Err(e) => {
+                log::warn!("quarantining unreadable metadata file {metadata_path:?}: {e:#}");
+                quarantine_metadata(&metadata_path).map(Some)
+            }
         }
     }
 
+    /// parses the contents of the metadata file and upgrades it to the current schema version if necessary
+    fn parse_recording_metadata(metadata_path: &Path, json: &[u8]) -> Result<MetadataFile> {
+        let value = serde_json::from_slice::<Value>(json)?;
+
+        match migration::from_json(value)? {
+            Migrated::No(metadata_file) => Ok(metadata_file),
+            Migrated::Yes(metadata_file) => {
+                let version = migration::schema_version(&metadata_file);
+                log::info!("migrated metadata file {metadata_path:?} to schema version {version}");
+                if let Err(e) = save_recording_metadata(metadata_path, &metadata_file) {
+                    log::error!("failed to save migrated metadata: {e}");
+                }
+                Ok(metadata_file)
+            }
+        }
+    }
+
+    /// moves a metadata file that can't be read into the `.quarantine` folder next to it and replaces it with `NoData`
+    fn quarantine_metadata(metadata_path: &Path) -> Result<MetadataFile> {
+        let parent = metadata_path.parent().context("metadata file has no parent folder")?;
+        let file_stem = metadata_path.file_stem().context("metadata file has no name")?;
+
+        let quarantine_folder = parent.join(QUARANTINE_FOLDER);
+        fs::create_dir_all(&quarantine_folder)?;
+
+        // add a timestamp to not overwrite previously quarantined versions of the same file
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let quarantine_path = quarantine_folder.join(format!("{}.{timestamp}.json", file_stem.to_string_lossy()));
+
+        // mark as favorite so the recording doesn't get deleted by the cleanup before the user had a look
+        let metadata_file = MetadataFile::NoData(NoData {
+            favorite: true,
+            annotations: Annotations::default(),
+            review: ReviewState::default(),
+        });
+
+        let mut transaction = Transaction::new();
+        transaction.rename(metadata_path, &quarantine_path)?;
+        write_recording_metadata(&mut transaction, metadata_path, &metadata_file)?;
+        transaction.commit();
+
+        Ok(metadata_file)
+    }
+
+    /// reads the metadata of the recording, applies `update` and saves it if `update` returns `true`
+    /// concurrent updates wait for each other so none of them gets lost
+    pub fn update_recording_metadata(
+        video_path: &Path,
+        fetch: Option<&AppHandle>,
+        update: impl FnOnce(&mut MetadataFile) -> bool,
+    ) -> Result<MetadataFile> {
+        if fetch.is_some() {
+            // re-processing can take a while => not while holding the lock
+            get_recording_metadata(video_path, fetch)?;
+        }
+
+        let _lock = METADATA_UPDATE.lock().unwrap();
+        let mut metadata_file = get_recording_metadata(video_path, None)?;
+        if update(&mut metadata_file) {
+            save_recording_metadata(video_path, &metadata_file)?;
+        }
+        Ok(metadata_file)
+    }
+
     pub fn save_recording_metadata(path: &Path, metadata_file: &MetadataFile) -> Result<()> {
+        let mut transaction = Transaction::new();
+        write_recording_metadata(&mut transaction, path, metadata_file)?;
+        transaction.commit();
+        Ok(())
+    }
+
+    fn write_recording_metadata(
+        transaction: &mut Transaction,
+        path: &Path,
+        metadata_file: &MetadataFile,
+    ) -> Result<()> {
         let mut path = path.to_owned();
         path.set_extension("json");
 
-        let writer = BufWriter::new(File::create(path)?);
-        Ok(serde_json::to_writer(writer, &metadata_file)?)
+        let json = serde_json::to_vec(&migration::to_json(metadata_file)?)?;
+        transaction.write(&path, &json)
     }
 }
@@ -0,0 +1,146 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use riot_datatypes::{GameId, Timestamp};
+use serde::Serialize;
+
+use crate::rofl;
+use crate::state::RecordingSummary;
+use crate::util;
+
+/// replay of the League client, linked to the recording of the same game
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayFile {
+    pub path: PathBuf,
+    pub game_id: Option<GameId>,
+    pub patch: String,
+    /// in seconds
+    pub game_duration: Timestamp,
+    /// recording of the same game (if there is one)
+    pub video_id: Option<String>,
+    /// the recording has no game data (e.g. because the game is no longer in the match history)
+    pub can_backfill: bool,
+}
+
+/// recordings start before the game is loaded and end a bit after the game ended
+const RECORDING_LENGTH_TOLERANCE: Timestamp = 2 * 60 * 1000;
+/// the client saves the replay shortly after the game (when downloaded right away), a replay that got saved later
+/// can't be told apart from the replays of other games with about the same length
+const REPLAY_SAVE_TOLERANCE: Timestamp = 30 * 60 * 1000;
+
+/// all replays in `replays_path` (newest game first), each linked to the recording with the same game id
+/// recordings without game data have no game id, they get linked by the length of the game instead
+/// replays that can't be read are left out
+pub fn scan_replays(replays_path: &Path, recordings: &[RecordingSummary]) -> Result<Vec<ReplayFile>> {
+    let mut replays = Vec::new();
+    for entry in replays_path.read_dir()?.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != rofl::EXTENSION) {
+            continue;
+        }
+        let replay = match rofl::read(&path) {
+            Ok(replay) => replay,
+            Err(e) => {
+                log::warn!("failed to read replay {}: {e:#}", path.display());
+                continue;
+            }
+        };
+
+        // clips have the match id of their source recording
+        // game ids are only unique per platform (only known if the replay has its original filename)
+        let recording = replay
+            .game_id
+            .and_then(|game_id| {
+                recordings.iter().find(|recording| {
+                    !recording.clip
+                        && recording.match_id.as_ref().is_some_and(|match_id| {
+                            match_id.game_id == game_id
+                                && replay.platform_id.as_ref().is_none_or(|platform_id| {
+                                    match_id.platform_id.is_empty()
+                                        || match_id.platform_id.eq_ignore_ascii_case(platform_id)
+                                })
+                        })
+                })
+            })
+            .or_else(|| {
+                let saved = util::created_timestamp(&path).ok()?;
+                recording_without_data(recordings, replay.game_length, saved)
+            });
+
+        replays.push(ReplayFile {
+            path,
+            game_id: replay.game_id,
+            patch: util::patch(&replay.game_version),
+            game_duration: replay.game_length / 1000,
+            video_id: recording.map(|recording| recording.video_id.clone()),
+            can_backfill: recording.is_some_and(|recording| recording.stats.is_none()),
+        });
+    }
+
+    replays.sort_by_key(|replay| Reverse(replay.game_id));
+    Ok(replays)
+}
+
+/// the only recording without game data that is about as long as the game and ended shortly before the replay got
+/// saved
+fn recording_without_data(
+    recordings: &[RecordingSummary],
+    game_length: Timestamp,
+    replay_saved: Timestamp,
+) -> Option<&RecordingSummary> {
+    let mut candidates = recordings.iter().filter(|recording| {
+        let recording_length = recording.modified - recording.timestamp;
+        !recording.clip
+            && recording.match_id.is_none()
+            && (0..=REPLAY_SAVE_TOLERANCE).contains(&(replay_saved - recording.modified))
+            && (recording_length - game_length).abs() <= RECORDING_LENGTH_TOLERANCE
+    });
+    match (candidates.next(), candidates.next()) {
+        (Some(recording), None) => Some(recording),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Timestamp = 60 * 1000;
+
+    fn recording(video_id: &str, start: Timestamp, end: Timestamp) -> RecordingSummary {
+        let mut recording = RecordingSummary::without_metadata(video_id, start);
+        recording.modified = end;
+        recording
+    }
+
+    #[test]
+    fn link_recording_without_data() {
+        let game = recording("game.mp4", 0, 31 * MINUTE);
+        let short_game = recording("short_game.mp4", 40 * MINUTE, 55 * MINUTE);
+        let recordings = [game, short_game];
+
+        let linked = |game_length, replay_saved| {
+            recording_without_data(&recordings, game_length, replay_saved).map(|recording| recording.video_id.as_str())
+        };
+        assert_eq!(linked(30 * MINUTE, 32 * MINUTE), Some("game.mp4"));
+        assert_eq!(linked(15 * MINUTE, 56 * MINUTE), Some("short_game.mp4"));
+        // the replay got saved before the recording ended
+        assert_eq!(linked(30 * MINUTE, 30 * MINUTE), None);
+        // the replay got saved long after the recording (e.g. of a later game with about the same length)
+        assert_eq!(linked(30 * MINUTE, 24 * 60 * MINUTE), None);
+        // no recording is about as long as the game
+        assert_eq!(linked(20 * MINUTE, 56 * MINUTE), None);
+    }
+
+    #[test]
+    fn ambiguous_recordings_without_data() {
+        let recordings = [
+            recording("a.mp4", 0, 30 * MINUTE),
+            recording("b.mp4", 5 * MINUTE, 34 * MINUTE),
+        ];
+        assert!(recording_without_data(&recordings, 29 * MINUTE, 40 * MINUTE).is_none());
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::markers::{markers, MarkerKind};
+use crate::recorder::GameMetadata;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportFormat {
+    Markdown,
+    /// standalone HTML page without any external resources
+    Html,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+/// everything that is part of a report, independent of the format
+struct Report {
+    title: String,
+    details: Vec<String>,
+    /// (name, value) pairs
+    stats: Vec<(&'static str, String)>,
+    /// (video timestamp, text) of the kills, deaths, assists and objectives
+    timeline: Vec<(String, String)>,
+    /// (video timestamp, text) of the highlights and comments
+    highlights: Vec<(String, String)>,
+    notes: String,
+    tags: Vec<String>,
+}
+
+/// post-game report with the result, stats, a timeline with video timestamps and the highlights and notes of the game
+pub fn report(metadata: &GameMetadata, format: ReportFormat) -> String {
+    let report = Report::new(metadata);
+    match format {
+        ReportFormat::Markdown => report.markdown(),
+        ReportFormat::Html => report.html(),
+    }
+}
+
+impl Report {
+    fn new(metadata: &GameMetadata) -> Self {
+        let result = metadata.result().as_str();
+
+        let mut details = vec![format!("Match {}", metadata.match_id)];
+        details.extend(
+            metadata
+                .game_creation
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()),
+        );
+        details.extend(metadata.game_duration.map(|seconds| video_timestamp(seconds as f64)));
+        details.extend(metadata.patch.as_ref().map(|patch| format!("Patch {patch}")));
+
+        let game_stats = &metadata.stats;
+        let cs = game_stats.total_minions_killed + game_stats.neutral_minions_killed;
+        let minutes = metadata
+            .game_duration
+            .map(|seconds| seconds as f64 / 60.0)
+            .filter(|&minutes| minutes > 0.0);
+        let per_minute =
+            |value: f64| minutes.map_or_else(|| "-".to_owned(), |minutes| format!("{:.1}", value / minutes));
+        let kda = (game_stats.kills + game_stats.assists) as f64 / game_stats.deaths.max(1) as f64;
+
+        let mut stats = vec![
+            ("Result", result.to_owned()),
+            (
+                "KDA",
+                format!(
+                    "{}/{}/{} ({kda:.2})",
+                    game_stats.kills, game_stats.deaths, game_stats.assists
+                ),
+            ),
+            ("CS", cs.to_string()),
+            ("CS/min", per_minute(cs as f64)),
+            ("Vision", format!("{:.0}", game_stats.vision_score)),
+            ("Vision/min", per_minute(game_stats.vision_score)),
+        ];
+        if let Some(lp_delta) = metadata.lp_delta() {
+            stats.push(("LP", format!("{lp_delta:+}")));
+        }
+
+        let (highlights, timeline) = markers(metadata)
+            .into_iter()
+            .map(|marker| (marker.kind, (video_timestamp(marker.time), marker.text)))
+            .partition::<Vec<_>, _>(|(kind, _)| matches!(kind, MarkerKind::Highlight | MarkerKind::Comment));
+
+        Report {
+            title: format!("{} - {} - {result}", metadata.champion_name, metadata.queue.name),
+            details,
+            stats,
+            timeline: timeline.into_iter().map(|(_, entry)| entry).collect(),
+            highlights: highlights.into_iter().map(|(_, entry)| entry).collect(),
+            notes: metadata.annotations.notes.trim().to_owned(),
+            tags: metadata.annotations.tags.clone(),
+        }
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = format!("# {}\n\n{}\n\n", self.title, self.details.join(" · "));
+
+        let (names, values): (Vec<_>, Vec<_>) = self.stats.iter().map(|(name, value)| (*name, value.as_str())).unzip();
+        _ = writeln!(out, "| {} |", names.join(" | "));
+        _ = writeln!(out, "|{}", "---|".repeat(names.len()));
+        _ = writeln!(out, "| {} |", values.join(" | "));
+
+        for (heading, entries) in [("Timeline", &self.timeline), ("Highlights", &self.highlights)] {
+            if entries.is_empty() {
+                continue;
+            }
+            _ = writeln!(out, "\n## {heading}\n");
+            for (timestamp, text) in entries {
+                _ = writeln!(out, "- `{timestamp}` {}", text.replace(['\r', '\n'], " "));
+            }
+        }
+
+        if !self.notes.is_empty() {
+            _ = writeln!(out, "\n## Notes\n\n{}", self.notes);
+        }
+        if !self.tags.is_empty() {
+            _ = writeln!(out, "\nTags: {}", self.tags.join(", "));
+        }
+        out
+    }
+
+    fn html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>body{{font-family:sans-serif;max-width:800px;margin:auto}}\
+             table{{border-collapse:collapse}}td,th{{border:1px solid #888;padding:4px 8px}}\
+             code{{font-weight:bold}}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{details}</p>\n",
+            title = escape_html(&self.title),
+            details = escape_html(&self.details.join(" · ")),
+        );
+
+        out.push_str("<table>\n<tr>");
+        for (name, _) in &self.stats {
+            _ = write!(out, "<th>{name}</th>");
+        }
+        out.push_str("</tr>\n<tr>");
+        for (_, value) in &self.stats {
+            _ = write!(out, "<td>{}</td>", escape_html(value));
+        }
+        out.push_str("</tr>\n</table>\n");
+
+        for (heading, entries) in [("Timeline", &self.timeline), ("Highlights", &self.highlights)] {
+            if entries.is_empty() {
+                continue;
+            }
+            _ = writeln!(out, "<h2>{heading}</h2>\n<ul>");
+            for (timestamp, text) in entries {
+                _ = writeln!(out, "<li><code>{timestamp}</code> {}</li>", escape_html(text));
+            }
+            out.push_str("</ul>\n");
+        }
+
+        if !self.notes.is_empty() {
+            _ = writeln!(
+                out,
+                "<h2>Notes</h2>\n<p>{}</p>",
+                escape_html(&self.notes).replace('\n', "<br>\n")
+            );
+        }
+        if !self.tags.is_empty() {
+            _ = writeln!(out, "<p>Tags: {}</p>", escape_html(&self.tags.join(", ")));
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// `M:SS` or `H:MM:SS`
+fn video_timestamp(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{hours}:{:02}:{:02}", seconds / 60 % 60, seconds % 60),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{bail, Result};
+
+    use super::*;
+    use crate::recorder::migration::{self, Migrated};
+    use crate::recorder::MetadataFile;
+
+    fn metadata() -> Result<GameMetadata> {
+        let fixture = include_str!("../../fixtures/metadata/v7_metadata_with_rank.json");
+        let Migrated::Yes(MetadataFile::Metadata(mut metadata)) = migration::from_json(serde_json::from_str(fixture)?)?
+        else {
+            bail!("expected Metadata");
+        };
+        metadata.highlights = vec![61000.0];
+        Ok(metadata)
+    }
+
+    #[test]
+    fn video_timestamps() {
+        assert_eq!(video_timestamp(0.0), "0:00");
+        assert_eq!(video_timestamp(412.9), "6:52");
+        assert_eq!(video_timestamp(3599.0), "59:59");
+        assert_eq!(video_timestamp(3723.0), "1:02:03");
+        assert_eq!(video_timestamp(-5.0), "0:00");
+    }
+
+    #[test]
+    fn highlights_and_comments_apart_from_timeline() -> Result<()> {
+        let report = Report::new(&metadata()?);
+        assert_eq!(report.title, "Ahri - Ranked Solo/Duo - Win");
+        assert_eq!(report.timeline, [("6:52".to_owned(), "You killed an enemy".to_owned())]);
+        assert_eq!(
+            report.highlights,
+            [
+                ("1:01".to_owned(), "Highlight".to_owned()),
+                ("6:52".to_owned(), "good trade".to_owned())
+            ]
+        );
+        assert_eq!(report.tags, ["laning"]);
+        Ok(())
+    }
+
+    #[test]
+    fn markdown_table() -> Result<()> {
+        let markdown = report(&metadata()?, ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Ahri - Ranked Solo/Duo - Win\n"));
+        assert!(markdown.contains(
+            "| Result | KDA | CS | CS/min | Vision | Vision/min | LP |\n\
+             |---|---|---|---|---|---|---|\n\
+             | Win | 7/3/11 (6.00) | 213 | 7.5 | 23 | 0.8 | +19 |\n"
+        ));
+        assert!(markdown.contains("\n## Timeline\n\n- `6:52` You killed an enemy\n"));
+        assert!(markdown.contains("\n## Highlights\n\n- `1:01` Highlight\n- `6:52` good trade\n"));
+        assert!(markdown.ends_with("\nTags: laning\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn html_escapes_user_text() -> Result<()> {
+        let mut metadata = metadata()?;
+        metadata.annotations.notes = "<script>alert(\"gg\")</script>\nsecond line".to_owned();
+        metadata.annotations.tags = vec!["R&D".to_owned()];
+        metadata.annotations.comments[0].text = "<img src=x onerror=alert(1)>".to_owned();
+
+        let html = report(&metadata, ReportFormat::Html);
+        assert!(!html.contains("<script>") && !html.contains("<img"));
+        assert!(html.contains("<p>&lt;script&gt;alert(&quot;gg&quot;)&lt;/script&gt;<br>\nsecond line</p>"));
+        assert!(html.contains("<li><code>6:52</code> &lt;img src=x onerror=alert(1)&gt;</li>"));
+        assert!(html.contains("<p>Tags: R&amp;D</p>"));
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_html() {
+        assert_eq!(
+            escape_html("a < b && \"c\" > d"),
+            "a &lt; b &amp;&amp; &quot;c&quot; &gt; d"
+        );
+    }
+}
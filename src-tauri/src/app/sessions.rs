@@ -0,0 +1,303 @@
+use std::cmp::Reverse;
+
+use riot_datatypes::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::recorder::GameResult;
+use crate::state::RecordingSummary;
+
+/// consecutive games that started less than the configured gap after the previous game ended
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    /// unix timestamp in milliseconds of when the first game started
+    pub start: Timestamp,
+    /// unix timestamp in milliseconds of when the last game ended (if the game duration is known)
+    pub end: Timestamp,
+    /// oldest first
+    pub video_ids: Vec<String>,
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub remakes: u32,
+    /// sum of the LP changes of all ranked games with a known LP change
+    pub lp_delta: Option<i64>,
+    pub average_kda: Option<f64>,
+    /// the KDA dropped from loss to loss over at least `TILT_LOSSES` consecutive losses
+    pub tilt: bool,
+}
+
+/// number of consecutive losses with a dropping KDA that mark a session as tilted
+const TILT_LOSSES: usize = 3;
+
+impl Session {
+    fn new(recordings: &[&RecordingSummary]) -> Self {
+        let mut session = Session {
+            start: recordings.first().map(|summary| summary.timestamp).unwrap_or_default(),
+            end: recordings.last().map(|summary| end(summary)).unwrap_or_default(),
+            video_ids: recordings.iter().map(|summary| summary.video_id.clone()).collect(),
+            games: recordings.len() as u32,
+            wins: 0,
+            losses: 0,
+            remakes: 0,
+            lp_delta: None,
+            average_kda: None,
+            tilt: false,
+        };
+
+        let mut kdas = Vec::new();
+        // KDAs of the current streak of losses
+        let mut losing_streak = Vec::new();
+        for summary in recordings {
+            match summary.result {
+                Some(GameResult::Win) => session.wins += 1,
+                Some(GameResult::Loss) => session.losses += 1,
+                Some(GameResult::Remake) => session.remakes += 1,
+                None => {}
+            }
+
+            if let Some(lp_delta) = summary.lp_delta {
+                session.lp_delta = Some(session.lp_delta.unwrap_or_default() + lp_delta);
+            }
+
+            let kda = summary.kda().filter(|_| summary.result != Some(GameResult::Remake));
+            kdas.extend(kda);
+
+            match (summary.result, kda) {
+                (Some(GameResult::Loss), Some(kda)) => {
+                    if losing_streak.last().is_some_and(|last| kda >= *last) {
+                        losing_streak.clear();
+                    }
+                    losing_streak.push(kda);
+                    session.tilt |= losing_streak.len() >= TILT_LOSSES;
+                }
+                (Some(GameResult::Remake), _) => {}
+                _ => losing_streak.clear(),
+            }
+        }
+
+        if !kdas.is_empty() {
+            session.average_kda = Some(kdas.iter().sum::<f64>() / kdas.len() as f64);
+        }
+
+        session
+    }
+
+    pub fn contains(&self, video_id: &str) -> bool {
+        self.video_ids.iter().any(|id| id == video_id)
+    }
+}
+
+/// unix timestamp in milliseconds of when the game ended (falls back to the start if the duration is unknown)
+fn end(summary: &RecordingSummary) -> Timestamp {
+    summary.timestamp + summary.game_duration.unwrap_or_default() * 1000
+}
+
+/// groups the recordings into play sessions, clips and recordings without game data don't count as games
+/// `recordings` have to be sorted by time of the game (index 0 is newest), the sessions are sorted the same way
+pub fn sessions(recordings: &[RecordingSummary], gap_minutes: u64) -> Vec<Session> {
+    let gap = gap_minutes as Timestamp * 60 * 1000;
+
+    let mut sessions = Vec::new();
+    let mut current = Vec::<&RecordingSummary>::new();
+    for summary in recordings.iter().rev().filter(|summary| summary.is_game()) {
+        if current.last().is_some_and(|last| summary.timestamp - end(last) >= gap) {
+            sessions.push(Session::new(&current));
+            current.clear();
+        }
+        current.push(summary);
+    }
+    if !current.is_empty() {
+        sessions.push(Session::new(&current));
+    }
+
+    sessions.reverse();
+    sessions
+}
+
+/// same as `sessions(...)` but groups the recordings of each account separately
+/// (games of different accounts that were played at the same time don't belong to the same session)
+pub fn account_sessions(recordings: &[RecordingSummary], gap_minutes: u64) -> Vec<Session> {
+    let mut account_ids = Vec::<&Option<String>>::new();
+    for summary in recordings.iter().filter(|summary| summary.is_game()) {
+        if !account_ids.contains(&&summary.account_id) {
+            account_ids.push(&summary.account_id);
+        }
+    }
+
+    let mut sessions = Vec::new();
+    for account_id in account_ids {
+        let account_recordings = recordings
+            .iter()
+            .filter(|summary| summary.is_game() && summary.account_id == *account_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        sessions.extend(self::sessions(&account_recordings, gap_minutes));
+    }
+
+    sessions.sort_by_key(|session| Reverse(session.start));
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use riot_datatypes::lcu;
+
+    use super::*;
+
+    const GAP_MINUTES: u64 = 30;
+
+    /// game that started `start` minutes after the first game and took `duration` minutes
+    fn game(start: Timestamp, duration: Timestamp, result: GameResult, kills: i64, deaths: i64) -> RecordingSummary {
+        let mut summary = RecordingSummary::without_metadata(&format!("{start}.mp4"), start * 60 * 1000);
+        summary.game_duration = Some(duration * 60);
+        summary.result = Some(result);
+        summary.stats = Some(lcu::Stats {
+            kills,
+            deaths,
+            assists: 0,
+            largest_multi_kill: 1,
+            neutral_minions_killed: 0,
+            neutral_minions_killed_enemy_jungle: 0,
+            neutral_minions_killed_team_jungle: 0,
+            total_minions_killed: 0,
+            vision_score: 0.0,
+            vision_wards_bought_in_game: 0,
+            wards_placed: 0,
+            wards_killed: 0,
+            game_ended_in_early_surrender: result == GameResult::Remake,
+            game_ended_in_surrender: false,
+            win: result == GameResult::Win,
+        });
+        summary
+    }
+
+    /// newest first (like the recordings index)
+    fn newest_first(mut recordings: Vec<RecordingSummary>) -> Vec<RecordingSummary> {
+        recordings.reverse();
+        recordings
+    }
+
+    fn video_ids(sessions: &[Session]) -> Vec<Vec<&str>> {
+        sessions
+            .iter()
+            .map(|session| session.video_ids.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn split_by_gap() {
+        let recordings = newest_first(vec![
+            game(0, 20, GameResult::Win, 1, 1),
+            // 25 minutes after the previous game ended
+            game(45, 30, GameResult::Win, 1, 1),
+            // exactly the gap after the previous game ended
+            game(105, 30, GameResult::Loss, 1, 1),
+            game(185, 50, GameResult::Win, 1, 1),
+            // more than the gap after the previous game started, but less after it ended
+            game(250, 30, GameResult::Win, 1, 1),
+        ]);
+
+        let sessions = sessions(&recordings, GAP_MINUTES);
+        // newest first, games of a session oldest first
+        assert_eq!(
+            video_ids(&sessions),
+            vec![vec!["185.mp4", "250.mp4"], vec!["105.mp4"], vec!["0.mp4", "45.mp4"]]
+        );
+        assert_eq!(sessions[2].start, 0);
+        assert_eq!(sessions[2].end, 75 * 60 * 1000);
+        assert_eq!((sessions[2].games, sessions[2].wins, sessions[2].losses), (2, 2, 0));
+
+        assert!(super::sessions(&[], GAP_MINUTES).is_empty());
+    }
+
+    #[test]
+    fn lp_delta() {
+        let mut recordings = vec![
+            game(0, 30, GameResult::Win, 1, 1),
+            game(40, 30, GameResult::Loss, 1, 1),
+            // e.g. a normal game in between
+            game(80, 30, GameResult::Win, 1, 1),
+        ];
+        recordings[0].lp_delta = Some(21);
+        recordings[1].lp_delta = Some(-17);
+        let sessions = sessions(&newest_first(recordings.clone()), GAP_MINUTES);
+        assert_eq!(sessions[0].lp_delta, Some(4));
+
+        // no game with rank data
+        for recording in &mut recordings {
+            recording.lp_delta = None;
+        }
+        let sessions = super::sessions(&newest_first(recordings), GAP_MINUTES);
+        assert_eq!(sessions[0].lp_delta, None);
+    }
+
+    #[test]
+    fn tilt() {
+        let tilt = |recordings: Vec<RecordingSummary>| sessions(&newest_first(recordings), GAP_MINUTES)[0].tilt;
+
+        // KDA 3 => 2 => 1
+        assert!(tilt(vec![
+            game(0, 30, GameResult::Loss, 3, 1),
+            game(40, 30, GameResult::Loss, 2, 1),
+            game(80, 30, GameResult::Loss, 1, 1),
+        ]));
+        // the KDA didn't drop in the last loss
+        assert!(!tilt(vec![
+            game(0, 30, GameResult::Loss, 3, 1),
+            game(40, 30, GameResult::Loss, 2, 1),
+            game(80, 30, GameResult::Loss, 2, 1),
+        ]));
+        // a win ends the losing streak
+        assert!(!tilt(vec![
+            game(0, 30, GameResult::Loss, 3, 1),
+            game(40, 30, GameResult::Loss, 2, 1),
+            game(80, 30, GameResult::Win, 1, 1),
+            game(120, 30, GameResult::Loss, 1, 1),
+        ]));
+        // a remake doesn't
+        assert!(tilt(vec![
+            game(0, 30, GameResult::Loss, 3, 1),
+            game(40, 30, GameResult::Loss, 2, 1),
+            game(80, 5, GameResult::Remake, 0, 0),
+            game(95, 30, GameResult::Loss, 1, 1),
+        ]));
+    }
+
+    #[test]
+    fn sessions_per_account() {
+        let mut recordings = vec![
+            game(0, 30, GameResult::Win, 1, 1),
+            game(10, 30, GameResult::Loss, 1, 1),
+            game(40, 30, GameResult::Win, 1, 1),
+        ];
+        recordings[0].account_id = Some("puuid-1".into());
+        recordings[1].account_id = Some("puuid-2".into());
+        recordings[2].account_id = Some("puuid-1".into());
+
+        let sessions = account_sessions(&newest_first(recordings), GAP_MINUTES);
+        assert_eq!(video_ids(&sessions), vec![vec!["10.mp4"], vec!["0.mp4", "40.mp4"]]);
+    }
+
+    #[test]
+    fn clips_are_not_games() {
+        let mut clip = RecordingSummary::without_metadata("clip.mp4", 40 * 60 * 1000);
+        // clips have the account of their source game
+        clip.account_id = Some("puuid-1".into());
+        clip.clip = true;
+        let mut no_data = RecordingSummary::without_metadata("no_data.mp4", 200 * 60 * 1000);
+        no_data.account_id = Some("puuid-1".into());
+        let mut recordings = vec![game(0, 30, GameResult::Win, 1, 1), clip, no_data];
+        recordings[0].account_id = Some("puuid-1".into());
+
+        let recordings = newest_first(recordings);
+        let sessions = sessions(&recordings, GAP_MINUTES);
+        assert_eq!(video_ids(&sessions), vec![vec!["0.mp4"]]);
+        assert_eq!(sessions[0].games, 1);
+        assert_eq!(
+            video_ids(&account_sessions(&recordings, GAP_MINUTES)),
+            vec![vec!["0.mp4"]]
+        );
+    }
+}
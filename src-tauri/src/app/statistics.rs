@@ -0,0 +1,360 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use riot_datatypes::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::recorder::GameResult;
+use crate::state::RecordingSummary;
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StatisticsFilter {
+    /// unix timestamp in milliseconds (inclusive)
+    pub from: Option<Timestamp>,
+    /// unix timestamp in milliseconds (inclusive)
+    pub to: Option<Timestamp>,
+    pub account_id: Option<String>,
+}
+
+impl StatisticsFilter {
+    fn matches(&self, summary: &RecordingSummary) -> bool {
+        self.from.is_none_or(|from| summary.timestamp >= from)
+            && self.to.is_none_or(|to| summary.timestamp <= to)
+            && self
+                .account_id
+                .as_deref()
+                .is_none_or(|account_id| summary.account_id.as_deref() == Some(account_id))
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statistics {
+    pub overall: GroupStatistics,
+    /// sorted by number of games
+    pub champions: Vec<GroupStatistics>,
+    /// sorted by number of games
+    pub queues: Vec<GroupStatistics>,
+    pub longest_win_streak: u32,
+    pub longest_loss_streak: u32,
+    /// positive for a win streak, negative for a loss streak
+    pub current_streak: i32,
+}
+
+/// remakes only count towards `games` and `remakes`
+/// averages are `None` if there is no game with the necessary data
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupStatistics {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub remakes: u32,
+    pub winrate: Option<f64>,
+    /// (kills + assists) / deaths over all games
+    pub kda: Option<f64>,
+    pub average_kills: Option<f64>,
+    pub average_deaths: Option<f64>,
+    pub average_assists: Option<f64>,
+    pub cs_per_minute: Option<f64>,
+    pub vision_score_per_minute: Option<f64>,
+    pub kill_participation: Option<f64>,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    games: u32,
+    wins: u32,
+    losses: u32,
+    remakes: u32,
+    kills: i64,
+    deaths: i64,
+    assists: i64,
+    // only games with a known duration
+    minutes: f64,
+    cs: i64,
+    vision_score: f64,
+    // only games with known team kills
+    takedowns: i64,
+    team_kills: i64,
+}
+
+impl Accumulator {
+    fn add(&mut self, summary: &RecordingSummary) {
+        let (Some(result), Some(stats)) = (summary.result, &summary.stats) else { return };
+
+        self.games += 1;
+        match result {
+            GameResult::Win => self.wins += 1,
+            GameResult::Loss => self.losses += 1,
+            GameResult::Remake => {
+                self.remakes += 1;
+                return;
+            }
+        }
+
+        self.kills += stats.kills;
+        self.deaths += stats.deaths;
+        self.assists += stats.assists;
+
+        if let Some(game_duration) = summary.game_duration.filter(|duration| *duration > 0) {
+            self.minutes += game_duration as f64 / 60.0;
+            self.cs += stats.total_minions_killed + stats.neutral_minions_killed;
+            self.vision_score += stats.vision_score;
+        }
+
+        if let Some(team_kills) = summary.team_kills {
+            self.takedowns += stats.kills + stats.assists;
+            self.team_kills += team_kills;
+        }
+    }
+
+    fn finish(self, name: String) -> GroupStatistics {
+        fn ratio(a: f64, b: f64) -> Option<f64> {
+            (b > 0.0).then(|| a / b)
+        }
+
+        let played = (self.wins + self.losses) as f64;
+        GroupStatistics {
+            name,
+            games: self.games,
+            wins: self.wins,
+            losses: self.losses,
+            remakes: self.remakes,
+            winrate: ratio(self.wins as f64, played),
+            kda: (played > 0.0).then(|| (self.kills + self.assists) as f64 / self.deaths.max(1) as f64),
+            average_kills: ratio(self.kills as f64, played),
+            average_deaths: ratio(self.deaths as f64, played),
+            average_assists: ratio(self.assists as f64, played),
+            cs_per_minute: ratio(self.cs as f64, self.minutes),
+            vision_score_per_minute: ratio(self.vision_score, self.minutes),
+            kill_participation: ratio(self.takedowns as f64, self.team_kills as f64),
+        }
+    }
+}
+
+/// aggregates the stats of all recordings with game metadata that match the filter
+/// `recordings` have to be sorted by time of the game (index 0 is newest)
+pub fn statistics(recordings: &[RecordingSummary], filter: &StatisticsFilter) -> Statistics {
+    let recordings = recordings
+        .iter()
+        .filter(|summary| summary.result.is_some() && filter.matches(summary))
+        .collect::<Vec<_>>();
+
+    let mut overall = Accumulator::default();
+    let mut champions = BTreeMap::<String, Accumulator>::new();
+    let mut queues = BTreeMap::<String, Accumulator>::new();
+    for summary in &recordings {
+        overall.add(summary);
+        if let Some(champion_name) = &summary.champion_name {
+            champions.entry(champion_name.clone()).or_default().add(summary);
+        }
+        if let Some(queue) = &summary.queue {
+            queues.entry(queue.name.clone()).or_default().add(summary);
+        }
+    }
+
+    fn sorted(groups: BTreeMap<String, Accumulator>) -> Vec<GroupStatistics> {
+        let mut groups = groups
+            .into_iter()
+            .map(|(name, accumulator)| accumulator.finish(name))
+            .collect::<Vec<_>>();
+        groups.sort_by_key(|group| Reverse(group.games));
+        groups
+    }
+
+    // go through the games from oldest to newest, remakes don't break a streak
+    let mut longest_win_streak = 0;
+    let mut longest_loss_streak = 0;
+    let mut current_streak: i32 = 0;
+    for result in recordings.iter().rev().filter_map(|summary| summary.result) {
+        current_streak = match result {
+            GameResult::Win => current_streak.max(0) + 1,
+            GameResult::Loss => current_streak.min(0) - 1,
+            GameResult::Remake => continue,
+        };
+        longest_win_streak = longest_win_streak.max(current_streak.max(0) as u32);
+        longest_loss_streak = longest_loss_streak.max(current_streak.min(0).unsigned_abs());
+    }
+
+    Statistics {
+        overall: overall.finish("Overall".into()),
+        champions: sorted(champions),
+        queues: sorted(queues),
+        longest_win_streak,
+        longest_loss_streak,
+        current_streak,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use riot_datatypes::{lcu, Queue};
+
+    use super::*;
+
+    fn game(timestamp: Timestamp, champion_name: &str, result: GameResult) -> RecordingSummary {
+        let mut summary = RecordingSummary::without_metadata(&format!("{timestamp}.mp4"), timestamp);
+        summary.champion_name = Some(champion_name.into());
+        summary.queue = Some(Queue {
+            id: 420,
+            name: "Ranked Solo/Duo".into(),
+            is_ranked: true,
+        });
+        summary.result = Some(result);
+        // 20 minutes
+        summary.game_duration = Some(1200);
+        summary.team_kills = Some(20);
+        summary.stats = Some(lcu::Stats {
+            kills: 4,
+            deaths: 2,
+            assists: 6,
+            largest_multi_kill: 1,
+            neutral_minions_killed: 10,
+            neutral_minions_killed_enemy_jungle: 0,
+            neutral_minions_killed_team_jungle: 10,
+            total_minions_killed: 150,
+            vision_score: 20.0,
+            vision_wards_bought_in_game: 1,
+            wards_placed: 8,
+            wards_killed: 2,
+            game_ended_in_early_surrender: result == GameResult::Remake,
+            game_ended_in_surrender: false,
+            win: result == GameResult::Win,
+        });
+        summary
+    }
+
+    /// newest first (like the recordings index)
+    fn games(results: &[GameResult]) -> Vec<RecordingSummary> {
+        results
+            .iter()
+            .enumerate()
+            .map(|(i, result)| game((results.len() - i) as Timestamp * 1_000, "Ahri", *result))
+            .collect()
+    }
+
+    fn statistics_of(results: &[GameResult]) -> Statistics {
+        statistics(&games(results), &StatisticsFilter::default())
+    }
+
+    #[test]
+    fn streaks() {
+        use GameResult::*;
+
+        // oldest to newest: W W W L L W R W
+        let statistics = statistics_of(&[Win, Remake, Win, Loss, Loss, Win, Win, Win]);
+        assert_eq!(statistics.longest_win_streak, 3);
+        assert_eq!(statistics.longest_loss_streak, 2);
+        // the remake doesn't break the streak
+        assert_eq!(statistics.current_streak, 2);
+
+        let statistics = statistics_of(&[Loss, Loss, Win]);
+        assert_eq!(statistics.current_streak, -2);
+        assert_eq!(statistics.longest_win_streak, 1);
+
+        assert_eq!(statistics_of(&[]).current_streak, 0);
+    }
+
+    #[test]
+    fn winrate_ignores_remakes() {
+        use GameResult::*;
+
+        let overall = statistics_of(&[Win, Remake, Win, Loss, Loss, Win, Win, Win]).overall;
+        assert_eq!(overall.games, 8);
+        assert_eq!((overall.wins, overall.losses, overall.remakes), (5, 2, 1));
+        assert_eq!(overall.winrate, Some(5.0 / 7.0));
+        assert_eq!(overall.average_kills, Some(4.0));
+        assert_eq!(overall.kda, Some(5.0));
+        // 160 cs and 20 vision score in 20 minutes
+        assert_eq!(overall.cs_per_minute, Some(8.0));
+        assert_eq!(overall.vision_score_per_minute, Some(1.0));
+
+        let remakes = statistics_of(&[Remake]).overall;
+        assert_eq!(remakes.games, 1);
+        assert_eq!(remakes.winrate, None);
+        assert_eq!(remakes.kda, None);
+
+        // recordings without game data don't count at all
+        let no_data = statistics(
+            &[RecordingSummary::without_metadata("no_data.mp4", 0)],
+            &StatisticsFilter::default(),
+        );
+        assert_eq!(no_data.overall.games, 0);
+    }
+
+    #[test]
+    fn kill_participation() {
+        let mut recordings = games(&[GameResult::Win, GameResult::Loss, GameResult::Win]);
+        // 10 of 20 and 5 of 10 team kills
+        recordings[1].team_kills = Some(10);
+        if let Some(stats) = &mut recordings[1].stats {
+            stats.kills = 1;
+            stats.assists = 4;
+        }
+        // games without events have no team kills and are left out
+        recordings[2].team_kills = None;
+
+        let overall = statistics(&recordings, &StatisticsFilter::default()).overall;
+        assert_eq!(overall.kill_participation, Some(15.0 / 30.0));
+
+        for recording in &mut recordings {
+            recording.team_kills = None;
+        }
+        let overall = statistics(&recordings, &StatisticsFilter::default()).overall;
+        assert_eq!(overall.kill_participation, None);
+    }
+
+    #[test]
+    fn per_champion() {
+        let mut annie = game(5_000, "Annie", GameResult::Loss);
+        annie.account_id = Some("puuid-2".into());
+        annie.queue = Some(Queue {
+            id: 400,
+            name: "Normal Draft".into(),
+            is_ranked: false,
+        });
+        let mut recordings = vec![annie];
+        recordings.extend(games(&[GameResult::Win, GameResult::Loss, GameResult::Win]));
+        for recording in &mut recordings[1..] {
+            recording.account_id = Some("puuid-1".into());
+        }
+
+        let statistics = statistics(&recordings, &StatisticsFilter::default());
+        assert_eq!(statistics.overall.games, 4);
+        // sorted by number of games
+        let champions = statistics
+            .champions
+            .iter()
+            .map(|group| (group.name.as_str(), group.games, group.wins))
+            .collect::<Vec<_>>();
+        assert_eq!(champions, vec![("Ahri", 3, 2), ("Annie", 1, 0)]);
+        let queues = statistics
+            .queues
+            .iter()
+            .map(|group| (group.name.as_str(), group.games))
+            .collect::<Vec<_>>();
+        assert_eq!(queues, vec![("Ranked Solo/Duo", 3), ("Normal Draft", 1)]);
+
+        let filter = StatisticsFilter {
+            account_id: Some("puuid-2".into()),
+            ..Default::default()
+        };
+        let statistics = super::statistics(&recordings, &filter);
+        assert_eq!(statistics.champions.len(), 1);
+        assert_eq!(statistics.champions[0].name, "Annie");
+        assert_eq!(statistics.overall.winrate, Some(0.0));
+
+        let filter = StatisticsFilter {
+            from: Some(2_000),
+            to: Some(3_000),
+            ..Default::default()
+        };
+        assert_eq!(super::statistics(&recordings, &filter).overall.games, 2);
+    }
+}
@@ -0,0 +1,250 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// a group of file operations on a recording and its sidecar files that either all happen or none of them do
+///
+/// every operation is recorded in an undo log that gets replayed in reverse if the transaction is dropped
+/// without calling `commit()` (e.g. because one of the operations returned an error)
+#[must_use = "a transaction gets rolled back if it isn't committed"]
+#[derive(Default)]
+pub struct Transaction {
+    undo_log: Vec<Undo>,
+}
+
+enum Undo {
+    Rename { from: PathBuf, to: PathBuf },
+    Remove { path: PathBuf, backup: PathBuf },
+    Write { path: PathBuf, previous: Option<Vec<u8>> },
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rename(&mut self, from: &Path, to: &Path) -> Result<()> {
+        fs::rename(from, to).with_context(|| format!("failed to rename {from:?} to {to:?}"))?;
+        self.undo_log.push(Undo::Rename {
+            from: from.to_owned(),
+            to: to.to_owned(),
+        });
+        Ok(())
+    }
+
+    /// same as `rename()` but a missing `from` file is not an error (e.g. a recording without metadata file)
+    pub fn rename_optional(&mut self, from: &Path, to: &Path) -> Result<bool> {
+        if !from.exists() {
+            return Ok(false);
+        }
+        self.rename(from, to).map(|_| true)
+    }
+
+    /// the file only gets moved to a hidden backup file and is deleted for real on `commit()`
+    pub fn remove(&mut self, path: &Path) -> Result<()> {
+        let backup = hidden_sibling(path, "bak")?;
+        fs::rename(path, &backup).with_context(|| format!("failed to remove {path:?}"))?;
+        self.undo_log.push(Undo::Remove { path: path.to_owned(), backup });
+        Ok(())
+    }
+
+    /// same as `remove()` but a missing file is not an error
+    pub fn remove_optional(&mut self, path: &Path) -> Result<bool> {
+        if !path.exists() {
+            return Ok(false);
+        }
+        self.remove(path).map(|_| true)
+    }
+
+    /// replaces the content of `path` atomically so a crash never leaves a half written file behind
+    pub fn write(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+        let previous = match fs::read(path) {
+            Ok(previous) => Some(previous),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("failed to read {path:?}")),
+        };
+
+        write_atomic(path, contents)?;
+        self.undo_log.push(Undo::Write {
+            path: path.to_owned(),
+            previous,
+        });
+        Ok(())
+    }
+
+    pub fn commit(mut self) {
+        for undo in self.undo_log.drain(..) {
+            if let Undo::Remove { backup, .. } = undo {
+                if let Err(e) = fs::remove_file(&backup) {
+                    log::warn!("failed to delete backup file {backup:?}: {e}");
+                }
+            }
+        }
+    }
+
+    fn rollback(&mut self) {
+        while let Some(undo) = self.undo_log.pop() {
+            let result = match &undo {
+                Undo::Rename { from, to } => fs::rename(to, from).map_err(anyhow::Error::from),
+                Undo::Remove { path, backup } => fs::rename(backup, path).map_err(anyhow::Error::from),
+                Undo::Write { path, previous: Some(previous) } => write_atomic(path, previous),
+                Undo::Write { path, previous: None } => fs::remove_file(path).map_err(anyhow::Error::from),
+            };
+
+            if let Err(e) = result {
+                log::error!("failed to roll back file operation: {e}");
+            }
+        }
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if !self.undo_log.is_empty() {
+            log::warn!("rolling back {} file operation(s)", self.undo_log.len());
+            self.rollback();
+        }
+    }
+}
+
+/// writes to a temporary file in the same folder first and then renames it over `path`
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp_path = hidden_sibling(path, "tmp")?;
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        _ = fs::remove_file(&tmp_path);
+    }
+    result.with_context(|| format!("failed to write {path:?}"))
+}
+
+/// deletes the temporary and backup files in `folder` that were left behind because the app crashed in the middle of
+/// a file operation (only call this while no transaction can be running, e.g. on startup)
+pub fn remove_leftovers(folder: &Path) {
+    let Ok(entries) = fs::read_dir(folder) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !is_leftover(&path) || !path.is_file() {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => log::info!("deleted leftover file {path:?}"),
+            Err(e) => log::warn!("failed to delete leftover file {path:?}: {e}"),
+        }
+    }
+}
+
+/// hidden `.tmp` and `.bak` files (see `hidden_sibling(...)`)
+fn is_leftover(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.starts_with('.') && (name.ends_with(".tmp") || name.ends_with(".bak")))
+}
+
+/// e.g. `recording.mp4` => `.recording.mp4.tmp`
+/// hidden files in the same folder are ignored by the recordings list and stay on the same filesystem for renames
+fn hidden_sibling(path: &Path, extension: &str) -> Result<PathBuf> {
+    let file_name = path.file_name().context("path has no file name")?;
+    Ok(path.with_file_name(format!(".{}.{extension}", file_name.to_string_lossy())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollback_after_failed_rename() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("transaction_test_rollback_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let recording = dir.join("game.mp4");
+        let metadata = dir.join("game.json");
+        let old = dir.join("old.mp4");
+        fs::write(&recording, "video")?;
+        fs::write(&metadata, "metadata")?;
+        fs::write(&old, "old video")?;
+
+        let result = (|| {
+            let mut transaction = Transaction::new();
+            transaction.remove(&old)?;
+            transaction.write(&metadata, b"changed metadata")?;
+            transaction.rename(&recording, &dir.join("renamed.mp4"))?;
+            // fails because the folder doesn't exist
+            transaction.rename(&metadata, &dir.join("missing").join("renamed.json"))?;
+            transaction.commit();
+            Ok::<_, anyhow::Error>(())
+        })();
+        assert!(result.is_err());
+
+        // everything is back where it was before the transaction
+        assert_eq!(fs::read_to_string(&recording)?, "video");
+        assert_eq!(fs::read_to_string(&metadata)?, "metadata");
+        assert_eq!(fs::read_to_string(&old)?, "old video");
+        assert!(!dir.join("renamed.mp4").exists());
+        assert!(!dir.join(".old.mp4.bak").exists());
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn commit_without_sidecar() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("transaction_test_commit_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let recording = dir.join("game.mp4");
+        let renamed = dir.join("renamed.mp4");
+        let old = dir.join("old.mp4");
+        fs::write(&recording, "video")?;
+        fs::write(&old, "old video")?;
+
+        let mut transaction = Transaction::new();
+        transaction.rename(&recording, &renamed)?;
+        assert!(!transaction.rename_optional(&dir.join("game.json"), &dir.join("renamed.json"))?);
+        assert!(transaction.remove_optional(&old)?);
+        assert!(!transaction.remove_optional(&dir.join("old.json"))?);
+        transaction.commit();
+
+        assert!(!recording.exists());
+        assert_eq!(fs::read_to_string(&renamed)?, "video");
+        assert!(!dir.join("renamed.json").exists());
+        // the backup of the removed file is gone as well
+        assert!(!old.exists());
+        assert!(!dir.join(".old.mp4.bak").exists());
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn leftovers_after_crash() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("transaction_test_leftovers_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        for file in [
+            "game.mp4",
+            ".game.mp4.bak",
+            ".game.json.tmp",
+            ".game.clip.tmp",
+            ".hidden",
+            "game.tmp",
+        ] {
+            fs::write(dir.join(file), file)?;
+        }
+
+        remove_leftovers(&dir);
+
+        let mut remaining = fs::read_dir(&dir)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>>>()?;
+        remaining.sort();
+        assert_eq!(remaining, [".hidden", "game.mp4", "game.tmp"]);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
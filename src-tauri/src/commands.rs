@@ -1,14 +1,21 @@
-use std::cmp::Ordering;
-use std::fs::metadata;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use riot_datatypes::Timestamp;
+use shaco::rest::LcuRestClient;
 use tauri::{AppHandle, State};
 
-use crate::app::{action, RecordingManager};
-use crate::recorder::MetadataFile;
-use crate::state::{MarkerFlags, SettingsFile, SettingsWrapper};
-use crate::util::compare_time;
+use crate::app::{
+    self, action, DeathWindow, LedgerColumn, LedgerFormat, MarkerFormat, Pseudonyms, RecordingManager, ReplayFile,
+    ReportFormat, Session, Statistics, StatisticsFilter,
+};
+use crate::mp4;
+use crate::recorder::{self, Annotations, MetadataFile, ReviewState};
+use crate::state::{
+    Collection, CollectionItem, Collections, DeathReviewWindow, GameDataSummary, MarkerFlags, RecordingSummary,
+    RecordingsIndex, RecordingsQuery, ReelPart, SearchResults, SettingsFile, SettingsWrapper, StaticData, TimeRange,
+};
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
@@ -35,41 +42,191 @@ pub fn get_recordings_path(settings: State<SettingsWrapper>) -> PathBuf {
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn get_recordings_size(app_handle: AppHandle) -> f32 {
-    let mut size = 0;
-    for file in app_handle.get_recordings() {
-        if let Ok(metadata) = metadata(file) {
-            size += metadata.len();
+pub fn get_recordings_size(recordings_index: State<RecordingsIndex>) -> f32 {
+    recordings_index.total_size() as f32 / 1_000_000_000.0 // in Gigabyte
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_recordings_list(
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Vec<RecordingSummary> {
+    let mut recordings = recordings_index.recordings();
+    hide_accounts(&mut recordings, &recordings_index, &settings);
+    recordings
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn search_recordings(
+    query: RecordingsQuery,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> SearchResults {
+    let query = real_query(query, &recordings_index, &settings);
+    let mut results = recordings_index.search(&query);
+    hide_accounts(&mut results.recordings, &recordings_index, &settings);
+    results
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_statistics(
+    mut filter: StatisticsFilter,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Statistics {
+    filter.account_id = filter
+        .account_id
+        .map(|account_id| real_account_id(account_id, &recordings_index, &settings));
+    app::statistics(&recordings_index.recordings(), &filter)
+}
+
+/// play sessions of all accounts or only of the given account (index 0 is newest)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_sessions(
+    account_id: Option<String>,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Vec<Session> {
+    let recordings = recordings_index.recordings();
+    match account_id {
+        Some(account_id) => {
+            let account_id = Some(real_account_id(account_id, &recordings_index, &settings));
+            let recordings = recordings
+                .into_iter()
+                .filter(|recording| recording.account_id == account_id)
+                .collect::<Vec<_>>();
+            app::sessions(&recordings, settings.session_gap_minutes())
         }
+        None => app::account_sessions(&recordings, settings.session_gap_minutes()),
     }
-    size as f32 / 1_000_000_000.0 // in Gigabyte
 }
 
 #[cfg_attr(test, derive(specta::Type))]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Recording {
-    video_id: String,
-    metadata: Option<MetadataFile>,
+pub struct Account {
+    account_id: String,
+    game_name: String,
+    tag_line: String,
+    recordings: u32,
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_accounts(recordings_index: State<RecordingsIndex>, settings: State<SettingsWrapper>) -> Vec<Account> {
+    let mut recordings = recordings_index.recordings();
+    hide_accounts(&mut recordings, &recordings_index, &settings);
+
+    let mut accounts = Vec::<Account>::new();
+    for recording in recordings {
+        let (Some(account_id), Some(player)) = (recording.account_id, recording.account) else { continue };
+
+        match accounts.iter_mut().find(|account| account.account_id == account_id) {
+            Some(account) => account.recordings += 1,
+            None => accounts.push(Account {
+                account_id,
+                game_name: player.game_name,
+                tag_line: player.tag_line,
+                recordings: 1,
+            }),
+        }
+    }
+    accounts
 }
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn get_recordings_list(app_handle: AppHandle) -> Vec<Recording> {
-    let mut recordings = app_handle.get_recordings();
-    // sort by time created (index 0 is newest)
-    recordings.sort_by(|a, b| compare_time(a, b).unwrap_or(Ordering::Equal));
-    let mut ret = Vec::new();
-    for path in recordings {
-        if let Some(video_id) = path
-            .file_name()
-            .and_then(|fname| fname.to_os_string().into_string().ok())
-        {
-            let metadata = action::get_recording_metadata(&path, true).ok();
-            ret.push(Recording { video_id, metadata });
+pub fn get_account_recordings(
+    account_id: String,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Vec<RecordingSummary> {
+    let account_id = real_account_id(account_id, &recordings_index, &settings);
+    let mut recordings = recordings_index
+        .recordings()
+        .into_iter()
+        .filter(|recording| recording.account_id.as_ref() == Some(&account_id))
+        .collect::<Vec<_>>();
+    hide_accounts(&mut recordings, &recordings_index, &settings);
+    recordings
+}
+
+/// in streamer mode the accounts of the recordings are replaced by pseudonyms
+/// every account gets the same pseudonym in all commands (numbered in the order of the recordings index)
+/// the account id and path are replaced too because they can contain the Riot ID (e.g. the account subfolder)
+fn hide_accounts(recordings: &mut [RecordingSummary], recordings_index: &RecordingsIndex, settings: &SettingsWrapper) {
+    if !settings.streamer_mode() {
+        return;
+    }
+
+    let mut pseudonyms = account_pseudonyms(recordings_index);
+    for recording in recordings {
+        recording.path = PathBuf::from(&recording.video_id);
+        if let Some(account) = &mut recording.account {
+            *account = pseudonyms.player(account);
+            recording.account_id = Some(app::account_id(account));
+        } else {
+            recording.account_id = None;
+        }
+    }
+}
+
+/// the frontend only knows the pseudonymous account ids of `hide_accounts(...)` in streamer mode
+fn real_account_id(account_id: String, recordings_index: &RecordingsIndex, settings: &SettingsWrapper) -> String {
+    if !settings.streamer_mode() {
+        return account_id;
+    }
+
+    let mut pseudonyms = account_pseudonyms(recordings_index);
+    recordings_index
+        .recordings()
+        .into_iter()
+        .find(|recording| {
+            recording
+                .account
+                .as_ref()
+                .is_some_and(|account| app::account_id(&pseudonyms.player(account)) == account_id)
+        })
+        .and_then(|recording| recording.account_id)
+        .unwrap_or(account_id)
+}
+
+/// the query of the frontend with the real account id, in streamer mode the free text search ignores Riot IDs
+fn real_query(
+    mut query: RecordingsQuery,
+    recordings_index: &RecordingsIndex,
+    settings: &SettingsWrapper,
+) -> RecordingsQuery {
+    query.account_id = query
+        .account_id
+        .map(|account_id| real_account_id(account_id, recordings_index, settings));
+    query.hide_accounts = settings.streamer_mode();
+    query
+}
+
+fn account_pseudonyms(recordings_index: &RecordingsIndex) -> Pseudonyms {
+    let mut pseudonyms = Pseudonyms::default();
+    for recording in recordings_index.recordings() {
+        if let Some(account) = &recording.account {
+            pseudonyms.player(account);
         }
     }
-    ret
+    pseudonyms
+}
+
+/// in streamer mode only the video id because the path contains the account subfolder (Riot ID)
+/// the recording then gets played through the `recording` protocol
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_video_path(video_id: String, app_handle: AppHandle, settings: State<SettingsWrapper>) -> PathBuf {
+    if settings.streamer_mode() {
+        return PathBuf::from(video_id);
+    }
+    app_handle.get_recording_path(&video_id)
 }
 
 #[cfg_attr(test, specta::specta)]
@@ -86,9 +243,21 @@ pub fn open_recordings_folder(state: State<SettingsWrapper>) {
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn rename_video(video_id: String, new_video_id: String, state: State<SettingsWrapper>) -> bool {
-    let recording = state.get_recordings_path().join(video_id);
-    action::rename_recording(recording, new_video_id).unwrap_or_else(|e| {
+pub fn rename_video(
+    video_id: String,
+    new_video_id: String,
+    app_handle: AppHandle,
+    collections: State<Collections>,
+    settings: State<SettingsWrapper>,
+) -> bool {
+    let recording = app_handle.get_recording_path(&video_id);
+    action::rename_recording(
+        recording,
+        new_video_id,
+        &settings.get_recordings_path(),
+        collections.inner(),
+    )
+    .unwrap_or_else(|e| {
         log::error!("failed to rename video: {e}");
         false
     })
@@ -96,8 +265,8 @@ pub fn rename_video(video_id: String, new_video_id: String, state: State<Setting
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn delete_video(video_id: String, state: State<SettingsWrapper>) -> bool {
-    let recording = state.get_recordings_path().join(video_id);
+pub fn delete_video(video_id: String, app_handle: AppHandle) -> bool {
+    let recording = app_handle.get_recording_path(&video_id);
 
     match action::delete_recording(recording) {
         Ok(_) => true,
@@ -110,22 +279,557 @@ pub fn delete_video(video_id: String, state: State<SettingsWrapper>) -> bool {
 
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn get_metadata(video_id: String, state: State<SettingsWrapper>) -> Option<MetadataFile> {
-    let path = state.get_recordings_path().join(video_id);
-    action::get_recording_metadata(&path, true).ok()
+pub fn get_metadata(
+    video_id: String,
+    app_handle: AppHandle,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Option<MetadataFile> {
+    let path = app_handle.get_recording_path(&video_id);
+    let mut metadata_file = action::get_recording_metadata(&path, Some(&app_handle)).ok()?;
+    if settings.streamer_mode() {
+        // the account keeps the pseudonym it has in the list of recordings
+        app::anonymize_metadata(&mut metadata_file, &mut account_pseudonyms(&recordings_index));
+    }
+    Some(metadata_file)
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn toggle_favorite(video_id: String, app_handle: AppHandle) -> Option<bool> {
+    let path = app_handle.get_recording_path(&video_id);
+
+    let metadata = action::update_recording_metadata(&path, Some(&app_handle), |metadata| {
+        metadata.set_favorite(!metadata.is_favorite());
+        true
+    })
+    .ok()?;
+
+    Some(metadata.is_favorite())
+}
+
+/// applies `update` to the annotations of the recording and saves them if `update` returns true
+/// doesn't collect the game data of `Deferred` recordings (done in the background) so it works without the client
+fn update_annotations(
+    video_id: &str,
+    app_handle: &AppHandle,
+    update: impl FnOnce(&mut Annotations) -> bool,
+) -> Option<Annotations> {
+    let path = app_handle.get_recording_path(video_id);
+
+    let metadata =
+        action::update_recording_metadata(&path, None, |metadata| update(metadata.annotations_mut())).ok()?;
+
+    Some(metadata.annotations().clone())
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn set_notes(video_id: String, notes: String, app_handle: AppHandle) -> Option<Annotations> {
+    update_annotations(&video_id, &app_handle, |annotations| {
+        annotations.notes = notes;
+        true
+    })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn add_tag(video_id: String, tag: String, app_handle: AppHandle) -> Option<Annotations> {
+    update_annotations(&video_id, &app_handle, |annotations| annotations.add_tag(tag))
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn remove_tag(video_id: String, tag: String, app_handle: AppHandle) -> Option<Annotations> {
+    update_annotations(&video_id, &app_handle, |annotations| annotations.remove_tag(&tag))
+}
+
+/// `game_time` in milliseconds (for recordings without game data it's the time in the video instead)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn add_comment(video_id: String, game_time: f64, text: String, app_handle: AppHandle) -> Option<Annotations> {
+    update_annotations(&video_id, &app_handle, |annotations| {
+        annotations.add_comment(game_time, text);
+        true
+    })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn edit_comment(video_id: String, comment_id: u32, text: String, app_handle: AppHandle) -> Option<Annotations> {
+    update_annotations(&video_id, &app_handle, |annotations| {
+        let Some(comment) = annotations.comment_mut(comment_id) else { return false };
+        comment.text = text;
+        true
+    })
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn delete_comment(video_id: String, comment_id: u32, app_handle: AppHandle) -> Option<Annotations> {
+    update_annotations(&video_id, &app_handle, |annotations| {
+        annotations.remove_comment(comment_id)
+    })
+}
+
+/// applies `update` to the review state of the recording and saves it
+/// like `update_annotations(...)` this doesn't collect the game data of `Deferred` recordings
+fn update_review(video_id: &str, app_handle: &AppHandle, update: impl FnOnce(&mut ReviewState)) -> Option<ReviewState> {
+    let path = app_handle.get_recording_path(video_id);
+
+    let metadata = action::update_recording_metadata(&path, None, |metadata| {
+        update(metadata.review_mut());
+        true
+    })
+    .ok()?;
+
+    Some(metadata.review().clone())
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn set_watched(video_id: String, watched: bool, app_handle: AppHandle) -> Option<ReviewState> {
+    update_review(&video_id, &app_handle, |review| review.watched = watched)
+}
+
+/// a reviewed recording also counts as watched
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn set_reviewed(video_id: String, reviewed: bool, app_handle: AppHandle) -> Option<ReviewState> {
+    update_review(&video_id, &app_handle, |review| {
+        review.reviewed = reviewed;
+        review.watched |= reviewed;
+    })
+}
+
+/// positions that are closer than this (in seconds) to the saved one aren't worth another write of the metadata file
+const PLAYBACK_POSITION_PRECISION: f64 = 5.0;
+
+/// `position` in seconds of the video, `None` if the recording was watched until the end
+/// doesn't create or re-process a metadata file (this gets called every time the playback pauses)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn set_playback_position(video_id: String, position: Option<f64>, app_handle: AppHandle) -> Option<ReviewState> {
+    let path = app_handle.get_recording_path(&video_id);
+
+    action::find_recording_metadata(&path).ok().flatten()?;
+    let metadata = action::update_recording_metadata(&path, None, |metadata| {
+        let review = metadata.review_mut();
+        let unchanged = match (review.position, position) {
+            (Some(saved), Some(position)) => (saved - position).abs() < PLAYBACK_POSITION_PRECISION,
+            (saved, position) => saved.is_none() && position.is_none(),
+        };
+        if !unchanged {
+            review.position = position;
+        }
+        !unchanged
+    })
+    .ok()?;
+
+    Some(metadata.review().clone())
 }
 
+/// recordings that haven't been reviewed yet and match the `reviewQueue` rule of the settings (oldest first)
 #[cfg_attr(test, specta::specta)]
 #[tauri::command]
-pub fn toggle_favorite(video_id: String, state: State<SettingsWrapper>) -> Option<bool> {
-    let path = state.get_recordings_path().join(video_id);
+pub fn get_review_queue(
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Vec<RecordingSummary> {
+    let rule = settings.review_queue();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as Timestamp);
+
+    let mut queue = recordings_index
+        .recordings()
+        .into_iter()
+        .filter(|summary| !summary.review.reviewed && rule.matches(summary, now))
+        .collect::<Vec<_>>();
+    queue.reverse();
+    hide_accounts(&mut queue, &recordings_index, &settings);
+    queue
+}
 
-    let mut metadata = action::get_recording_metadata(&path, true).ok()?;
-    let favorite = !metadata.is_favorite();
-    metadata.set_favorite(favorite);
-    action::save_recording_metadata(&path, &metadata).ok()?;
+/// playlist of every death of the player in the recording
+/// `window` defaults to the `deathReviewWindow` of the settings
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_recording_deaths(
+    video_id: String,
+    window: Option<DeathReviewWindow>,
+    app_handle: AppHandle,
+    settings: State<SettingsWrapper>,
+) -> Vec<DeathWindow> {
+    let path = app_handle.get_recording_path(&video_id);
+    let window = window.unwrap_or_else(|| settings.death_review_window());
 
-    Some(favorite)
+    match action::find_recording_metadata(&path) {
+        Ok(Some(MetadataFile::Metadata(metadata))) => app::death_windows(&video_id, &metadata, &window),
+        Ok(_) => Vec::new(),
+        Err(e) => {
+            log::error!("failed to read metadata of {video_id}: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// playlist of every death of the player across all recordings that match the query (e.g. all deaths on a champion
+/// in the last week), sorted the same way as the recordings of `search_recordings`
+/// `window` defaults to the `deathReviewWindow` of the settings
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_death_review(
+    query: RecordingsQuery,
+    window: Option<DeathReviewWindow>,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Vec<DeathWindow> {
+    let window = window.unwrap_or_else(|| settings.death_review_window());
+    let query = real_query(query, &recordings_index, &settings);
+
+    recordings_index
+        .search(&query)
+        .recordings
+        .into_iter()
+        // only recordings with game data contain the events of the game
+        .filter(|summary| summary.result.is_some())
+        .flat_map(|summary| match action::find_recording_metadata(&summary.path) {
+            Ok(Some(MetadataFile::Metadata(metadata))) => app::death_windows(&summary.video_id, &metadata, &window),
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                log::error!("failed to read metadata of {}: {e}", summary.video_id);
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+/// cuts the part between `start` and `end` (in seconds of the video) out of the recording into the clips folder
+/// the clip starts at the last keyframe before `start`, returns the video id of the clip
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_clip(
+    video_id: String,
+    start: f64,
+    end: f64,
+    app_handle: AppHandle,
+    settings: State<SettingsWrapper>,
+) -> Option<String> {
+    let recording = app_handle.get_recording_path(&video_id);
+    match action::export_clip(&recording, &settings.get_recordings_path(), start, end) {
+        Ok(clip) => clip.file_name().map(|name| name.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::error!("failed to export clip of {video_id}: {e:#}");
+            None
+        }
+    }
+}
+
+/// joins the parts (e.g. the items of a collection or the deaths of a death review) into one video in the clips
+/// folder without re-encoding, all recordings need the same encoder settings
+/// returns the video id of the highlight reel
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_highlight_reel(
+    name: String,
+    parts: Vec<ReelPart>,
+    app_handle: AppHandle,
+    settings: State<SettingsWrapper>,
+) -> Option<String> {
+    let segments = parts
+        .into_iter()
+        .map(|part| mp4::Segment {
+            path: app_handle.get_recording_path(&part.video_id),
+            start: part.range.map_or(0.0, |range| range.start),
+            end: part.range.map_or(f64::INFINITY, |range| range.end),
+        })
+        .collect::<Vec<_>>();
+
+    match action::export_highlight_reel(&name, &segments, &settings.get_recordings_path()) {
+        Ok(reel) => reel.file_name().map(|name| name.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::error!("failed to export highlight reel {name}: {e:#}");
+            None
+        }
+    }
+}
+
+/// writes the markers of the recording as a sidecar file in the markers folder, returns the path of the file
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_markers(
+    video_id: String,
+    format: MarkerFormat,
+    app_handle: AppHandle,
+    settings: State<SettingsWrapper>,
+) -> Option<String> {
+    let path = app_handle.get_recording_path(&video_id);
+    match action::export_markers(&path, &settings.get_recordings_path(), format) {
+        Ok(path) => Some(path.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::error!("failed to export markers of {video_id}: {e:#}");
+            None
+        }
+    }
+}
+
+/// writes one row per recording that matches `query` (oldest first) with the selected `columns` (all if empty) into
+/// the recordings folder, returns the path of the ledger
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_ledger(
+    query: RecordingsQuery,
+    columns: Vec<LedgerColumn>,
+    format: LedgerFormat,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Option<String> {
+    let query = real_query(query, &recordings_index, &settings);
+    let mut recordings = recordings_index.search(&query).recordings;
+    recordings.reverse();
+    match action::export_ledger(&recordings, &settings.get_recordings_path(), &columns, format) {
+        Ok(path) => Some(path.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::error!("failed to export ledger: {e:#}");
+            None
+        }
+    }
+}
+
+/// post-game report of the recording (e.g. to copy it to the clipboard), `None` if the recording has no game data
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_report(video_id: String, format: ReportFormat, app_handle: AppHandle) -> Option<String> {
+    let path = app_handle.get_recording_path(&video_id);
+    action::report(&path, format)
+        .inspect_err(|e| log::error!("failed to create report of {video_id}: {e:#}"))
+        .ok()
+}
+
+/// writes the post-game report next to the recording, returns the path of the report
+/// (only the file name in streamer mode because the account subfolder can contain the Riot ID)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn save_report(
+    video_id: String,
+    format: ReportFormat,
+    app_handle: AppHandle,
+    settings: State<SettingsWrapper>,
+) -> Option<String> {
+    let path = app_handle.get_recording_path(&video_id);
+    match action::save_report(&path, format) {
+        Ok(path) if settings.streamer_mode() => path.file_name().map(|name| name.to_string_lossy().into_owned()),
+        Ok(path) => Some(path.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::error!("failed to save report of {video_id}: {e:#}");
+            None
+        }
+    }
+}
+
+/// packages the recording with its metadata into a zip archive in the bundles folder, returns the path of the bundle
+/// if `anonymize` is true all players in the metadata of the bundle are replaced by pseudonyms
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn export_bundle(
+    video_id: String,
+    anonymize: bool,
+    app_handle: AppHandle,
+    settings: State<SettingsWrapper>,
+) -> Option<String> {
+    let path = app_handle.get_recording_path(&video_id);
+    match action::export_bundle(&path, &settings.get_recordings_path(), anonymize) {
+        Ok(path) => Some(path.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::error!("failed to export bundle of {video_id}: {e:#}");
+            None
+        }
+    }
+}
+
+/// adds the recording of the bundle (e.g. sent back by a coach) to the recordings, returns the video id of the
+/// imported recording
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn import_bundle(path: String, settings: State<SettingsWrapper>) -> Option<String> {
+    match action::import_bundle(Path::new(&path), &settings.get_recordings_path()) {
+        Ok(recording) => recording.file_name().map(|name| name.to_string_lossy().into_owned()),
+        Err(e) => {
+            log::error!("failed to import bundle {path}: {e:#}");
+            None
+        }
+    }
+}
+
+/// all replays in the replays folder of the League client, linked to the recordings of the same games
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn scan_replays(
+    app_handle: AppHandle,
+    recordings_index: State<RecordingsIndex>,
+    settings: State<SettingsWrapper>,
+) -> Vec<ReplayFile> {
+    let Some(replays_path) = settings.get_replays_path(&app_handle) else { return Vec::new() };
+    app::scan_replays(&replays_path, &recordings_index.recordings()).unwrap_or_else(|e| {
+        log::error!("failed to scan replays folder {}: {e:#}", replays_path.display());
+        Vec::new()
+    })
+}
+
+/// fills in the game data of a recording without game data (e.g. because the game is no longer in the match history)
+/// from the replay of the game
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn backfill_from_replay(
+    video_id: String,
+    replay_path: String,
+    app_handle: AppHandle,
+    recordings_index: State<RecordingsIndex>,
+    static_data: State<StaticData>,
+    settings: State<SettingsWrapper>,
+) -> bool {
+    let path = app_handle.get_recording_path(&video_id);
+    let accounts = recordings_index
+        .recordings()
+        .into_iter()
+        .filter_map(|recording| recording.account)
+        .collect::<Vec<_>>();
+    let embed_chapters = settings.embed_chapters();
+    action::backfill_from_replay(
+        &path,
+        Path::new(&replay_path),
+        &accounts,
+        static_data.inner(),
+        embed_chapters,
+    )
+    .inspect_err(|e| log::error!("failed to backfill {video_id} from replay {replay_path}: {e:#}"))
+    .is_ok()
+}
+
+/// starts the replay of the recorded game in the League client
+/// the client has to be running and the replay has to be downloaded (only possible for games of the current patch)
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub async fn open_replay(video_id: String, app_handle: AppHandle) -> bool {
+    let path = app_handle.get_recording_path(&video_id);
+    let match_id = match action::get_recording_metadata(&path, None) {
+        Ok(MetadataFile::Metadata(metadata)) => metadata.match_id,
+        Ok(MetadataFile::Deferred(deferred)) => deferred.match_id,
+        _ => return false,
+    };
+
+    let result = match LcuRestClient::new() {
+        Ok(lcu_rest_client) => recorder::watch_replay(&lcu_rest_client, &match_id).await,
+        Err(e) => Err(e.into()),
+    };
+    result
+        .inspect_err(|e| log::error!("failed to open replay of {video_id}: {e:#}"))
+        .is_ok()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_collections(collections: State<Collections>) -> Vec<Collection> {
+    collections.get()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn create_collection(name: String, collections: State<Collections>) -> Option<Collection> {
+    collections
+        .create(name)
+        .inspect_err(|e| log::error!("failed to create collection: {e}"))
+        .ok()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn delete_collection(collection_id: u32, collections: State<Collections>) -> bool {
+    collections.delete(collection_id).unwrap_or_else(|e| {
+        log::error!("failed to delete collection: {e}");
+        false
+    })
+}
+
+/// moves the collection to `index` in the list of collections
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn move_collection(collection_id: u32, index: u32, collections: State<Collections>) -> bool {
+    collections
+        .move_collection(collection_id, index as usize)
+        .unwrap_or_else(|e| {
+            log::error!("failed to move collection: {e}");
+            false
+        })
+}
+
+/// `range` in seconds of the video, `None` adds the whole recording
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn add_to_collection(
+    collection_id: u32,
+    video_id: String,
+    range: Option<TimeRange>,
+    collections: State<Collections>,
+) -> Option<Collection> {
+    if range.is_some_and(|range| !range.is_valid()) {
+        log::warn!("invalid collection time range: {range:?}");
+        return None;
+    }
+
+    collections
+        .update_collection(collection_id, |collection| {
+            collection.add_item(video_id, range);
+            true
+        })
+        .inspect_err(|e| log::error!("failed to add to collection: {e}"))
+        .ok()
+        .flatten()
+}
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn remove_from_collection(collection_id: u32, item_id: u32, collections: State<Collections>) -> Option<Collection> {
+    collections
+        .update_collection(collection_id, |collection| collection.remove_item(item_id))
+        .inspect_err(|e| log::error!("failed to remove from collection: {e}"))
+        .ok()
+        .flatten()
+}
+
+/// moves the item to `index` inside of the collection
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn move_collection_item(
+    collection_id: u32,
+    item_id: u32,
+    index: u32,
+    collections: State<Collections>,
+) -> Option<Collection> {
+    collections
+        .update_collection(collection_id, |collection| {
+            collection.move_item(item_id, index as usize)
+        })
+        .inspect_err(|e| log::error!("failed to move collection item: {e}"))
+        .ok()
+        .flatten()
+}
+
+/// the items of the collection in playback order, items of recordings that don't exist (anymore) are skipped
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_collection_playlist(
+    collection_id: u32,
+    collections: State<Collections>,
+    recordings_index: State<RecordingsIndex>,
+) -> Vec<CollectionItem> {
+    let Some(collection) = collections.get_collection(collection_id) else { return Vec::new() };
+    collection
+        .items
+        .into_iter()
+        .filter(|item| recordings_index.get(&item.video_id).is_some())
+        .collect()
 }
 
 #[cfg_attr(test, specta::specta)]
@@ -140,3 +844,9 @@ pub fn disable_confirm_delete(settings: State<SettingsWrapper>, settings_file: S
     settings.set_confirm_delete(false);
     settings.write_to_file(settings_file.get());
 }
+
+#[cfg_attr(test, specta::specta)]
+#[tauri::command]
+pub fn get_static_data(static_data: State<StaticData>) -> GameDataSummary {
+    static_data.get().as_ref().clone()
+}
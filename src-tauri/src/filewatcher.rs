@@ -5,13 +5,15 @@ use notify::event::{ModifyKind, RenameMode};
 use notify::{EventKind, Watcher};
 use tauri::{AppHandle, Manager};
 
-use crate::app::{AppEvent, EventManager};
+use crate::app::{self, AppEvent, EventManager, Session};
 use crate::state::CurrentlyRecording;
 use crate::state::FileWatcher;
+use crate::state::{RecordingsIndex, SettingsWrapper};
 
 pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
     let watcher = notify::recommended_watcher({
         let app_handle = app_handle.clone();
+        let recordings_path = recordings_path.to_owned();
         move |res: notify::Result<notify::Event>| {
             let Ok(event) = res else { return };
 
@@ -19,16 +21,29 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
 
             let mut contains_mp4_path: bool = false;
             let mut json_paths: Vec<String> = Vec::new();
+            let mut changed_recordings: Vec<PathBuf> = Vec::new();
 
             for path in event.paths {
                 if Some(&path) == currently_recording.as_ref() {
                     continue;
                 }
 
+                // hidden files and folders are internal files of the app (e.g. collections, transaction backups or
+                // the quarantine folder)
+                if is_hidden(&path, &recordings_path) {
+                    continue;
+                }
+
                 let ext = path.extension().and_then(OsStr::to_str);
 
                 contains_mp4_path |= ext == Some("mp4");
 
+                // the metadata file of the video that is currently being recorded also has to be ignored
+                let is_currently_recording = Some(path.with_extension("mp4")) == currently_recording;
+                if (ext == Some("mp4") || ext == Some("json")) && !is_currently_recording {
+                    changed_recordings.push(path.clone());
+                }
+
                 if ext == Some("json") {
                     if let Some(video_id) = path.file_name().and_then(OsStr::to_str).map(str::to_owned) {
                         json_paths.push(video_id);
@@ -36,6 +51,20 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
                 }
             }
 
+            // in-place changes (e.g. embedding chapters into the video) change the size and modified time as well
+            if matches!(
+                event.kind,
+                EventKind::Create(_)
+                    | EventKind::Remove(_)
+                    | EventKind::Modify(ModifyKind::Name(_) | ModifyKind::Data(_) | ModifyKind::Any)
+            ) {
+                let recordings_index = app_handle.state::<RecordingsIndex>();
+                for path in &changed_recordings {
+                    recordings_index.update(path);
+                }
+                send_session_updates(&app_handle, &changed_recordings);
+            }
+
             match event.kind {
                 EventKind::Create(_) => {
                     if contains_mp4_path {
@@ -67,8 +96,9 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
                         }
                     }
                 }
+                // 'From' is needed for files that get moved to a hidden backup file on delete (see app::transaction)
                 EventKind::Modify(ModifyKind::Name(
-                    RenameMode::To | RenameMode::Both | RenameMode::Any | RenameMode::Other,
+                    RenameMode::From | RenameMode::To | RenameMode::Both | RenameMode::Any | RenameMode::Other,
                 )) => {
                     if contains_mp4_path {
                         log::info!("filewatcher event contains .mp4 path: {contains_mp4_path}");
@@ -91,7 +121,8 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
 
     match watcher {
         Ok(mut watcher) => {
-            _ = watcher.watch(recordings_path, notify::RecursiveMode::NonRecursive);
+            // recursive so recordings in account subfolders are watched as well
+            _ = watcher.watch(recordings_path, notify::RecursiveMode::Recursive);
 
             // store Watcher so it doesn't drop and stop watching
             // also drop old watcher
@@ -104,3 +135,59 @@ pub fn replace(app_handle: &AppHandle, recordings_path: &Path) {
         Err(e) => log::error!("failed to start filewatcher: {e}"),
     }
 }
+
+/// the name of the file or of any folder below `recordings_path` starts with a '.'
+fn is_hidden(path: &Path, recordings_path: &Path) -> bool {
+    // only the file name can be checked if the event path isn't inside of the recordings folder
+    let relative = path
+        .strip_prefix(recordings_path)
+        .ok()
+        .or_else(|| path.file_name().map(Path::new));
+    relative.is_some_and(|relative| {
+        relative
+            .components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+    })
+}
+
+/// sends the updated play session of every changed recording that still exists
+fn send_session_updates(app_handle: &AppHandle, changed_recordings: &[PathBuf]) {
+    let recordings = app_handle.state::<RecordingsIndex>().recordings();
+    let gap_minutes = app_handle.state::<SettingsWrapper>().session_gap_minutes();
+
+    let mut updated_sessions = Vec::<Session>::new();
+    for path in changed_recordings {
+        let video_path = path.with_extension("mp4");
+        // clips and recordings without game data aren't part of a session
+        let Some(summary) = recordings
+            .iter()
+            .find(|summary| summary.path == video_path && summary.is_game())
+        else {
+            continue;
+        };
+
+        // sessions are per account
+        let account_recordings = recordings
+            .iter()
+            .filter(|recording| recording.is_game() && recording.account_id == summary.account_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        let Some(session) = app::sessions(&account_recordings, gap_minutes)
+            .into_iter()
+            .find(|session| session.contains(&summary.video_id))
+        else {
+            continue;
+        };
+
+        if updated_sessions
+            .iter()
+            .any(|updated| updated.video_ids == session.video_ids)
+        {
+            continue;
+        }
+        if let Err(e) = app_handle.send_event(AppEvent::SessionUpdated { payload: session.clone() }) {
+            log::warn!("filewatcher failed to send event: {e:?}");
+        }
+        updated_sessions.push(session);
+    }
+}
@@ -11,13 +11,51 @@ fn generate_bindings() -> anyhow::Result<()> {
             commands::get_recordings_path,
             commands::get_recordings_size,
             commands::get_recordings_list,
+            commands::search_recordings,
+            commands::get_statistics,
+            commands::get_sessions,
+            commands::get_accounts,
+            commands::get_account_recordings,
+            commands::get_video_path,
             commands::open_recordings_folder,
             commands::delete_video,
             commands::rename_video,
             commands::get_metadata,
             commands::toggle_favorite,
+            commands::set_notes,
+            commands::add_tag,
+            commands::remove_tag,
+            commands::add_comment,
+            commands::edit_comment,
+            commands::delete_comment,
+            commands::set_watched,
+            commands::set_reviewed,
+            commands::set_playback_position,
+            commands::get_review_queue,
+            commands::get_recording_deaths,
+            commands::get_death_review,
+            commands::export_clip,
+            commands::export_highlight_reel,
+            commands::export_markers,
+            commands::export_ledger,
+            commands::get_report,
+            commands::save_report,
+            commands::export_bundle,
+            commands::import_bundle,
+            commands::scan_replays,
+            commands::backfill_from_replay,
+            commands::open_replay,
+            commands::get_collections,
+            commands::create_collection,
+            commands::delete_collection,
+            commands::move_collection,
+            commands::add_to_collection,
+            commands::remove_from_collection,
+            commands::move_collection_item,
+            commands::get_collection_playlist,
             commands::confirm_delete,
-            commands::disable_confirm_delete
+            commands::disable_confirm_delete,
+            commands::get_static_data
         ])
         .events(collect_events![AppEvent])
         .export(
@@ -34,8 +72,18 @@ fn generate_type_bindings() -> anyhow::Result<()> {
     use specta::{function::fn_datatype, TypeMap};
     use specta_typescript::{export_named_datatype, BigIntExportBehavior, Typescript};
 
+    /// content of the metadata file of a recording (`{videoName}.json`)
+    #[derive(serde::Serialize, specta::Type)]
+    #[serde(rename_all = "camelCase")]
+    struct MetadataFileJson {
+        #[serde(flatten)]
+        metadata_file: MetadataFile,
+        /// version of the format of the metadata file, older files get migrated when LeagueRecord reads them
+        schema_version: u64,
+    }
+
     #[specta::specta]
-    fn _tmp(_types: (Settings, MetadataFile)) {}
+    fn _tmp(_types: (Settings, MetadataFile, MetadataFileJson)) {}
 
     let mut type_map = TypeMap::default();
     _ = fn_datatype!(_tmp)(&mut type_map);
@@ -8,7 +8,9 @@ mod commands;
 mod constants;
 mod filewatcher;
 mod generate_bindings;
+mod mp4;
 mod recorder;
+mod rofl;
 mod state;
 mod util;
 
@@ -36,19 +38,58 @@ fn main() {
         .manage(TrayState::default())
         .manage(windows_key_listener::KeyListener::new())
         .manage(Shutdown::default())
+        .register_asynchronous_uri_scheme_protocol(app::RECORDING_PROTOCOL, app::recording_protocol)
         .invoke_handler(tauri::generate_handler![
             commands::get_marker_flags,
             commands::set_marker_flags,
             commands::get_recordings_path,
             commands::get_recordings_size,
             commands::get_recordings_list,
+            commands::search_recordings,
+            commands::get_statistics,
+            commands::get_sessions,
+            commands::get_accounts,
+            commands::get_account_recordings,
+            commands::get_video_path,
             commands::open_recordings_folder,
             commands::delete_video,
             commands::rename_video,
             commands::get_metadata,
             commands::toggle_favorite,
+            commands::set_notes,
+            commands::add_tag,
+            commands::remove_tag,
+            commands::add_comment,
+            commands::edit_comment,
+            commands::delete_comment,
+            commands::set_watched,
+            commands::set_reviewed,
+            commands::set_playback_position,
+            commands::get_review_queue,
+            commands::get_recording_deaths,
+            commands::get_death_review,
+            commands::export_clip,
+            commands::export_highlight_reel,
+            commands::export_markers,
+            commands::export_ledger,
+            commands::get_report,
+            commands::save_report,
+            commands::export_bundle,
+            commands::import_bundle,
+            commands::scan_replays,
+            commands::backfill_from_replay,
+            commands::open_replay,
+            commands::get_collections,
+            commands::create_collection,
+            commands::delete_collection,
+            commands::move_collection,
+            commands::add_to_collection,
+            commands::remove_from_collection,
+            commands::move_collection_item,
+            commands::get_collection_playlist,
             commands::confirm_delete,
-            commands::disable_confirm_delete
+            commands::disable_confirm_delete,
+            commands::get_static_data
         ])
         .setup(|app| app.app_handle().setup().map_err(anyhow::Error::into))
         .build(tauri::generate_context!());
@@ -0,0 +1,342 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use anyhow::{bail, Context, Result};
+
+pub type FourCC = [u8; 4];
+
+/// boxes that only contain other boxes and get parsed into a tree
+/// all other boxes are kept as raw bytes
+const CONTAINERS: &[&FourCC] = &[b"moov", b"trak", b"mdia", b"minf", b"stbl", b"edts"];
+
+#[derive(Debug, Clone)]
+pub enum Content {
+    Leaf(Vec<u8>),
+    Container(Vec<Mp4Box>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Mp4Box {
+    pub kind: FourCC,
+    pub content: Content,
+}
+
+impl Mp4Box {
+    pub fn leaf(kind: &FourCC, data: Vec<u8>) -> Self {
+        Self {
+            kind: *kind,
+            content: Content::Leaf(data),
+        }
+    }
+
+    pub fn container(kind: &FourCC, children: Vec<Mp4Box>) -> Self {
+        Self {
+            kind: *kind,
+            content: Content::Container(children),
+        }
+    }
+
+    /// parses a box with its header from the start of `data` and returns it together with its size
+    pub fn parse(data: &[u8]) -> Result<(Self, usize)> {
+        let header = Header::parse(data)?;
+        let size = header.size.map_or(data.len(), |size| size as usize);
+        if size < header.len || size > data.len() {
+            bail!("invalid size of box {}", fourcc(&header.kind));
+        }
+
+        let body = &data[header.len..size];
+        let content = if CONTAINERS.contains(&&header.kind) {
+            Content::Container(Self::parse_all(body)?)
+        } else {
+            Content::Leaf(body.to_vec())
+        };
+
+        Ok((Self { kind: header.kind, content }, size))
+    }
+
+    pub fn parse_all(mut data: &[u8]) -> Result<Vec<Self>> {
+        let mut boxes = Vec::new();
+        // anything smaller than a box header is padding
+        while data.len() >= 8 {
+            let (mp4_box, size) = Self::parse(data)?;
+            boxes.push(mp4_box);
+            data = &data[size..];
+        }
+        Ok(boxes)
+    }
+
+    pub fn children(&self) -> &[Mp4Box] {
+        match &self.content {
+            Content::Container(children) => children,
+            Content::Leaf(_) => &[],
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Mp4Box>> {
+        match &mut self.content {
+            Content::Container(children) => Some(children),
+            Content::Leaf(_) => None,
+        }
+    }
+
+    pub fn data(&self) -> Option<&[u8]> {
+        match &self.content {
+            Content::Leaf(data) => Some(data),
+            Content::Container(_) => None,
+        }
+    }
+
+    pub fn data_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match &mut self.content {
+            Content::Leaf(data) => Some(data),
+            Content::Container(_) => None,
+        }
+    }
+
+    pub fn child(&self, kind: &FourCC) -> Option<&Mp4Box> {
+        self.children().iter().find(|child| &child.kind == kind)
+    }
+
+    pub fn child_mut(&mut self, kind: &FourCC) -> Option<&mut Mp4Box> {
+        self.children_mut()?.iter_mut().find(|child| &child.kind == kind)
+    }
+
+    /// data of the leaf box at the end of `path`
+    pub fn find_data(&self, path: &[&FourCC]) -> Result<&[u8]> {
+        let mut current = self;
+        for kind in path {
+            current = current
+                .child(kind)
+                .with_context(|| format!("missing box {}", fourcc(kind)))?;
+        }
+        current.data().context("expected leaf box")
+    }
+
+    pub fn find_data_mut(&mut self, path: &[&FourCC]) -> Result<&mut Vec<u8>> {
+        let mut current = self;
+        for kind in path {
+            current = current
+                .child_mut(kind)
+                .with_context(|| format!("missing box {}", fourcc(kind)))?;
+        }
+        current.data_mut().context("expected leaf box")
+    }
+
+    /// size of the box including its header
+    pub fn size(&self) -> u64 {
+        let body = match &self.content {
+            Content::Leaf(data) => data.len() as u64,
+            Content::Container(children) => children.iter().map(Mp4Box::size).sum(),
+        };
+        if body + 8 > u32::MAX as u64 {
+            body + 16
+        } else {
+            body + 8
+        }
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let size = self.size();
+        if size > u32::MAX as u64 {
+            out.extend_from_slice(&1u32.to_be_bytes());
+            out.extend_from_slice(&self.kind);
+            out.extend_from_slice(&size.to_be_bytes());
+        } else {
+            out.extend_from_slice(&(size as u32).to_be_bytes());
+            out.extend_from_slice(&self.kind);
+        }
+
+        match &self.content {
+            Content::Leaf(data) => out.extend_from_slice(data),
+            Content::Container(children) => children.iter().for_each(|child| child.write(out)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Header {
+    pub kind: FourCC,
+    /// size including the header, `None` if the box extends to the end of the file
+    pub size: Option<u64>,
+    /// length of the header itself
+    pub len: usize,
+}
+
+impl Header {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(data);
+        let size = reader.u32()?;
+        let kind = reader.fourcc()?;
+        match size {
+            0 => Ok(Self { kind, size: None, len: 8 }),
+            1 => Ok(Self {
+                kind,
+                size: Some(reader.u64()?),
+                len: 16,
+            }),
+            size => Ok(Self {
+                kind,
+                size: Some(size as u64),
+                len: 8,
+            }),
+        }
+    }
+
+    /// reads the header of the box at the current position of the file
+    pub fn read(file: &mut File) -> Result<Option<Self>> {
+        let mut buffer = [0; 16];
+        let mut read = 0;
+        while read < 8 {
+            match file.read(&mut buffer[read..8])? {
+                0 if read == 0 => return Ok(None),
+                0 => bail!("unexpected end of file"),
+                n => read += n,
+            }
+        }
+        if buffer[0..4] == 1u32.to_be_bytes() {
+            file.read_exact(&mut buffer[8..16])?;
+        }
+        Self::parse(&buffer).map(Some)
+    }
+}
+
+/// position of a box at the top level of a file
+#[derive(Debug, Clone, Copy)]
+pub struct TopLevelBox {
+    pub kind: FourCC,
+    /// of the header
+    pub position: u64,
+    /// size including the header
+    pub size: u64,
+    /// the size in the header is 0 (the box extends to the end of the file)
+    pub open_ended: bool,
+    pub header_len: usize,
+}
+
+impl TopLevelBox {
+    pub fn read_body(&self, file: &mut File) -> Result<Vec<u8>> {
+        let mut data = vec![0; (self.size - self.header_len as u64) as usize];
+        file.seek(SeekFrom::Start(self.position + self.header_len as u64))?;
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// a truncated box at the end of the file is left out if it comes after the first `moov` box
+/// (e.g. from an interrupted append of `embed()`), players ignore it as well
+pub fn top_level_boxes(file: &mut File) -> Result<Vec<TopLevelBox>> {
+    let file_len = file.metadata()?.len();
+    let mut boxes = Vec::<TopLevelBox>::new();
+    let has_moov = |boxes: &[TopLevelBox]| boxes.iter().any(|top_level_box| &top_level_box.kind == b"moov");
+
+    let mut position = 0;
+    file.seek(SeekFrom::Start(0))?;
+    loop {
+        let header = match Header::read(file) {
+            Ok(Some(header)) => header,
+            Ok(None) => break,
+            Err(e) if has_moov(&boxes) => {
+                log::warn!("ignoring truncated data at the end of the file: {e}");
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let size = header.size.unwrap_or(file_len - position);
+        if size < header.len as u64 || position + size > file_len {
+            if has_moov(&boxes) {
+                log::warn!(
+                    "ignoring truncated top-level box {} at the end of the file",
+                    fourcc(&header.kind)
+                );
+                break;
+            }
+            bail!("invalid size of top-level box {}", fourcc(&header.kind));
+        }
+        if &header.kind == b"moof" {
+            bail!("fragmented mp4 files are not supported");
+        }
+
+        boxes.push(TopLevelBox {
+            kind: header.kind,
+            position,
+            size,
+            open_ended: header.size.is_none(),
+            header_len: header.len,
+        });
+
+        position += size;
+        file.seek(SeekFrom::Start(position))?;
+    }
+    Ok(boxes)
+}
+
+/// the `ftyp` and `moov` boxes of the file
+/// only the first `moov` counts (same as for players), a later one can be left over from an interrupted rewrite
+pub fn read_top_level(file: &mut File) -> Result<(Vec<u8>, Mp4Box)> {
+    let boxes = top_level_boxes(file)?;
+    let find = |kind: &FourCC| boxes.iter().find(|top_level_box| &top_level_box.kind == kind);
+
+    let ftyp = find(b"ftyp").context("missing ftyp box")?.read_body(file)?;
+    let moov = find(b"moov").context("missing moov box")?.read_body(file)?;
+    Ok((ftyp, Mp4Box::container(b"moov", Mp4Box::parse_all(&moov)?)))
+}
+
+pub fn fourcc(kind: &FourCC) -> String {
+    String::from_utf8_lossy(kind).into_owned()
+}
+
+/// big-endian reader for the fields of a box
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            bail!("unexpected end of box");
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    pub fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.bytes(8)?.try_into()?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.bytes(8)?.try_into()?))
+    }
+
+    pub fn fourcc(&mut self) -> Result<FourCC> {
+        Ok(self.bytes(4)?.try_into()?)
+    }
+
+    /// version and flags of a full box, returns the version
+    pub fn version(&mut self) -> Result<u8> {
+        let version = self.u8()?;
+        self.skip(3)?;
+        Ok(version)
+    }
+}
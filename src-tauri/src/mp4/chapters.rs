@@ -0,0 +1,409 @@
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::boxes::{self, FourCC, Mp4Box, Reader};
+use super::sample_table::{self, push_u32, Sample};
+use super::{set_duration, timescale};
+
+/// namespace of the custom tags
+const TAG_NAMESPACE: &str = "com.leaguerecord";
+/// timescale of the chapter track (milliseconds)
+const CHAPTER_TIMESCALE: u32 = 1000;
+/// handler of the chapter track, recordings don't have any other text tracks
+const CHAPTER_HANDLER: &FourCC = b"text";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    /// in seconds of the video
+    pub start: f64,
+    pub title: String,
+}
+
+/// metadata tags (iTunes style `ilst` tags, like most players read them)
+#[derive(Debug, Clone, Default)]
+pub struct Tags {
+    pub title: Option<String>,
+    pub comment: Option<String>,
+    /// freeform key value pairs
+    pub custom: Vec<(String, String)>,
+}
+
+/// embeds the chapters and tags into the mp4 file without touching the video and audio data
+///
+/// the new `moov` box gets appended to the end of the file and only afterwards the old ones are turned into `free`
+/// boxes, so if the rewrite gets interrupted the old `moov` is still the first one and the file stays playable
+/// existing chapters and tags that were written by this function get replaced
+pub fn embed(path: &Path, chapters: &[Chapter], tags: &Tags) -> Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let top_level = boxes::top_level_boxes(&mut file)?;
+    let old_moov = *top_level
+        .iter()
+        .find(|top_level_box| &top_level_box.kind == b"moov")
+        .context("missing moov box")?;
+    let mut moov = Mp4Box::container(b"moov", Mp4Box::parse_all(&old_moov.read_body(&mut file)?)?);
+
+    // drop the truncated box of an interrupted previous `embed()` so the new boxes directly follow the valid ones
+    let file_len = top_level.last().map_or(0, |last| last.position + last.size);
+    if file.metadata()?.len() > file_len {
+        file.set_len(file_len)?;
+    }
+
+    // a box that extends to the end of the file would swallow the appended boxes
+    if let Some(last) = top_level.last().filter(|last| last.open_ended) {
+        let size = u32::try_from(last.size).context("last box is too big")?;
+        file.seek(SeekFrom::Start(last.position))?;
+        file.write_all(&size.to_be_bytes())?;
+    }
+
+    remove_chapter_tracks(&mut moov)?;
+
+    // the chapter titles are the samples of the chapter track and get stored in their own mdat box
+    let mut mdat = Vec::new();
+    let mut samples = Vec::new();
+    let chapter_data_offset = file_len + 8;
+    let movie_duration = movie_seconds(&moov)?;
+    for (index, chapter) in chapters.iter().enumerate() {
+        let start = (chapter.start * CHAPTER_TIMESCALE as f64) as u64;
+        let end = chapters
+            .get(index + 1)
+            .map_or(movie_duration, |next| next.start)
+            .max(chapter.start);
+        let data = chapter_sample(&chapter.title);
+        samples.push(Sample {
+            offset: chapter_data_offset + mdat.len() as u64,
+            size: data.len() as u32,
+            decode_time: start,
+            duration: ((end * CHAPTER_TIMESCALE as f64) as u64).saturating_sub(start) as u32,
+            composition_offset: 0,
+            sync: true,
+            description_index: 1,
+        });
+        mdat.extend(data);
+    }
+
+    if !samples.is_empty() {
+        let track_id = next_track_id(&mut moov)?;
+        let trak = chapter_track(&moov, track_id, &samples, chapter_data_offset)?;
+        let children = moov.children_mut().context("moov is not a container")?;
+        let position = children
+            .iter()
+            .rposition(|child| &child.kind == b"trak")
+            .map_or(children.len(), |position| position + 1);
+        children.insert(position, trak);
+        add_chapter_reference(&mut moov, track_id)?;
+    }
+
+    set_user_data(&mut moov, chapters, tags)?;
+
+    let mut appended = Vec::new();
+    Mp4Box::leaf(b"mdat", mdat).write(&mut appended);
+    moov.write(&mut appended);
+
+    let result = file
+        .seek(SeekFrom::Start(file_len))
+        .and_then(|_| file.write_all(&appended))
+        .and_then(|_| file.sync_all());
+    if let Err(e) = result {
+        // the file is still valid without the appended boxes
+        _ = file.set_len(file_len);
+        return Err(e.into());
+    }
+
+    // the only writes that change which moov box is used
+    // an `embed()` that got interrupted before this point can have left a complete moov box behind as well, which would
+    // become the first one if only `old_moov` was freed
+    for moov_box in top_level.iter().filter(|top_level_box| &top_level_box.kind == b"moov") {
+        file.seek(SeekFrom::Start(moov_box.position + 4))?;
+        file.write_all(b"free")?;
+    }
+    file.sync_all()?;
+
+    Ok(())
+}
+
+fn is_chapter_track(trak: &Mp4Box) -> bool {
+    &trak.kind == b"trak"
+        && trak
+            .find_data(&[b"mdia", b"hdlr"])
+            .is_ok_and(|hdlr| hdlr.get(8..12) == Some(CHAPTER_HANDLER))
+}
+
+/// removes the chapter track of a previous `embed()` and the references to it
+pub fn remove_chapter_tracks(moov: &mut Mp4Box) -> Result<()> {
+    let children = moov.children_mut().context("moov is not a container")?;
+    children.retain(|child| !is_chapter_track(child));
+
+    for trak in children.iter_mut().filter(|child| &child.kind == b"trak") {
+        let Some(tref) = trak.child_mut(b"tref") else { continue };
+        let references = Mp4Box::parse_all(tref.data().unwrap_or_default())?
+            .into_iter()
+            .filter(|reference| &reference.kind != b"chap")
+            .collect::<Vec<_>>();
+
+        if references.is_empty() {
+            let trak_children = trak.children_mut().context("trak is not a container")?;
+            trak_children.retain(|child| &child.kind != b"tref");
+        } else {
+            let mut data = Vec::new();
+            references.iter().for_each(|reference| reference.write(&mut data));
+            *tref = Mp4Box::leaf(b"tref", data);
+        }
+    }
+    Ok(())
+}
+
+/// the video track (or the first track) references the chapter track
+fn add_chapter_reference(moov: &mut Mp4Box, track_id: u32) -> Result<()> {
+    let children = moov.children_mut().context("moov is not a container")?;
+    let index = children
+        .iter()
+        .position(|child| &child.kind == b"trak" && child.find_data(&[b"mdia", b"hdlr"]).is_ok_and(is_video))
+        .or_else(|| {
+            children
+                .iter()
+                .position(|child| &child.kind == b"trak" && !is_chapter_track(child))
+        })
+        .context("no track for the chapters")?;
+    let trak = children[index].children_mut().context("trak is not a container")?;
+
+    let mut chap = Vec::new();
+    push_u32(&mut chap, track_id);
+    let mut data = Vec::new();
+    Mp4Box::leaf(b"chap", chap).write(&mut data);
+
+    match trak.iter_mut().find(|child| &child.kind == b"tref") {
+        Some(tref) => tref.data_mut().context("tref is not a leaf")?.extend(data),
+        None => {
+            let position = trak
+                .iter()
+                .position(|child| &child.kind == b"tkhd")
+                .map_or(0, |position| position + 1);
+            trak.insert(position, Mp4Box::leaf(b"tref", data));
+        }
+    }
+    Ok(())
+}
+
+fn is_video(hdlr: &[u8]) -> bool {
+    hdlr.get(8..12) == Some(b"vide")
+}
+
+/// reserves a new track id in the mvhd box
+fn next_track_id(moov: &mut Mp4Box) -> Result<u32> {
+    // some muxers don't fill in next_track_ID, so also look at the existing tracks
+    let max_track_id = moov
+        .children()
+        .iter()
+        .filter(|child| &child.kind == b"trak")
+        .filter_map(|trak| trak.find_data(&[b"tkhd"]).ok())
+        .filter_map(|tkhd| {
+            let mut reader = Reader::new(tkhd);
+            let skip = if reader.version().ok()? == 1 { 16 } else { 8 };
+            reader.skip(skip).ok()?;
+            reader.u32().ok()
+        })
+        .max()
+        .unwrap_or_default();
+
+    let mvhd = moov.find_data_mut(&[b"mvhd"])?;
+    let offset = if mvhd.first() == Some(&1) { 108 } else { 96 };
+    let field = mvhd.get_mut(offset..offset + 4).context("mvhd too short")?;
+    let track_id = u32::from_be_bytes((&*field).try_into()?).max(max_track_id + 1);
+    field.copy_from_slice(&(track_id + 1).to_be_bytes());
+    Ok(track_id)
+}
+
+fn movie_seconds(moov: &Mp4Box) -> Result<f64> {
+    let mvhd = moov.find_data(&[b"mvhd"])?;
+    let timescale = timescale(mvhd)?;
+    let mut reader = Reader::new(mvhd);
+    let duration = if reader.version()? == 1 {
+        reader.skip(20)?;
+        reader.u64()?
+    } else {
+        reader.skip(12)?;
+        reader.u32()? as u64
+    };
+    Ok(duration as f64 / timescale.max(1) as f64)
+}
+
+/// QuickTime text sample: length, UTF-8 text and the encoding
+fn chapter_sample(title: &str) -> Vec<u8> {
+    let title = &title.as_bytes()[..title.len().min(u16::MAX as usize)];
+    let mut data = Vec::with_capacity(title.len() + 14);
+    data.extend_from_slice(&(title.len() as u16).to_be_bytes());
+    data.extend_from_slice(title);
+    push_u32(&mut data, 12);
+    data.extend_from_slice(b"encd");
+    push_u32(&mut data, 0x100);
+    data
+}
+
+fn chapter_track(moov: &Mp4Box, track_id: u32, samples: &[Sample], chunk_offset: u64) -> Result<Mp4Box> {
+    let media_duration = samples.iter().map(|sample| sample.duration as u64).sum::<u64>();
+    let movie_timescale = timescale(moov.find_data(&[b"mvhd"])?)?;
+    let duration = media_duration * movie_timescale as u64 / CHAPTER_TIMESCALE as u64;
+
+    // disabled so players don't show the chapter titles as subtitles
+    let mut tkhd = vec![0; 84];
+    tkhd[12..16].copy_from_slice(&track_id.to_be_bytes());
+    let matrix: [u32; 9] = [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000];
+    for (index, value) in matrix.iter().enumerate() {
+        tkhd[40 + index * 4..44 + index * 4].copy_from_slice(&value.to_be_bytes());
+    }
+    set_duration(&mut tkhd, 20, 28, duration)?;
+
+    let mut mdhd = vec![0; 24];
+    mdhd[12..16].copy_from_slice(&CHAPTER_TIMESCALE.to_be_bytes());
+    mdhd[20..22].copy_from_slice(&0x55c4u16.to_be_bytes()); // "und"
+    set_duration(&mut mdhd, 16, 24, media_duration)?;
+
+    let mut hdlr = vec![0; 8];
+    hdlr.extend_from_slice(CHAPTER_HANDLER);
+    hdlr.extend_from_slice(&[0; 12]);
+    hdlr.extend_from_slice(b"Chapters\0");
+
+    // the data is in the same file
+    let mut dref = vec![0; 4];
+    push_u32(&mut dref, 1);
+    Mp4Box::leaf(b"url ", vec![0, 0, 0, 1]).write(&mut dref);
+    let mut dinf = Vec::new();
+    Mp4Box::leaf(b"dref", dref).write(&mut dinf);
+
+    // QuickTime text sample entry with default formatting
+    let mut text = vec![0; 6];
+    text.extend_from_slice(&1u16.to_be_bytes());
+    text.resize(52, 0);
+    let mut stsd = vec![0; 4];
+    push_u32(&mut stsd, 1);
+    Mp4Box::leaf(b"text", text).write(&mut stsd);
+
+    let stbl = Mp4Box::container(b"stbl", vec![Mp4Box::leaf(b"stsd", stsd)]);
+    let stbl = sample_table::sample_table(&stbl, samples, &[(samples.len() as u32, chunk_offset)])?;
+
+    Ok(Mp4Box::container(
+        b"trak",
+        vec![
+            Mp4Box::leaf(b"tkhd", tkhd),
+            Mp4Box::container(
+                b"mdia",
+                vec![
+                    Mp4Box::leaf(b"mdhd", mdhd),
+                    Mp4Box::leaf(b"hdlr", hdlr),
+                    Mp4Box::container(
+                        b"minf",
+                        vec![Mp4Box::leaf(b"nmhd", vec![0; 4]), Mp4Box::leaf(b"dinf", dinf), stbl],
+                    ),
+                ],
+            ),
+        ],
+    ))
+}
+
+/// replaces the Nero chapters (`chpl`) and the tags in `moov/udta`, other user data is kept
+fn set_user_data(moov: &mut Mp4Box, chapters: &[Chapter], tags: &Tags) -> Result<()> {
+    let children = moov.children_mut().context("moov is not a container")?;
+    let (mut user_data, old_meta) = match children.iter().position(|child| &child.kind == b"udta") {
+        Some(index) => {
+            let udta = children.remove(index);
+            let mut user_data = Mp4Box::parse_all(udta.data().unwrap_or_default())?;
+            let meta = user_data
+                .iter()
+                .position(|child| &child.kind == b"meta")
+                .map(|index| user_data.remove(index));
+            user_data.retain(|child| &child.kind != b"chpl");
+            (user_data, meta)
+        }
+        None => (Vec::new(), None),
+    };
+
+    // Nero chapters for players that don't read chapter tracks, 100ns units and at most 255 chapters
+    let mut chpl = vec![1, 0, 0, 0];
+    push_u32(&mut chpl, 0);
+    let chapters = &chapters[..chapters.len().min(u8::MAX as usize)];
+    chpl.push(chapters.len() as u8);
+    for chapter in chapters {
+        chpl.extend_from_slice(&((chapter.start * 10_000_000.0) as u64).to_be_bytes());
+        let title = &chapter.title.as_bytes()[..chapter.title.len().min(u8::MAX as usize)];
+        chpl.push(title.len() as u8);
+        chpl.extend_from_slice(title);
+    }
+    user_data.push(Mp4Box::leaf(b"chpl", chpl));
+    user_data.push(meta(old_meta.as_ref(), tags)?);
+
+    let mut udta = Vec::new();
+    user_data.iter().for_each(|child| child.write(&mut udta));
+    children.push(Mp4Box::leaf(b"udta", udta));
+    Ok(())
+}
+
+/// `meta` box with the tags, items of the old `meta` box that aren't set by `tags` are kept
+fn meta(old_meta: Option<&Mp4Box>, tags: &Tags) -> Result<Mp4Box> {
+    let mut items = Vec::new();
+    if let Some(old_ilst) = old_meta
+        .and_then(|meta| meta.data())
+        .and_then(|data| Mp4Box::parse_all(data.get(4..)?).ok())
+        .and_then(|children| children.into_iter().find(|child| &child.kind == b"ilst"))
+    {
+        items = Mp4Box::parse_all(old_ilst.data().unwrap_or_default())?;
+    }
+
+    // freeform tags of this namespace always get rewritten completely
+    items.retain(|item| {
+        let replaced = match &item.kind {
+            b"\xa9nam" => tags.title.is_some(),
+            b"\xa9cmt" => tags.comment.is_some(),
+            b"----" => freeform_namespace(item).as_deref() == Some(TAG_NAMESPACE),
+            _ => false,
+        };
+        !replaced
+    });
+
+    if let Some(title) = &tags.title {
+        items.push(Mp4Box::leaf(b"\xa9nam", text_data(title)));
+    }
+    if let Some(comment) = &tags.comment {
+        items.push(Mp4Box::leaf(b"\xa9cmt", text_data(comment)));
+    }
+    for (key, value) in &tags.custom {
+        let mut freeform = Vec::new();
+        Mp4Box::leaf(b"mean", [&[0; 4], TAG_NAMESPACE.as_bytes()].concat()).write(&mut freeform);
+        Mp4Box::leaf(b"name", [&[0; 4], key.as_bytes()].concat()).write(&mut freeform);
+        freeform.extend(text_data(value));
+        items.push(Mp4Box::leaf(b"----", freeform));
+    }
+
+    let mut ilst = Vec::new();
+    items.iter().for_each(|item| item.write(&mut ilst));
+
+    let mut hdlr = vec![0; 8];
+    hdlr.extend_from_slice(b"mdir");
+    hdlr.extend_from_slice(b"appl");
+    hdlr.extend_from_slice(&[0; 9]);
+
+    let mut meta = vec![0; 4];
+    Mp4Box::leaf(b"hdlr", hdlr).write(&mut meta);
+    Mp4Box::leaf(b"ilst", ilst).write(&mut meta);
+    Ok(Mp4Box::leaf(b"meta", meta))
+}
+
+/// `data` box with a UTF-8 value
+fn text_data(value: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    push_u32(&mut data, 1);
+    push_u32(&mut data, 0);
+    data.extend_from_slice(value.as_bytes());
+
+    let mut item = Vec::new();
+    Mp4Box::leaf(b"data", data).write(&mut item);
+    item
+}
+
+fn freeform_namespace(item: &Mp4Box) -> Option<String> {
+    let children = Mp4Box::parse_all(item.data()?).ok()?;
+    let mean = children.iter().find(|child| &child.kind == b"mean")?.data()?;
+    Some(String::from_utf8_lossy(mean.get(4..)?).into_owned())
+}
@@ -0,0 +1,929 @@
+//! lossless cutting and joining of (non-fragmented) mp4 files
+//! the samples inside of the cut ranges get copied into a new file without re-encoding, so a cut can only start at a
+//! keyframe of the video
+
+mod boxes;
+mod chapters;
+mod sample_table;
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use boxes::{Mp4Box, Reader};
+use sample_table::{push_u32, Sample, Track};
+
+pub use chapters::{embed, Chapter, Tags};
+
+/// max duration of a chunk in seconds, chunks of all tracks get interleaved so playback doesn't have to seek around
+const CHUNK_DURATION: f64 = 1.0;
+
+/// the range of the source video that actually got cut, in seconds of the video
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CutRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// part of a video file that gets copied by `concat`
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub path: PathBuf,
+    /// in seconds of the video
+    pub start: f64,
+    /// in seconds of the video
+    pub end: f64,
+}
+
+/// copies the part of `input` between `start` and `end` (in seconds of the video) into `output`
+/// the cut starts at the last keyframe before `start`, so the returned range can start earlier than requested
+pub fn cut(input: &Path, output: &Path, start: f64, end: f64) -> Result<CutRange> {
+    let segment = Segment {
+        path: input.to_owned(),
+        start,
+        end,
+    };
+    concat(&[segment], output)?.pop().context("missing cut range")
+}
+
+/// copies the segments one after another into `output`
+/// all source files need the same tracks with the same encoder settings (e.g. recordings with the same settings)
+/// every segment starts at the last keyframe before its start, returns the range that got copied of every segment
+pub fn concat(segments: &[Segment], output: &Path) -> Result<Vec<CutRange>> {
+    // segments of the same file share the parsed file
+    let mut sources = Vec::<Source>::new();
+    let mut segment_sources = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if !(segment.start >= 0.0 && segment.start < segment.end) {
+            bail!("invalid range {}..{}", segment.start, segment.end);
+        }
+
+        let index = match sources.iter().position(|source| source.path == segment.path) {
+            Some(index) => index,
+            None => {
+                let source = Source::open(&segment.path)?;
+                if let Some(first) = sources.first() {
+                    first
+                        .check_compatible(&source)
+                        .with_context(|| format!("{} can't be joined", segment.path.display()))?;
+                }
+                sources.push(source);
+                sources.len() - 1
+            }
+        };
+        segment_sources.push(index);
+    }
+    let template = sources.first().context("no segments")?;
+
+    // the video track decides where the segments start and end
+    let reference = template
+        .tracks
+        .iter()
+        .position(Track::is_video)
+        .or((!template.tracks.is_empty()).then_some(0))
+        .context("no tracks")?;
+
+    let mut output_tracks = template
+        .tracks
+        .iter()
+        .map(|track| OutputTrack::new(track.timescale))
+        .collect::<Vec<_>>();
+    let mut ranges = Vec::with_capacity(segments.len());
+    for (segment, &source_index) in segments.iter().zip(&segment_sources) {
+        let tracks = &sources[source_index].tracks;
+        let (first, last) = reference_range(&tracks[reference], segment.start, segment.end)?;
+        let range = {
+            let track = &tracks[reference];
+            let start = track.seconds(&track.samples[first]);
+            let duration = track.samples[first..last]
+                .iter()
+                .map(|sample| sample.duration as f64)
+                .sum::<f64>();
+            CutRange {
+                start,
+                end: start + duration / track.timescale as f64,
+            }
+        };
+
+        // every other track gets filled up to the end of the video of the segment, so they don't drift apart
+        let output_end = output_tracks[reference].seconds() + (range.end - range.start);
+        for (index, track) in tracks.iter().enumerate() {
+            let samples = if index == reference {
+                &track.samples[first..last]
+            } else {
+                let first = track
+                    .samples
+                    .partition_point(|sample| track.seconds(sample) < range.start);
+                let end = (output_end * track.timescale as f64).round() as u64;
+                let mut duration = output_tracks[index].duration;
+                let count = track.samples[first..]
+                    .iter()
+                    .take_while(|sample| {
+                        let take = duration < end;
+                        duration += sample.duration as u64;
+                        take
+                    })
+                    .count();
+                &track.samples[first..first + count]
+            };
+            output_tracks[index].append(source_index, samples);
+        }
+
+        ranges.push(range);
+    }
+
+    // the chunks of all tracks ordered by time: (track index, first sample, number of samples)
+    let mut chunks = Vec::<(usize, usize, usize)>::new();
+    for (index, track) in output_tracks.iter().enumerate() {
+        chunks.extend(track.chunks().into_iter().map(|(first, count)| (index, first, count)));
+    }
+    chunks.sort_by(|a, b| {
+        let start = |(track, first, _): &(usize, usize, usize)| output_tracks[*track].decode_seconds(*first);
+        start(a).total_cmp(&start(b))
+    });
+
+    // the moov box has the same size with any chunk offsets (co64), so it can be built once to get the offsets
+    let mdat_len = chunks
+        .iter()
+        .map(|&(track, first, count)| output_tracks[track].size(first, count))
+        .sum::<u64>();
+    let mdat_header_len = if mdat_len + 8 > u32::MAX as u64 { 16 } else { 8 };
+    let ftyp = Mp4Box::leaf(b"ftyp", template.ftyp.clone());
+    let traks = template.traks();
+    let placeholder = build_moov(&template.moov, &traks, &output_tracks, &chunks, 0)?;
+    let data_offset = ftyp.size() + placeholder.size() + mdat_header_len;
+    let moov = build_moov(&template.moov, &traks, &output_tracks, &chunks, data_offset)?;
+
+    let mut header = Vec::new();
+    ftyp.write(&mut header);
+    moov.write(&mut header);
+    if mdat_header_len == 16 {
+        push_u32(&mut header, 1);
+        header.extend_from_slice(b"mdat");
+        header.extend_from_slice(&(mdat_len + 16).to_be_bytes());
+    } else {
+        push_u32(&mut header, mdat_len as u32 + 8);
+        header.extend_from_slice(b"mdat");
+    }
+
+    let mut out = BufWriter::new(File::create(output)?);
+    out.write_all(&header)?;
+    let mut buffer = Vec::new();
+    for &(track, first, count) in &chunks {
+        let track = &output_tracks[track];
+        // a chunk never contains samples of different sources
+        let source = &mut sources[track.sources[first]].file;
+        for sample in &track.samples[first..first + count] {
+            buffer.resize(sample.size as usize, 0);
+            source.seek(SeekFrom::Start(sample.offset))?;
+            source.read_exact(&mut buffer)?;
+            out.write_all(&buffer)?;
+        }
+    }
+    out.into_inner()?.sync_all()?;
+
+    Ok(ranges)
+}
+
+/// average frames per second of the video
+pub fn frame_rate(path: &Path) -> Result<f64> {
+    let source = Source::open(path)?;
+    let video = source
+        .tracks
+        .iter()
+        .find(|track| track.is_video())
+        .context("no video track")?;
+
+    let duration = video.samples.iter().map(|sample| sample.duration as u64).sum::<u64>();
+    if duration == 0 {
+        bail!("the video has no duration");
+    }
+    Ok(video.samples.len() as f64 * video.timescale as f64 / duration as f64)
+}
+
+/// a parsed input file
+struct Source {
+    path: PathBuf,
+    file: File,
+    ftyp: Vec<u8>,
+    moov: Mp4Box,
+    tracks: Vec<Track>,
+}
+
+impl Source {
+    fn open(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let (ftyp, mut moov) = boxes::read_top_level(&mut file)?;
+
+        // embedded chapters and tags describe the whole source file
+        chapters::remove_chapter_tracks(&mut moov)?;
+        moov.children_mut()
+            .context("moov is not a container")?
+            .retain(|child| &child.kind != b"udta");
+
+        let tracks = moov
+            .children()
+            .iter()
+            .filter(|child| &child.kind == b"trak")
+            .map(Track::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            file,
+            ftyp,
+            moov,
+            tracks,
+        })
+    }
+
+    fn traks(&self) -> Vec<&Mp4Box> {
+        self.moov
+            .children()
+            .iter()
+            .filter(|child| &child.kind == b"trak")
+            .collect()
+    }
+
+    /// samples of both files can only be mixed if they have the same tracks with the same sample descriptions
+    /// (codec, resolution, sample rate, ...)
+    fn check_compatible(&self, other: &Source) -> Result<()> {
+        if self.tracks.len() != other.tracks.len() {
+            bail!("different number of tracks");
+        }
+
+        for ((trak, track), (other_trak, other_track)) in self
+            .traks()
+            .into_iter()
+            .zip(&self.tracks)
+            .zip(other.traks().into_iter().zip(&other.tracks))
+        {
+            if track.handler != other_track.handler || track.timescale != other_track.timescale {
+                bail!("different tracks");
+            }
+            let stsd = [b"mdia", b"minf", b"stbl", b"stsd"];
+            if trak.find_data(&stsd)? != other_trak.find_data(&stsd)? {
+                bail!("different encoder settings");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// samples (`first..last`) of the video track for the range, `first` is a keyframe
+fn reference_range(track: &Track, start: f64, end: f64) -> Result<(usize, usize)> {
+    let keyframes = track
+        .samples
+        .iter()
+        .enumerate()
+        .filter(|(_, sample)| sample.sync)
+        .collect::<Vec<_>>();
+    let (first, keyframe) = keyframes
+        .iter()
+        .rev()
+        .find(|(_, sample)| track.seconds(sample) <= start)
+        .or(keyframes.first())
+        .copied()
+        .context("no keyframe in the video")?;
+
+    // samples are in decode order, so the end is decided by the decode time (shifted like the keyframe)
+    let decode_seconds = |sample: &Sample| {
+        (sample.decode_time as i64 + keyframe.composition_offset - track.media_time) as f64 / track.timescale as f64
+    };
+    let last = track.samples[first..]
+        .iter()
+        .position(|sample| decode_seconds(sample) >= end)
+        .map_or(track.samples.len(), |position| first + position);
+
+    let video_end = track.samples[..last].last().map_or(0.0, |sample| {
+        decode_seconds(sample) + sample.duration as f64 / track.timescale as f64
+    });
+    if last <= first || track.seconds(keyframe) >= end || video_end <= start {
+        bail!("the range contains no video");
+    }
+    Ok((first, last))
+}
+
+/// the samples of a track of the output file, in decode order
+struct OutputTrack {
+    timescale: u32,
+    /// with the decode time of the output and the offset in the source file
+    samples: Vec<Sample>,
+    /// index of the source file of every sample
+    sources: Vec<usize>,
+    /// sum of the durations of all samples
+    duration: u64,
+}
+
+impl OutputTrack {
+    fn new(timescale: u32) -> Self {
+        Self {
+            timescale,
+            samples: Vec::new(),
+            sources: Vec::new(),
+            duration: 0,
+        }
+    }
+
+    /// appends the samples of the source directly after the previous samples
+    fn append(&mut self, source: usize, samples: &[Sample]) {
+        let Some(first) = samples.first() else { return };
+        let first_decode_time = first.decode_time;
+        for sample in samples {
+            self.samples.push(Sample {
+                decode_time: self.duration + sample.decode_time - first_decode_time,
+                ..*sample
+            });
+            self.sources.push(source);
+        }
+        self.duration = self
+            .samples
+            .last()
+            .map_or(0, |sample| sample.decode_time + sample.duration as u64);
+    }
+
+    fn seconds(&self) -> f64 {
+        self.duration as f64 / self.timescale as f64
+    }
+
+    fn decode_seconds(&self, index: usize) -> f64 {
+        self.samples[index].decode_time as f64 / self.timescale as f64
+    }
+
+    fn size(&self, first: usize, count: usize) -> u64 {
+        self.samples[first..first + count]
+            .iter()
+            .map(|sample| sample.size as u64)
+            .sum()
+    }
+
+    /// (first sample, number of samples) of every chunk
+    fn chunks(&self) -> Vec<(usize, usize)> {
+        let max_duration = (CHUNK_DURATION * self.timescale as f64) as u64;
+
+        let mut chunks = Vec::<(usize, usize)>::new();
+        let mut chunk_duration = 0;
+        for (index, sample) in self.samples.iter().enumerate() {
+            match chunks.last_mut() {
+                Some((first, count))
+                    if chunk_duration < max_duration
+                        && self.sources[*first] == self.sources[index]
+                        && self.samples[*first].description_index == sample.description_index =>
+                {
+                    *count += 1;
+                }
+                _ => {
+                    chunks.push((index, 1));
+                    chunk_duration = 0;
+                }
+            }
+            chunk_duration += sample.duration as u64;
+        }
+        chunks
+    }
+
+    /// presentation time of the first sample that is shown at the start of the movie
+    fn media_time(&self) -> i64 {
+        self.samples
+            .first()
+            .map_or(0, |sample| sample.composition_offset.max(0))
+    }
+}
+
+fn build_moov(
+    moov: &Mp4Box,
+    traks: &[&Mp4Box],
+    output_tracks: &[OutputTrack],
+    chunks: &[(usize, usize, usize)],
+    data_offset: u64,
+) -> Result<Mp4Box> {
+    let mut moov = moov.clone();
+
+    let mvhd = moov.find_data(&[b"mvhd"])?;
+    let movie_timescale = timescale(mvhd)?;
+
+    let mut offset = data_offset;
+    let mut track_chunks = vec![Vec::<(u32, u64)>::new(); output_tracks.len()];
+    for &(track, first, count) in chunks {
+        track_chunks[track].push((count as u32, offset));
+        offset += output_tracks[track].size(first, count);
+    }
+
+    let mut new_traks = Vec::with_capacity(traks.len());
+    let mut movie_duration = 0;
+    for ((trak, output_track), chunks) in traks.iter().zip(output_tracks).zip(&track_chunks) {
+        let mut trak = (*trak).clone();
+
+        let media_duration = output_track.duration;
+        let duration = media_duration * movie_timescale as u64 / output_track.timescale as u64;
+        movie_duration = movie_duration.max(duration);
+
+        set_duration(trak.find_data_mut(&[b"tkhd"])?, 20, 28, duration)?;
+        set_duration(trak.find_data_mut(&[b"mdia", b"mdhd"])?, 16, 24, media_duration)?;
+
+        let minf = trak
+            .child_mut(b"mdia")
+            .and_then(|mdia| mdia.child_mut(b"minf"))
+            .context("missing minf box")?;
+        let stbl = minf.child_mut(b"stbl").context("missing stbl box")?;
+        *stbl = sample_table::sample_table(stbl, &output_track.samples, chunks)?;
+
+        // one edit that starts the presentation at the first sample
+        let mut elst = vec![1, 0, 0, 0];
+        push_u32(&mut elst, 1);
+        elst.extend_from_slice(&duration.to_be_bytes());
+        elst.extend_from_slice(&output_track.media_time().to_be_bytes());
+        push_u32(&mut elst, 0x0001_0000);
+        let edts = Mp4Box::container(b"edts", vec![Mp4Box::leaf(b"elst", elst)]);
+        let children = trak.children_mut().context("trak is not a container")?;
+        match children.iter_mut().find(|child| &child.kind == b"edts") {
+            Some(old_edts) => *old_edts = edts,
+            None => {
+                let position = children
+                    .iter()
+                    .position(|child| &child.kind == b"tkhd")
+                    .map_or(0, |position| position + 1);
+                children.insert(position, edts);
+            }
+        }
+
+        new_traks.push(trak);
+    }
+
+    set_duration(moov.find_data_mut(&[b"mvhd"])?, 16, 24, movie_duration)?;
+    let mut new_traks = new_traks.into_iter();
+    for child in moov.children_mut().context("moov is not a container")? {
+        if &child.kind == b"trak" {
+            *child = new_traks.next().context("missing track")?;
+        }
+    }
+
+    Ok(moov)
+}
+
+fn timescale(mvhd: &[u8]) -> Result<u32> {
+    let mut reader = Reader::new(mvhd);
+    if reader.version()? == 1 {
+        reader.skip(16)?;
+    } else {
+        reader.skip(8)?;
+    }
+    reader.u32()
+}
+
+/// sets the duration field of a mvhd, tkhd or mdhd box (byte offset for version 0 and version 1)
+fn set_duration(data: &mut [u8], offset_v0: usize, offset_v1: usize, duration: u64) -> Result<()> {
+    let version = *data.first().context("empty box")?;
+    if version == 1 {
+        data.get_mut(offset_v1..offset_v1 + 8)
+            .context("box too short")?
+            .copy_from_slice(&duration.to_be_bytes());
+    } else {
+        let duration = u32::try_from(duration).context("duration too long")?;
+        data.get_mut(offset_v0..offset_v0 + 4)
+            .context("box too short")?
+            .copy_from_slice(&duration.to_be_bytes());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIDEO_TIMESCALE: u32 = 90000;
+    const FRAME: u32 = 3000; // 30 fps
+    const AUDIO_TIMESCALE: u32 = 48000;
+    const AUDIO_FRAME: u32 = 1024;
+
+    fn full_box(version: u8, fields: &[&[u8]]) -> Vec<u8> {
+        let mut data = vec![version, 0, 0, 0];
+        fields.iter().for_each(|field| data.extend_from_slice(field));
+        data
+    }
+
+    fn trak(handler: &[u8; 4], timescale: u32, media_time: i64, samples: &[Sample]) -> Result<Mp4Box> {
+        let duration = samples.iter().map(|sample| sample.duration).sum::<u32>();
+        let stsd = Mp4Box::leaf(b"stsd", full_box(0, &[&0u32.to_be_bytes()]));
+        let chunks = samples.iter().map(|sample| (1, sample.offset)).collect::<Vec<_>>();
+        let stbl = sample_table::sample_table(&Mp4Box::container(b"stbl", vec![stsd]), samples, &chunks)?;
+
+        let mut elst = full_box(0, &[&1u32.to_be_bytes(), &0u32.to_be_bytes()]);
+        elst.extend_from_slice(&(media_time as i32).to_be_bytes());
+        push_u32(&mut elst, 0x0001_0000);
+
+        Ok(Mp4Box::container(
+            b"trak",
+            vec![
+                Mp4Box::leaf(b"tkhd", full_box(0, &[&[0; 16], &0u32.to_be_bytes(), &[0; 60]])),
+                Mp4Box::container(b"edts", vec![Mp4Box::leaf(b"elst", elst)]),
+                Mp4Box::container(
+                    b"mdia",
+                    vec![
+                        Mp4Box::leaf(
+                            b"mdhd",
+                            full_box(
+                                0,
+                                &[&[0; 8], &timescale.to_be_bytes(), &duration.to_be_bytes(), &[0; 4]],
+                            ),
+                        ),
+                        Mp4Box::leaf(b"hdlr", full_box(0, &[&[0; 4], handler, &[0; 13]])),
+                        Mp4Box::container(b"minf", vec![stbl]),
+                    ],
+                ),
+            ],
+        ))
+    }
+
+    /// 10 seconds of video with a keyframe every second and B-frame like composition offsets and audio
+    /// every sample contains its track and index so it can be recognized in the output
+    fn source_file(path: &Path) -> Result<()> {
+        let payload = |track: u8, index: usize| {
+            let mut data = vec![track];
+            data.extend_from_slice(&(index as u32).to_be_bytes());
+            data.resize(5 + index % 7, track);
+            data
+        };
+
+        let mut mdat = Vec::new();
+        let mut video = Vec::new();
+        let mut audio = Vec::new();
+        let (mut video_time, mut audio_time) = (0, 0);
+        let mut audio_index = 0;
+        for index in 0..300 {
+            let data = payload(1, index);
+            video.push(Sample {
+                offset: mdat.len() as u64,
+                size: data.len() as u32,
+                decode_time: video_time,
+                duration: FRAME,
+                composition_offset: 2 * FRAME as i64,
+                sync: index % 30 == 0,
+                description_index: 1,
+            });
+            mdat.extend(data);
+            video_time += FRAME as u64;
+
+            // interleave the audio that belongs to the frame
+            while audio_time * (VIDEO_TIMESCALE as u64) < video_time * AUDIO_TIMESCALE as u64 {
+                let data = payload(2, audio_index);
+                audio.push(Sample {
+                    offset: mdat.len() as u64,
+                    size: data.len() as u32,
+                    decode_time: audio_time,
+                    duration: AUDIO_FRAME,
+                    composition_offset: 0,
+                    sync: true,
+                    description_index: 1,
+                });
+                mdat.extend(data);
+                audio_time += AUDIO_FRAME as u64;
+                audio_index += 1;
+            }
+        }
+
+        // offsets are relative to mdat so far, the moov box goes behind the mdat like OBS writes it
+        let ftyp = Mp4Box::leaf(b"ftyp", b"isom\0\0\x02\0isomiso2mp41".to_vec());
+        let data_start = ftyp.size() + 8;
+        for sample in video.iter_mut().chain(audio.iter_mut()) {
+            sample.offset += data_start;
+        }
+        let moov = Mp4Box::container(
+            b"moov",
+            vec![
+                Mp4Box::leaf(
+                    b"mvhd",
+                    full_box(0, &[&[0; 8], &1000u32.to_be_bytes(), &10000u32.to_be_bytes(), &[0; 80]]),
+                ),
+                trak(b"vide", VIDEO_TIMESCALE, 2 * FRAME as i64, &video)?,
+                trak(b"soun", AUDIO_TIMESCALE, 0, &audio)?,
+            ],
+        );
+
+        let mut file = Vec::new();
+        ftyp.write(&mut file);
+        Mp4Box::leaf(b"mdat", mdat).write(&mut file);
+        moov.write(&mut file);
+        std::fs::write(path, file)?;
+        Ok(())
+    }
+
+    fn read_tracks(path: &Path) -> Result<(File, Vec<Track>)> {
+        let mut file = File::open(path)?;
+        let (_, moov) = boxes::read_top_level(&mut file)?;
+        let tracks = moov
+            .children()
+            .iter()
+            .filter(|child| &child.kind == b"trak")
+            .map(Track::parse)
+            .collect::<Result<Vec<_>>>()?;
+        Ok((file, tracks))
+    }
+
+    fn sample_data(file: &mut File, sample: &Sample) -> Result<Vec<u8>> {
+        let mut data = vec![0; sample.size as usize];
+        file.seek(SeekFrom::Start(sample.offset))?;
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    #[test]
+    fn cut_starts_at_keyframe() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("mp4_cut_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let source = dir.join("source.mp4");
+        let clip = dir.join("clip.mp4");
+        source_file(&source)?;
+
+        let range = cut(&source, &clip, 3.5, 5.2)?;
+        assert_eq!(range, CutRange { start: 3.0, end: 5.2 });
+
+        let (mut source, source_tracks) = read_tracks(&source)?;
+        let (mut clip, clip_tracks) = read_tracks(&clip)?;
+        assert_eq!(clip_tracks.len(), 2);
+
+        // video starts with the keyframe of second 3
+        let video = &clip_tracks[0];
+        assert_eq!(video.samples.len(), 66);
+        assert!(video.samples[0].sync);
+        assert_eq!(video.seconds(&video.samples[0]), 0.0);
+        assert_eq!(video.samples.iter().filter(|sample| sample.sync).count(), 3);
+        for (clip_sample, source_sample) in video.samples.iter().zip(&source_tracks[0].samples[90..]) {
+            assert_eq!(
+                sample_data(&mut clip, clip_sample)?,
+                sample_data(&mut source, source_sample)?
+            );
+        }
+
+        // audio covers the same time
+        let audio = &clip_tracks[1];
+        let source_audio = &source_tracks[1];
+        let first = source_audio
+            .samples
+            .iter()
+            .position(|sample| source_audio.seconds(sample) >= 3.0)
+            .unwrap();
+        assert!(!audio.samples.is_empty());
+        for (clip_sample, source_sample) in audio.samples.iter().zip(&source_audio.samples[first..]) {
+            assert_eq!(
+                sample_data(&mut clip, clip_sample)?,
+                sample_data(&mut source, source_sample)?
+            );
+        }
+        let audio_end = audio.samples.iter().map(|sample| sample.duration as f64).sum::<f64>() / audio.timescale as f64;
+        assert!((audio_end - 2.2).abs() < 0.05);
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn concat_segments() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("mp4_concat_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let source = dir.join("source.mp4");
+        let reel = dir.join("reel.mp4");
+        source_file(&source)?;
+
+        let segment = |start, end| Segment {
+            path: source.clone(),
+            start,
+            end,
+        };
+        let ranges = concat(&[segment(7.2, 8.0), segment(3.0, 4.0)], &reel)?;
+        assert_eq!(
+            ranges,
+            vec![CutRange { start: 7.0, end: 8.0 }, CutRange { start: 3.0, end: 4.0 }]
+        );
+
+        let (mut source, source_tracks) = read_tracks(&source)?;
+        let (mut reel, reel_tracks) = read_tracks(&reel)?;
+
+        // the video of both segments in order
+        let video = &reel_tracks[0];
+        let expected = source_tracks[0].samples[210..240]
+            .iter()
+            .chain(&source_tracks[0].samples[90..120]);
+        assert_eq!(video.samples.len(), 60);
+        for (reel_sample, source_sample) in video.samples.iter().zip(expected) {
+            assert_eq!(reel_sample.sync, source_sample.sync);
+            assert_eq!(
+                sample_data(&mut reel, reel_sample)?,
+                sample_data(&mut source, source_sample)?
+            );
+        }
+        assert_eq!(video.samples[30].decode_time, 30 * FRAME as u64);
+
+        // the audio stays in sync with the video
+        let audio = &reel_tracks[1];
+        let audio_end = audio.samples.last().map_or(0.0, |sample| {
+            (sample.decode_time + sample.duration as u64) as f64 / AUDIO_TIMESCALE as f64
+        });
+        assert!((audio_end - 2.0).abs() < AUDIO_FRAME as f64 / AUDIO_TIMESCALE as f64);
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    fn read_chapters(path: &Path) -> Result<Vec<Chapter>> {
+        let (mut file, tracks) = read_tracks(path)?;
+        let Some(track) = tracks.iter().find(|track| &track.handler == b"text") else { return Ok(Vec::new()) };
+
+        let mut chapters = Vec::new();
+        for sample in &track.samples {
+            let data = sample_data(&mut file, sample)?;
+            let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+            chapters.push(Chapter {
+                start: track.seconds(sample),
+                title: String::from_utf8_lossy(&data[2..2 + len]).into_owned(),
+            });
+        }
+        Ok(chapters)
+    }
+
+    /// (key, value) of every tag, the key of freeform tags is their name
+    fn read_tags(path: &Path) -> Result<Vec<(String, String)>> {
+        let mut file = File::open(path)?;
+        let (_, moov) = boxes::read_top_level(&mut file)?;
+        let child = |data: &[u8], kind: &[u8; 4]| -> Result<Mp4Box> {
+            Mp4Box::parse_all(data)?
+                .into_iter()
+                .find(|child| &child.kind == kind)
+                .with_context(|| format!("missing {}", boxes::fourcc(kind)))
+        };
+        let udta = moov.child(b"udta").and_then(Mp4Box::data).context("missing udta")?;
+        let meta = child(udta, b"meta")?;
+        let ilst = child(&meta.data().unwrap_or_default()[4..], b"ilst")?;
+
+        let mut tags = Vec::new();
+        for item in Mp4Box::parse_all(ilst.data().unwrap_or_default())? {
+            let data = item.data().unwrap_or_default();
+            let value = String::from_utf8_lossy(&child(data, b"data")?.data().unwrap_or_default()[8..]).into_owned();
+            let key = match &item.kind {
+                b"----" => String::from_utf8_lossy(&child(data, b"name")?.data().unwrap_or_default()[4..]).into_owned(),
+                kind => String::from_utf8_lossy(&kind[1..]).into_owned(),
+            };
+            tags.push((key, value));
+        }
+        Ok(tags)
+    }
+
+    #[test]
+    fn embed_chapters_and_tags() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("mp4_chapters_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("source.mp4");
+        source_file(&path)?;
+        let (mut source, source_tracks) = read_tracks(&path)?;
+        let source_data = source_tracks[0]
+            .samples
+            .iter()
+            .map(|sample| sample_data(&mut source, sample))
+            .collect::<Result<Vec<_>>>()?;
+
+        let chapter = |start, title: &str| Chapter { start, title: title.to_owned() };
+        let tags = |title: &str| Tags {
+            title: Some(title.to_owned()),
+            comment: None,
+            custom: vec![("matchId".to_owned(), "EUW1_123".to_owned())],
+        };
+
+        // embedding twice replaces the first chapters and tags
+        embed(&path, &[chapter(0.0, "Start"), chapter(4.0, "Kill")], &tags("first"))?;
+        embed(
+            &path,
+            &[
+                chapter(0.0, "Start"),
+                chapter(3.0, "Baron Nashor"),
+                chapter(7.5, "Death"),
+            ],
+            &tags("Ahri"),
+        )?;
+
+        assert_eq!(
+            read_chapters(&path)?,
+            vec![
+                chapter(0.0, "Start"),
+                chapter(3.0, "Baron Nashor"),
+                chapter(7.5, "Death")
+            ]
+        );
+        assert_eq!(
+            read_tags(&path)?,
+            vec![
+                ("nam".to_owned(), "Ahri".to_owned()),
+                ("matchId".to_owned(), "EUW1_123".to_owned())
+            ]
+        );
+
+        // the old moov boxes are free boxes now and the samples didn't move
+        let mut file = File::open(&path)?;
+        let top_level = boxes::top_level_boxes(&mut file)?;
+        assert_eq!(
+            top_level
+                .iter()
+                .filter(|top_level_box| &top_level_box.kind == b"moov")
+                .count(),
+            1
+        );
+        assert_eq!(
+            top_level
+                .iter()
+                .filter(|top_level_box| &top_level_box.kind == b"free")
+                .count(),
+            2
+        );
+        let (mut file, tracks) = read_tracks(&path)?;
+        assert_eq!(tracks.len(), 3);
+        for (sample, data) in tracks[0].samples.iter().zip(source_data) {
+            assert_eq!(sample_data(&mut file, sample)?, data);
+        }
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn embed_after_interrupted_embed() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("mp4_chapters_test_interrupted_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("source.mp4");
+        source_file(&path)?;
+        let source_len = std::fs::metadata(&path)?.len();
+
+        // the append of the mdat box got interrupted after a few bytes
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path)?;
+        file.write_all(&[0, 0, 0x10, 0, b'm', b'd', b'a', b't', 1, 2, 3])?;
+        drop(file);
+
+        assert!(frame_rate(&path)? > 0.0);
+        let chapters = [Chapter {
+            start: 0.0,
+            title: "Start".to_owned(),
+        }];
+        embed(&path, &chapters, &Tags::default())?;
+        assert_eq!(read_chapters(&path)?, chapters);
+
+        // the truncated box got replaced by the appended boxes
+        let mut file = File::open(&path)?;
+        let top_level = boxes::top_level_boxes(&mut file)?;
+        let appended_mdat = top_level
+            .iter()
+            .rfind(|top_level_box| &top_level_box.kind == b"mdat")
+            .unwrap();
+        assert_eq!(appended_mdat.position, source_len);
+
+        // interrupted after the boxes got appended but before the old moov got freed
+        let copy = dir.join("copy.mp4");
+        std::fs::copy(&path, &copy)?;
+        let len = std::fs::metadata(&path)?.len() as usize;
+        let interrupted = [Chapter {
+            start: 0.0,
+            title: "Interrupted".to_owned(),
+        }];
+        embed(&copy, &interrupted, &Tags::default())?;
+        let appended = std::fs::read(&copy)?.split_off(len);
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(&appended)?;
+        // the first moov box is still the one that counts
+        assert_eq!(read_chapters(&path)?, chapters);
+
+        // the moov box of the interrupted embed must not become the first one
+        let chapters = [Chapter {
+            start: 0.0,
+            title: "Final".to_owned(),
+        }];
+        embed(&path, &chapters, &Tags::default())?;
+        assert_eq!(read_chapters(&path)?, chapters);
+        let mut file = File::open(&path)?;
+        let top_level = boxes::top_level_boxes(&mut file)?;
+        assert_eq!(
+            top_level
+                .iter()
+                .filter(|top_level_box| &top_level_box.kind == b"moov")
+                .count(),
+            1
+        );
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn range_without_video() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("mp4_cut_test_empty_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let source = dir.join("source.mp4");
+        source_file(&source)?;
+
+        assert!(cut(&source, &dir.join("clip.mp4"), 12.0, 15.0).is_err());
+        assert!(cut(&source, &dir.join("clip.mp4"), 5.0, 4.0).is_err());
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use anyhow::{bail, Context, Result};
+
+use super::boxes::{FourCC, Mp4Box, Reader};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    /// position of the sample data in the file
+    pub offset: u64,
+    pub size: u32,
+    /// decode time in the timescale of the track
+    pub decode_time: u64,
+    pub duration: u32,
+    /// presentation time - decode time
+    pub composition_offset: i64,
+    /// keyframe
+    pub sync: bool,
+    pub description_index: u32,
+}
+
+impl Sample {
+    pub fn presentation_time(&self) -> i64 {
+        self.decode_time as i64 + self.composition_offset
+    }
+}
+
+/// the samples of a track (`trak` box) in decode order
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub handler: FourCC,
+    /// units per second of all times of the track
+    pub timescale: u32,
+    /// presentation time of the track that is shown at the start of the movie (from the edit list)
+    pub media_time: i64,
+    pub samples: Vec<Sample>,
+}
+
+impl Track {
+    pub fn parse(trak: &Mp4Box) -> Result<Self> {
+        let mdia = trak.child(b"mdia").context("missing mdia box")?;
+
+        let mut hdlr = Reader::new(mdia.find_data(&[b"hdlr"])?);
+        hdlr.version()?;
+        hdlr.skip(4)?;
+        let handler = hdlr.fourcc()?;
+
+        let mut mdhd = Reader::new(mdia.find_data(&[b"mdhd"])?);
+        let timescale = if mdhd.version()? == 1 {
+            mdhd.skip(16)?;
+            mdhd.u32()?
+        } else {
+            mdhd.skip(8)?;
+            mdhd.u32()?
+        };
+        if timescale == 0 {
+            bail!("invalid timescale");
+        }
+
+        let stbl = mdia
+            .child(b"minf")
+            .and_then(|minf| minf.child(b"stbl"))
+            .context("missing stbl box")?;
+
+        Ok(Self {
+            handler,
+            timescale,
+            media_time: media_time(trak)?,
+            samples: samples(stbl)?,
+        })
+    }
+
+    /// time of the track in seconds that the player shows for the sample
+    pub fn seconds(&self, sample: &Sample) -> f64 {
+        (sample.presentation_time() - self.media_time) as f64 / self.timescale as f64
+    }
+
+    pub fn is_video(&self) -> bool {
+        &self.handler == b"vide"
+    }
+}
+
+/// media time of the first edit that isn't empty (0 if there is no edit list)
+fn media_time(trak: &Mp4Box) -> Result<i64> {
+    let Ok(elst) = trak.find_data(&[b"edts", b"elst"]) else { return Ok(0) };
+
+    let mut elst = Reader::new(elst);
+    let version = elst.version()?;
+    for _ in 0..elst.u32()? {
+        let media_time = if version == 1 {
+            elst.skip(8)?;
+            elst.i64()?
+        } else {
+            elst.skip(4)?;
+            elst.i32()? as i64
+        };
+        elst.skip(4)?;
+
+        // -1 is an empty edit (a delay at the start of the track)
+        if media_time >= 0 {
+            return Ok(media_time);
+        }
+    }
+    Ok(0)
+}
+
+fn samples(stbl: &Mp4Box) -> Result<Vec<Sample>> {
+    // sample sizes
+    let mut stsz = Reader::new(
+        stbl.find_data(&[b"stsz"])
+            .context("only stsz sample sizes are supported")?,
+    );
+    stsz.version()?;
+    let sample_size = stsz.u32()?;
+    let sample_count = stsz.u32()? as usize;
+    let sizes = if sample_size == 0 {
+        (0..sample_count).map(|_| stsz.u32()).collect::<Result<Vec<_>>>()?
+    } else {
+        vec![sample_size; sample_count]
+    };
+
+    // decode times
+    let mut stts = Reader::new(stbl.find_data(&[b"stts"])?);
+    stts.version()?;
+    let mut durations = Vec::with_capacity(sample_count);
+    for _ in 0..stts.u32()? {
+        let count = stts.u32()?;
+        let delta = stts.u32()?;
+        durations.extend(std::iter::repeat_n(delta, count as usize));
+    }
+
+    // composition offsets (only needed if the track has B-frames)
+    let mut composition_offsets = vec![0; sample_count];
+    if let Ok(ctts) = stbl.find_data(&[b"ctts"]) {
+        let mut ctts = Reader::new(ctts);
+        let version = ctts.version()?;
+        let mut index = 0;
+        for _ in 0..ctts.u32()? {
+            let count = ctts.u32()? as usize;
+            let offset = if version == 1 {
+                ctts.i32()? as i64
+            } else {
+                ctts.u32()? as i64
+            };
+            for composition_offset in composition_offsets.iter_mut().skip(index).take(count) {
+                *composition_offset = offset;
+            }
+            index += count;
+        }
+    }
+
+    // keyframes (every sample is a keyframe if there is no stss)
+    let sync = match stbl.find_data(&[b"stss"]) {
+        Ok(stss) => {
+            let mut stss = Reader::new(stss);
+            stss.version()?;
+            let mut sync = vec![false; sample_count];
+            for _ in 0..stss.u32()? {
+                let sample_number = stss.u32()? as usize;
+                if let Some(sync) = sample_number.checked_sub(1).and_then(|index| sync.get_mut(index)) {
+                    *sync = true;
+                }
+            }
+            sync
+        }
+        Err(_) => vec![true; sample_count],
+    };
+
+    // chunk offsets
+    let chunk_offsets = if let Ok(stco) = stbl.find_data(&[b"stco"]) {
+        let mut stco = Reader::new(stco);
+        stco.version()?;
+        (0..stco.u32()?)
+            .map(|_| stco.u32().map(u64::from))
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        let mut co64 = Reader::new(stbl.find_data(&[b"co64"]).context("missing chunk offsets")?);
+        co64.version()?;
+        (0..co64.u32()?).map(|_| co64.u64()).collect::<Result<Vec<_>>>()?
+    };
+
+    // samples of each chunk
+    let mut stsc = Reader::new(stbl.find_data(&[b"stsc"])?);
+    stsc.version()?;
+    let entries = (0..stsc.u32()?)
+        .map(|_| Ok((stsc.u32()?, stsc.u32()?, stsc.u32()?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut samples = Vec::with_capacity(sample_count);
+    let mut decode_time = 0;
+    for (i, &(first_chunk, samples_per_chunk, description_index)) in entries.iter().enumerate() {
+        let last_chunk = entries
+            .get(i + 1)
+            .map_or(chunk_offsets.len() as u32, |(next_first_chunk, _, _)| {
+                next_first_chunk - 1
+            });
+
+        for chunk in first_chunk..=last_chunk {
+            let mut offset = *chunk_offsets
+                .get(chunk.checked_sub(1).context("invalid chunk number")? as usize)
+                .context("invalid chunk number")?;
+
+            for _ in 0..samples_per_chunk {
+                let index = samples.len();
+                if index >= sample_count {
+                    break;
+                }
+                let size = sizes[index];
+                let duration = durations.get(index).copied().unwrap_or_default();
+                samples.push(Sample {
+                    offset,
+                    size,
+                    decode_time,
+                    duration,
+                    composition_offset: composition_offsets[index],
+                    sync: sync[index],
+                    description_index,
+                });
+                offset += size as u64;
+                decode_time += duration as u64;
+            }
+        }
+    }
+
+    if samples.len() != sample_count {
+        bail!("sample table is inconsistent");
+    }
+    Ok(samples)
+}
+
+/// a new `stbl` box for `samples` with the `stsd` box of the original
+/// `chunks` are the number of samples and the offset in the output file of every chunk
+pub fn sample_table(stbl: &Mp4Box, samples: &[Sample], chunks: &[(u32, u64)]) -> Result<Mp4Box> {
+    let stsd = stbl.child(b"stsd").context("missing stsd box")?.clone();
+    let mut children = vec![stsd];
+
+    // stts (run-length encoded durations)
+    let mut runs = Vec::<(u32, u32)>::new();
+    for sample in samples {
+        match runs.last_mut() {
+            Some((count, duration)) if *duration == sample.duration => *count += 1,
+            _ => runs.push((1, sample.duration)),
+        }
+    }
+    children.push(table(b"stts", 0, &runs, |(count, duration), out| {
+        push_u32(out, *count);
+        push_u32(out, *duration);
+    }));
+
+    // ctts (version 1 for signed offsets)
+    if samples.iter().any(|sample| sample.composition_offset != 0) {
+        let mut runs = Vec::<(u32, i64)>::new();
+        for sample in samples {
+            match runs.last_mut() {
+                Some((count, offset)) if *offset == sample.composition_offset => *count += 1,
+                _ => runs.push((1, sample.composition_offset)),
+            }
+        }
+        children.push(table(b"ctts", 1, &runs, |(count, offset), out| {
+            push_u32(out, *count);
+            out.extend_from_slice(&(*offset as i32).to_be_bytes());
+        }));
+    }
+
+    // stss (only if not every sample is a keyframe)
+    if samples.iter().any(|sample| !sample.sync) {
+        let keyframes = samples
+            .iter()
+            .enumerate()
+            .filter(|(_, sample)| sample.sync)
+            .map(|(index, _)| index as u32 + 1)
+            .collect::<Vec<_>>();
+        children.push(table(b"stss", 0, &keyframes, |sample_number, out| {
+            push_u32(out, *sample_number)
+        }));
+    }
+
+    // stsc (one entry per run of chunks with the same number of samples and sample description)
+    let mut entries = Vec::<(u32, u32, u32)>::new();
+    let mut first_sample = 0;
+    for (chunk_index, &(sample_count, _)) in chunks.iter().enumerate() {
+        let description_index = samples
+            .get(first_sample)
+            .context("chunks don't match the samples")?
+            .description_index;
+        first_sample += sample_count as usize;
+        match entries.last() {
+            Some(&(_, count, index)) if count == sample_count && index == description_index => {}
+            _ => entries.push((chunk_index as u32 + 1, sample_count, description_index)),
+        }
+    }
+    children.push(table(b"stsc", 0, &entries, |(first_chunk, count, index), out| {
+        push_u32(out, *first_chunk);
+        push_u32(out, *count);
+        push_u32(out, *index);
+    }));
+
+    // stsz
+    let mut stsz = vec![0; 4];
+    push_u32(&mut stsz, 0);
+    push_u32(&mut stsz, samples.len() as u32);
+    samples.iter().for_each(|sample| push_u32(&mut stsz, sample.size));
+    children.push(Mp4Box::leaf(b"stsz", stsz));
+
+    // co64 (so the size of the box doesn't depend on the offsets)
+    children.push(table(b"co64", 0, chunks, |(_, offset), out| {
+        out.extend_from_slice(&offset.to_be_bytes())
+    }));
+
+    Ok(Mp4Box::container(b"stbl", children))
+}
+
+/// full box with an entry count followed by the entries
+fn table<T>(kind: &FourCC, version: u8, entries: &[T], write: impl Fn(&T, &mut Vec<u8>)) -> Mp4Box {
+    let mut data = vec![version, 0, 0, 0];
+    push_u32(&mut data, entries.len() as u32);
+    entries.iter().for_each(|entry| write(entry, &mut data));
+    Mp4Box::leaf(kind, data)
+}
+
+pub fn push_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use riot_datatypes::*;
 use serde::{Deserialize, Serialize};
 
@@ -9,6 +11,8 @@ pub enum MetadataFile {
     Metadata(GameMetadata),
     Deferred(Deferred),
     NoData(NoData),
+    Clip(Clip),
+    Reel(Reel),
 }
 
 impl MetadataFile {
@@ -17,6 +21,8 @@ impl MetadataFile {
             MetadataFile::Metadata(metadata) => metadata.favorite,
             MetadataFile::Deferred(deferred) => deferred.favorite,
             MetadataFile::NoData(no_data) => no_data.favorite,
+            MetadataFile::Clip(clip) => clip.favorite,
+            MetadataFile::Reel(reel) => reel.favorite,
         }
     }
 
@@ -25,8 +31,138 @@ impl MetadataFile {
             MetadataFile::Metadata(metadata) => metadata.favorite = favorite,
             MetadataFile::Deferred(deferred) => deferred.favorite = favorite,
             MetadataFile::NoData(no_data) => no_data.favorite = favorite,
+            MetadataFile::Clip(clip) => clip.favorite = favorite,
+            MetadataFile::Reel(reel) => reel.favorite = favorite,
         };
     }
+
+    /// unix timestamp in milliseconds of when the game was created (if known)
+    pub fn game_creation(&self) -> Option<Timestamp> {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.game_creation,
+            MetadataFile::Deferred(_) | MetadataFile::NoData(_) | MetadataFile::Clip(_) | MetadataFile::Reel(_) => None,
+        }
+    }
+
+    pub fn annotations(&self) -> &Annotations {
+        match self {
+            MetadataFile::Metadata(metadata) => &metadata.annotations,
+            MetadataFile::Deferred(deferred) => &deferred.annotations,
+            MetadataFile::NoData(no_data) => &no_data.annotations,
+            MetadataFile::Clip(clip) => &clip.annotations,
+            MetadataFile::Reel(reel) => &reel.annotations,
+        }
+    }
+
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        match self {
+            MetadataFile::Metadata(metadata) => &mut metadata.annotations,
+            MetadataFile::Deferred(deferred) => &mut deferred.annotations,
+            MetadataFile::NoData(no_data) => &mut no_data.annotations,
+            MetadataFile::Clip(clip) => &mut clip.annotations,
+            MetadataFile::Reel(reel) => &mut reel.annotations,
+        }
+    }
+
+    pub fn review(&self) -> &ReviewState {
+        match self {
+            MetadataFile::Metadata(metadata) => &metadata.review,
+            MetadataFile::Deferred(deferred) => &deferred.review,
+            MetadataFile::NoData(no_data) => &no_data.review,
+            MetadataFile::Clip(clip) => &clip.review,
+            MetadataFile::Reel(reel) => &reel.review,
+        }
+    }
+
+    pub fn review_mut(&mut self) -> &mut ReviewState {
+        match self {
+            MetadataFile::Metadata(metadata) => &mut metadata.review,
+            MetadataFile::Deferred(deferred) => &mut deferred.review,
+            MetadataFile::NoData(no_data) => &mut no_data.review,
+            MetadataFile::Clip(clip) => &mut clip.review,
+            MetadataFile::Reel(reel) => &mut reel.review,
+        }
+    }
+
+    /// the account that played the recorded game (if known)
+    pub fn account(&self) -> Option<&lcu::Player> {
+        match self {
+            MetadataFile::Metadata(metadata) => Some(&metadata.player),
+            MetadataFile::Deferred(deferred) => deferred.player.as_ref(),
+            MetadataFile::NoData(_) => None,
+            MetadataFile::Clip(clip) => clip.player.as_ref(),
+            MetadataFile::Reel(_) => None,
+        }
+    }
+
+    /// every player in the file (the account first), the same player can be in the list more than once
+    pub fn players_mut(&mut self) -> Vec<&mut lcu::Player> {
+        match self {
+            MetadataFile::Metadata(metadata) => std::iter::once(&mut metadata.player)
+                .chain(
+                    metadata
+                        .participants
+                        .iter_mut()
+                        .filter_map(|entry| entry.player.as_mut()),
+                )
+                .collect(),
+            MetadataFile::Deferred(deferred) => deferred
+                .player
+                .iter_mut()
+                .chain(
+                    deferred
+                        .roster
+                        .iter_mut()
+                        .flat_map(|roster| roster.players.iter_mut().filter_map(|entry| entry.player.as_mut())),
+                )
+                .collect(),
+            MetadataFile::NoData(_) => Vec::new(),
+            MetadataFile::Clip(clip) => clip.player.iter_mut().collect(),
+            MetadataFile::Reel(_) => Vec::new(),
+        }
+    }
+
+    /// state of the download of the client replay (only recordings of games can have a replay)
+    pub fn replay(&self) -> Option<&ReplayState> {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.replay.as_ref(),
+            MetadataFile::Deferred(deferred) => deferred.replay.as_ref(),
+            MetadataFile::NoData(_) | MetadataFile::Clip(_) | MetadataFile::Reel(_) => None,
+        }
+    }
+
+    /// does nothing for recordings that are not of a game with a known match id
+    pub fn set_replay(&mut self, replay: ReplayState) {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.replay = Some(replay),
+            MetadataFile::Deferred(deferred) => deferred.replay = Some(replay),
+            MetadataFile::NoData(_) | MetadataFile::Clip(_) | MetadataFile::Reel(_) => {}
+        }
+    }
+
+    /// e.g. for exports, the path of the replay only exists on this computer
+    pub fn clear_replay(&mut self) {
+        match self {
+            MetadataFile::Metadata(metadata) => metadata.replay = None,
+            MetadataFile::Deferred(deferred) => deferred.replay = None,
+            MetadataFile::NoData(_) | MetadataFile::Clip(_) | MetadataFile::Reel(_) => {}
+        }
+    }
+
+    /// clips and highlight reels got exported from other recordings
+    pub fn is_clip(&self) -> bool {
+        matches!(self, MetadataFile::Clip(_) | MetadataFile::Reel(_))
+    }
+
+    /// ingame time in seconds at the start of the video (if known)
+    pub fn ingame_time_rec_start_offset(&self) -> Option<f64> {
+        match self {
+            MetadataFile::Metadata(metadata) => Some(metadata.ingame_time_rec_start_offset),
+            MetadataFile::Deferred(deferred) => Some(deferred.ingame_time_rec_start_offset),
+            MetadataFile::NoData(_) | MetadataFile::Reel(_) => None,
+            MetadataFile::Clip(clip) => clip.ingame_time_rec_start_offset,
+        }
+    }
 }
 
 #[cfg_attr(test, derive(specta::Type))]
@@ -36,7 +172,6 @@ pub struct GameMetadata {
     pub favorite: bool,
     pub match_id: MatchId,
     pub ingame_time_rec_start_offset: f64,
-    #[serde(default)]
     pub highlights: Vec<f64>,
     pub queue: Queue,
     pub player: lcu::Player,
@@ -44,6 +179,117 @@ pub struct GameMetadata {
     pub stats: lcu::Stats,
     pub participant_id: ParticipantId,
     pub events: Vec<GameEvent>,
+    /// all players of the game with their participant ids (from match history)
+    pub participants: Vec<RosterEntry>,
+    pub game_version: Option<String>,
+    /// major.minor part of `game_version` (e.g. "15.14")
+    pub patch: Option<String>,
+    /// in seconds
+    pub game_duration: Option<Timestamp>,
+    /// unix timestamp in milliseconds
+    pub game_creation: Option<Timestamp>,
+    pub map_id: Option<MapId>,
+    pub game_mode: Option<String>,
+    /// rank in the queue of the game before it started (only ranked queues)
+    pub rank_before: Option<lcu::RankedQueueStats>,
+    /// rank in the queue of the game after it ended (only ranked queues)
+    pub rank_after: Option<lcu::RankedQueueStats>,
+    /// download of the replay of the League client (only if replays get downloaded)
+    pub replay: Option<ReplayState>,
+    pub annotations: Annotations,
+    pub review: ReviewState,
+}
+
+impl GameMetadata {
+    /// takes over what got collected while recording and what the user changed before the game data was available
+    pub fn keep_deferred(&mut self, deferred: &Deferred) {
+        self.favorite = deferred.favorite;
+        self.highlights = deferred.highlights.clone();
+        self.rank_before = deferred.rank_before.clone();
+        self.replay = deferred.replay.clone();
+        self.annotations = deferred.annotations.clone();
+        self.review = deferred.review.clone();
+    }
+
+    /// takes over what the user changed on the recording before the game data was available
+    /// the comments of `NoData` are in the time of the video => moved to the ingame time
+    pub fn keep_no_data(&mut self, no_data: &NoData) {
+        self.favorite = no_data.favorite;
+        self.annotations = no_data.annotations.clone();
+        for comment in &mut self.annotations.comments {
+            comment.game_time += self.ingame_time_rec_start_offset * 1000.0;
+        }
+        self.review = no_data.review.clone();
+    }
+
+    pub fn result(&self) -> GameResult {
+        if self.stats.game_ended_in_early_surrender {
+            GameResult::Remake
+        } else if self.stats.win {
+            GameResult::Win
+        } else {
+            GameResult::Loss
+        }
+    }
+
+    /// LP won or lost in the game (only known for ranked games)
+    pub fn lp_delta(&self) -> Option<i64> {
+        let before = self.rank_before.as_ref()?.total_league_points()?;
+        let after = self.rank_after.as_ref()?.total_league_points()?;
+        Some(after - before)
+    }
+
+    /// only known if the metadata contains the participants of the game
+    pub fn team(&self) -> Option<&Team> {
+        self.participants
+            .iter()
+            .find(|participant| participant.participant_id == Some(self.participant_id))
+            .and_then(|participant| participant.team.as_ref())
+    }
+
+    /// number of champion kills of the team of the player (for kill participation)
+    /// unknown if the metadata has no events (e.g. the timeline was missing or the game data is from a replay)
+    pub fn team_kills(&self) -> Option<i64> {
+        if self.events.is_empty() {
+            return None;
+        }
+        let team = self.team()?;
+        let team_members = self
+            .participants
+            .iter()
+            .filter(|participant| participant.team.as_ref() == Some(team))
+            .filter_map(|participant| participant.participant_id)
+            .collect::<Vec<_>>();
+
+        let team_kills = self
+            .events
+            .iter()
+            .filter(|event| match &event.event {
+                Event::ChampionKill { killer_id, .. } => team_members.contains(killer_id),
+                _ => false,
+            })
+            .count();
+        Some(team_kills as i64)
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameResult {
+    Win,
+    Loss,
+    Remake,
+}
+
+impl GameResult {
+    /// same as the serialized name (e.g. for titles, reports and exports)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameResult::Win => "Win",
+            GameResult::Loss => "Loss",
+            GameResult::Remake => "Remake",
+        }
+    }
 }
 
 #[cfg_attr(test, derive(specta::Type))]
@@ -53,8 +299,60 @@ pub struct Deferred {
     pub favorite: bool,
     pub match_id: MatchId,
     pub ingame_time_rec_start_offset: f64,
-    #[serde(default)]
     pub highlights: Vec<f64>,
+    /// the account that was logged in when the recording started
+    pub player: Option<lcu::Player>,
+    /// lineup from the gameflow session when the recording started
+    pub roster: Option<Roster>,
+    /// rank in the queue of the game before it started (only ranked queues)
+    pub rank_before: Option<lcu::RankedQueueStats>,
+    /// download of the replay of the League client (only if replays get downloaded)
+    pub replay: Option<ReplayState>,
+    pub annotations: Annotations,
+    pub review: ReviewState,
+}
+
+/// the League client downloads its replay of the game (`.rofl` file) after the game ended
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ReplayState {
+    Downloading,
+    Downloaded {
+        path: PathBuf,
+    },
+    /// e.g. because the replay expired or the client was closed before the replay was available
+    Failed {
+        reason: String,
+    },
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Roster {
+    pub map_id: Option<MapId>,
+    pub map_name: Option<String>,
+    pub game_mode: Option<String>,
+    pub players: Vec<RosterEntry>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RosterEntry {
+    /// only known after the game from match history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub participant_id: Option<ParticipantId>,
+    /// missing if the match history has no (valid) team id for the participant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<Team>,
+    pub champion_id: ChampionId,
+    pub champion_name: Option<String>,
+    /// missing for bots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player: Option<lcu::Player>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<String>,
 }
 
 #[cfg_attr(test, derive(specta::Type))]
@@ -62,6 +360,234 @@ pub struct Deferred {
 #[serde(rename_all = "camelCase")]
 pub struct NoData {
     pub favorite: bool,
+    pub annotations: Annotations,
+    pub review: ReviewState,
+}
+
+/// part of a recording that got cut into its own video file
+/// keeps the data of the source recording that is needed to show the clip like a recording
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Clip {
+    pub favorite: bool,
+    /// video id of the recording the clip was cut from
+    pub source_video_id: String,
+    /// start of the clip in seconds of the source video
+    pub start: f64,
+    /// end of the clip in seconds of the source video
+    pub end: f64,
+    pub match_id: Option<MatchId>,
+    /// `None` if the ingame time of the source recording is unknown
+    pub ingame_time_rec_start_offset: Option<f64>,
+    pub queue: Option<Queue>,
+    pub player: Option<lcu::Player>,
+    pub champion_name: Option<String>,
+    pub participant_id: Option<ParticipantId>,
+    /// the events of the source recording inside of the clip
+    pub events: Vec<GameEvent>,
+    /// the highlights of the source recording inside of the clip
+    pub highlights: Vec<f64>,
+    pub annotations: Annotations,
+    pub review: ReviewState,
+}
+
+impl Clip {
+    /// `start` and `end` are the range of `source` that got cut, in seconds of the source video
+    pub fn new(source_video_id: String, source: &MetadataFile, start: f64, end: f64) -> Self {
+        let offset = source.ingame_time_rec_start_offset();
+        let mut clip = Clip {
+            favorite: false,
+            source_video_id,
+            start,
+            end,
+            match_id: None,
+            ingame_time_rec_start_offset: offset.map(|offset| offset + start),
+            queue: None,
+            player: source.account().cloned(),
+            champion_name: None,
+            participant_id: None,
+            events: Vec::new(),
+            highlights: Vec::new(),
+            annotations: Annotations {
+                tags: source.annotations().tags.clone(),
+                ..Default::default()
+            },
+            review: ReviewState::default(),
+        };
+
+        let (events, highlights): (&[GameEvent], &[f64]) = match source {
+            MetadataFile::Metadata(metadata) => {
+                clip.match_id = Some(metadata.match_id.clone());
+                clip.queue = Some(metadata.queue.clone());
+                clip.champion_name = Some(metadata.champion_name.clone());
+                clip.participant_id = Some(metadata.participant_id);
+                (&metadata.events, &metadata.highlights)
+            }
+            MetadataFile::Deferred(deferred) => {
+                clip.match_id = Some(deferred.match_id.clone());
+                (&[], &deferred.highlights)
+            }
+            MetadataFile::NoData(_) | MetadataFile::Reel(_) => (&[], &[]),
+            MetadataFile::Clip(source) => {
+                clip.match_id = source.match_id.clone();
+                clip.queue = source.queue.clone();
+                clip.champion_name = source.champion_name.clone();
+                clip.participant_id = source.participant_id;
+                (&source.events, &source.highlights)
+            }
+        };
+
+        // ingame times are in milliseconds, without ingame time comments use the time in the video instead
+        let to_video_time = |game_time: f64| game_time / 1000.0 - offset.unwrap_or_default();
+        let in_clip = |game_time: f64| (start..=end).contains(&to_video_time(game_time));
+
+        if offset.is_some() {
+            clip.events = events
+                .iter()
+                .filter(|event| in_clip(event.timestamp as f64))
+                .cloned()
+                .collect();
+            clip.highlights = highlights
+                .iter()
+                .copied()
+                .filter(|&highlight| in_clip(highlight))
+                .collect();
+        }
+
+        for comment in &source.annotations().comments {
+            if in_clip(comment.game_time) {
+                let game_time = match offset {
+                    Some(_) => comment.game_time,
+                    None => comment.game_time - start * 1000.0,
+                };
+                clip.annotations.add_comment(game_time, comment.text.clone());
+            }
+        }
+
+        clip
+    }
+}
+
+/// parts of one or more recordings that got joined into one video (e.g. all pentakills of a season)
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reel {
+    pub favorite: bool,
+    /// in the order they are played in the reel
+    pub segments: Vec<ReelSegment>,
+    pub annotations: Annotations,
+    pub review: ReviewState,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReelSegment {
+    /// video id of the recording the segment was copied from
+    pub source_video_id: String,
+    /// start of the segment in seconds of the source video
+    pub start: f64,
+    /// end of the segment in seconds of the source video
+    pub end: f64,
+    /// start of the segment in seconds of the reel
+    pub reel_start: f64,
+    pub match_id: Option<MatchId>,
+    pub champion_name: Option<String>,
+}
+
+impl ReelSegment {
+    /// `start` and `end` are the range of `source` that got copied, in seconds of the source video
+    pub fn new(source_video_id: String, source: &MetadataFile, start: f64, end: f64, reel_start: f64) -> Self {
+        let (match_id, champion_name) = match source {
+            MetadataFile::Metadata(metadata) => (Some(metadata.match_id.clone()), Some(metadata.champion_name.clone())),
+            MetadataFile::Deferred(deferred) => (Some(deferred.match_id.clone()), None),
+            MetadataFile::Clip(clip) => (clip.match_id.clone(), clip.champion_name.clone()),
+            MetadataFile::NoData(_) | MetadataFile::Reel(_) => (None, None),
+        };
+        Self {
+            source_video_id,
+            start,
+            end,
+            reel_start,
+            match_id,
+            champion_name,
+        }
+    }
+}
+
+/// progress of the user reviewing the recording
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewState {
+    pub watched: bool,
+    pub reviewed: bool,
+    /// time in the video in seconds where playback stopped last time
+    pub position: Option<f64>,
+}
+
+/// user-editable notes, tags and comments
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotations {
+    pub notes: String,
+    pub tags: Vec<String>,
+    pub comments: Vec<Comment>,
+}
+
+impl Annotations {
+    pub fn add_tag(&mut self, tag: String) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() || self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            return false;
+        }
+        self.tags.push(tag.to_owned());
+        true
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len = self.tags.len();
+        self.tags.retain(|t| !t.eq_ignore_ascii_case(tag));
+        self.tags.len() != len
+    }
+
+    pub fn add_comment(&mut self, game_time: f64, text: String) -> Comment {
+        let id = self
+            .comments
+            .iter()
+            .map(|comment| comment.id + 1)
+            .max()
+            .unwrap_or_default();
+        let comment = Comment { id, game_time, text };
+        self.comments.push(comment.clone());
+        self.comments.sort_by(|a, b| a.game_time.total_cmp(&b.game_time));
+        comment
+    }
+
+    pub fn comment_mut(&mut self, id: u32) -> Option<&mut Comment> {
+        self.comments.iter_mut().find(|comment| comment.id == id)
+    }
+
+    pub fn remove_comment(&mut self, id: u32) -> bool {
+        let len = self.comments.len();
+        self.comments.retain(|comment| comment.id != id);
+        self.comments.len() != len
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    /// unique inside of a metadata file
+    pub id: u32,
+    /// ingame time in milliseconds (same as `highlights`)
+    /// for `NoData` recordings the ingame time is unknown so this is the time in the video instead
+    pub game_time: f64,
+    pub text: String,
 }
 
 // seperate struct for frontend compatability since Specta is a bit limited for now and doesn't support some of the
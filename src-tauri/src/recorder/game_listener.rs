@@ -1,24 +1,35 @@
 use std::ffi::OsStr;
 use std::fmt::Display;
+use std::path::Path;
+use std::time::Duration;
 
 use anyhow::Result;
 use futures_util::StreamExt;
-use riot_datatypes::lcu::{GameData, GamePhase, SessionEventData, SubscriptionResponse};
-use riot_datatypes::{GameId, MatchId};
+use riot_datatypes::lcu::{
+    GameData, GamePhase, MapInfo, Player, RankedQueueStats, RankedStats, SessionEventData, SubscriptionResponse,
+    TeamMember,
+};
+use riot_datatypes::Team;
+use riot_datatypes::{GameId, MatchId, QueueId};
 use riot_local_auth::Credentials;
 use shaco::model::ws::{EventType, LcuSubscriptionType};
 use shaco::{rest::LcuRestClient, ws::LcuWebsocketClient};
 use tauri::async_runtime;
 use tauri::{AppHandle, Manager};
+use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 
 use super::highlight_task::HighlightTask;
 use super::metadata;
 use super::recording_task::{GameCtx, Metadata, RecordingTask};
+use super::replay;
 use crate::app::{action, AppEvent, EventManager};
 use crate::cancellable;
-use crate::recorder::MetadataFile;
-use crate::state::SettingsWrapper;
+use crate::recorder::{GameResult, MetadataFile, ReplayState, Roster, RosterEntry};
+use crate::state::{SettingsWrapper, StaticData};
+
+const RANK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const RANK_POLLS: u32 = 12;
 
 #[derive(Clone)]
 pub struct ApiCtx {
@@ -29,13 +40,22 @@ pub struct ApiCtx {
 }
 
 impl ApiCtx {
-    fn game_ctx(&self, game_id: GameId) -> GameCtx {
+    fn game_ctx(
+        &self,
+        game_id: GameId,
+        player: Option<Player>,
+        roster: Roster,
+        rank: Option<RankedQueueStats>,
+    ) -> GameCtx {
         GameCtx {
             app_handle: self.app_handle.clone(),
             match_id: MatchId {
                 game_id,
                 platform_id: self.platform_id.clone(),
             },
+            player,
+            roster,
+            rank,
             cancel_token: self.cancel_token.child_token(),
         }
     }
@@ -67,6 +87,8 @@ pub struct GameListener {
 impl GameListener {
     const GAMEFLOW_SESSION: &'static str = "/lol-gameflow/v1/session";
     const EOG_STATS_BLOCK: &'static str = "/lol-end-of-game/v1/eog-stats-block";
+    const CURRENT_SUMMONER: &'static str = "/lol-summoner/v1/current-summoner";
+    const RANKED_STATS: &'static str = "/lol-ranked/v1/current-ranked-stats";
 
     pub fn new(ctx: ApiCtx) -> Self {
         Self { ctx, state: State::Idle }
@@ -118,10 +140,32 @@ impl GameListener {
             State::Idle => match sub_resp {
                 SubscriptionResponse::Session(SessionEventData {
                     phase: GamePhase::GameStart | GamePhase::InProgress,
-                    game_data: GameData { queue, game_id },
-                }) if queue.is_ranked || !self.ctx.app_handle.state::<SettingsWrapper>().only_record_ranked() => {
+                    game_data,
+                    map,
+                }) if game_data.queue.is_ranked
+                    || !self.ctx.app_handle.state::<SettingsWrapper>().only_record_ranked() =>
+                {
+                    let lcu_rest_client = LcuRestClient::from(&self.ctx.credentials);
+
+                    // remember which account played the game
+                    let player = lcu_rest_client
+                        .get::<Player>(Self::CURRENT_SUMMONER)
+                        .await
+                        .inspect_err(|e| log::warn!("failed to get current summoner: {e}"))
+                        .ok();
+
+                    // remember the rank before the game to calculate the LP change after the game
+                    let rank = if game_data.queue.is_ranked {
+                        current_rank(&lcu_rest_client, game_data.queue.id).await
+                    } else {
+                        None
+                    };
+
+                    // save who plays which champion in case the match history never becomes available
+                    let roster = roster(map, &game_data, &self.ctx.app_handle.state::<StaticData>());
+
                     State::Recording(
-                        RecordingTask::new(self.ctx.game_ctx(game_id)),
+                        RecordingTask::new(self.ctx.game_ctx(game_data.game_id, player, roster, rank)),
                         HighlightTask::new(self.ctx.app_handle.clone()),
                     )
                 }
@@ -144,19 +188,18 @@ impl GameListener {
                     let highlight_data = highlight_task.stop().await;
                     match recording_task.stop().await {
                         Ok(metadata) => {
-                            let mut metadata_filepath = metadata.output_filepath.clone();
-                            metadata_filepath.set_extension("json");
-
-                            if let Ok(MetadataFile::Deferred(mut deferred)) =
-                                action::get_recording_metadata(&metadata_filepath, false)
-                            {
-                                deferred.highlights = highlight_data;
-                                if let Err(e) = action::save_recording_metadata(
-                                    &metadata_filepath,
-                                    &MetadataFile::Deferred(deferred),
-                                ) {
-                                    log::warn!("failed to write highlight data to deferred metadata file: {e}");
-                                }
+                            let result =
+                                action::update_recording_metadata(&metadata.output_filepath, None, |metadata_file| {
+                                    match metadata_file {
+                                        MetadataFile::Deferred(deferred) => {
+                                            deferred.highlights = highlight_data;
+                                            true
+                                        }
+                                        _ => false,
+                                    }
+                                });
+                            if let Err(e) = result {
+                                log::warn!("failed to write highlight data to deferred metadata file: {e}");
                             }
 
                             State::EndOfGame(metadata)
@@ -190,42 +233,79 @@ impl GameListener {
                             ingame_time_rec_start_offset,
                         } = metadata;
 
-                        let mut metadata_filepath = output_filepath;
-                        let video_id = metadata_filepath.file_name().and_then(OsStr::to_str).map(str::to_owned);
-                        metadata_filepath.set_extension("json");
+                        let video_path = output_filepath;
+                        let video_id = video_path.file_name().and_then(OsStr::to_str).map(str::to_owned);
 
                         match metadata::process_data_with_retry(
                             ingame_time_rec_start_offset,
-                            match_id,
+                            match_id.clone(),
                             &ctx.credentials,
+                            &ctx.app_handle.state::<StaticData>(),
                             &ctx.cancel_token,
                         )
                         .await
                         {
                             Ok(mut game_metadata) => {
-                                if let Ok(MetadataFile::Deferred(deferred)) =
-                                    action::get_recording_metadata(&metadata_filepath, false)
-                                {
-                                    game_metadata.favorite = deferred.favorite;
-                                    game_metadata.highlights = deferred.highlights;
+                                // polling the rank can take up to a minute => before updating the metadata file
+                                let rank_before = match action::find_recording_metadata(&video_path) {
+                                    Ok(Some(MetadataFile::Deferred(deferred))) => deferred.rank_before,
+                                    _ => None,
+                                };
+                                if let Some(rank_before) = &rank_before {
+                                    let lcu_rest_client = LcuRestClient::from(&ctx.credentials);
+                                    // remakes don't change the LP
+                                    let remake = game_metadata.result() == GameResult::Remake;
+                                    game_metadata.rank_after = rank_after(
+                                        &lcu_rest_client,
+                                        game_metadata.queue.id,
+                                        rank_before,
+                                        remake,
+                                        &ctx.cancel_token,
+                                    )
+                                    .await;
                                 }
 
-                                let result = action::save_recording_metadata(
-                                    &metadata_filepath,
-                                    &crate::recorder::MetadataFile::Metadata(game_metadata),
-                                );
-                                log::info!("writing game metadata to ({metadata_filepath:?}): {result:?}");
+                                // keep what the user changed while the game data was being collected
+                                let mut processed = false;
+                                let result = action::update_recording_metadata(&video_path, None, |metadata_file| {
+                                    match metadata_file {
+                                        MetadataFile::Deferred(deferred) => game_metadata.keep_deferred(deferred),
+                                        MetadataFile::NoData(no_data) => game_metadata.keep_no_data(no_data),
+                                        // already re-processed (e.g. the recording got opened in the meantime)
+                                        MetadataFile::Metadata(metadata) => {
+                                            if metadata.rank_after.is_some() || game_metadata.rank_after.is_none() {
+                                                return false;
+                                            }
+                                            metadata.rank_after = game_metadata.rank_after;
+                                            return true;
+                                        }
+                                        MetadataFile::Clip(_) | MetadataFile::Reel(_) => return false,
+                                    }
+                                    *metadata_file = MetadataFile::Metadata(game_metadata);
+                                    processed = true;
+                                    true
+                                });
+                                match result {
+                                    Ok(MetadataFile::Metadata(game_metadata)) if processed => {
+                                        log::info!("wrote game metadata of ({video_path:?})");
+                                        if ctx.app_handle.state::<SettingsWrapper>().embed_chapters() {
+                                            let result = action::embed_game_metadata(&video_path, &game_metadata);
+                                            log::info!("embedding chapters into ({video_path:?}): {result:?}");
+                                        }
+                                    }
+                                    Ok(_) => log::info!("game metadata of ({video_path:?}) was already written"),
+                                    Err(e) => log::error!("failed to write game metadata of ({video_path:?}): {e}"),
+                                }
                             }
                             Err(e) => log::error!("unable to process data: {e}"),
                         }
 
-                        if let Some(video_id) = video_id {
-                            if let Err(e) = ctx
-                                .app_handle
-                                .send_event(AppEvent::MetadataChanged { payload: vec![video_id] })
-                            {
-                                log::error!("GameListener failed to send event: {e}");
-                            }
+                        if let Some(video_id) = &video_id {
+                            send_metadata_changed(&ctx.app_handle, video_id);
+                        }
+
+                        if ctx.app_handle.state::<SettingsWrapper>().download_replays() {
+                            download_replay(&ctx, &match_id, &video_path, video_id.as_deref()).await;
                         }
                     });
 
@@ -238,3 +318,132 @@ impl GameListener {
         log::info!("recorder state: {}", self.state);
     }
 }
+
+/// lets the League client download the replay of the game and keeps track of the download in the metadata file
+async fn download_replay(ctx: &ApiCtx, match_id: &MatchId, video_path: &Path, video_id: Option<&str>) {
+    let set_replay_state = |replay_state: ReplayState| {
+        let result = action::update_recording_metadata(video_path, None, |metadata_file| {
+            metadata_file.set_replay(replay_state);
+            true
+        });
+        if let Err(e) = result {
+            log::error!("failed to save replay state of ({video_path:?}): {e}");
+        }
+        if let Some(video_id) = video_id {
+            send_metadata_changed(&ctx.app_handle, video_id);
+        }
+    };
+
+    set_replay_state(ReplayState::Downloading);
+    let lcu_rest_client = LcuRestClient::from(&ctx.credentials);
+    match replay::download_replay(&lcu_rest_client, match_id, &ctx.cancel_token).await {
+        Ok(path) => {
+            log::info!("downloaded replay of {match_id} to ({path:?})");
+            set_replay_state(ReplayState::Downloaded { path });
+        }
+        Err(e) => {
+            log::warn!("failed to download replay of {match_id}: {e}");
+            set_replay_state(ReplayState::Failed { reason: e.to_string() });
+        }
+    }
+}
+
+fn send_metadata_changed(app_handle: &AppHandle, video_id: &str) {
+    let payload = vec![video_id.to_owned()];
+    if let Err(e) = app_handle.send_event(AppEvent::MetadataChanged { payload }) {
+        log::error!("GameListener failed to send event: {e}");
+    }
+}
+
+async fn current_rank(lcu_rest_client: &LcuRestClient, queue_id: QueueId) -> Option<RankedQueueStats> {
+    lcu_rest_client
+        .get::<RankedStats>(GameListener::RANKED_STATS)
+        .await
+        .inspect_err(|e| log::warn!("failed to get ranked stats: {e}"))
+        .ok()
+        .and_then(|ranked_stats| ranked_stats.queue(queue_id).cloned())
+}
+
+/// the client updates the ranked stats a few seconds after the game ended
+/// => poll until the rank differs from the one before the game, `None` if it didn't change in time
+async fn rank_after(
+    lcu_rest_client: &LcuRestClient,
+    queue_id: QueueId,
+    rank_before: &RankedQueueStats,
+    remake: bool,
+    cancel_token: &CancellationToken,
+) -> Option<RankedQueueStats> {
+    if remake {
+        return current_rank(lcu_rest_client, queue_id).await;
+    }
+
+    for _ in 0..RANK_POLLS {
+        if let Some(rank) = current_rank(lcu_rest_client, queue_id).await {
+            if rank.total_league_points() != rank_before.total_league_points() {
+                return Some(rank);
+            }
+        }
+
+        let cancelled = cancellable!(sleep(RANK_POLL_INTERVAL), cancel_token, ());
+        if cancelled {
+            break;
+        }
+    }
+
+    log::warn!("ranked stats didn't change after the game");
+    None
+}
+
+fn roster(map: Option<MapInfo>, game_data: &GameData, static_data: &StaticData) -> Roster {
+    let roster_entry = |team: Team, member: &TeamMember| {
+        // the champion is not always set in the team list (e.g. blind pick)
+        let champion_id = member.champion_id.filter(|id| *id > 0).or_else(|| {
+            game_data
+                .player_champion_selections
+                .iter()
+                .find(|selection| selection.puuid.is_some() && selection.puuid == member.puuid)
+                .and_then(|selection| selection.champion_id)
+        });
+        let champion_id = champion_id.unwrap_or_default();
+
+        let puuid = member.puuid.clone().filter(|puuid| !puuid.is_empty());
+        let player = puuid.is_some().then(|| Player {
+            game_name: member
+                .game_name
+                .clone()
+                .or_else(|| member.summoner_name.clone())
+                .unwrap_or_default(),
+            tag_line: member.tag_line.clone().unwrap_or_default(),
+            summoner_id: member.summoner_id,
+            puuid,
+        });
+
+        RosterEntry {
+            participant_id: None,
+            team: Some(team),
+            champion_id,
+            champion_name: static_data.champion_name(champion_id),
+            player,
+            position: member.selected_position.clone().filter(|position| !position.is_empty()),
+        }
+    };
+
+    let players = game_data
+        .team_one
+        .iter()
+        .map(|member| roster_entry(Team::Blue, member))
+        .chain(game_data.team_two.iter().map(|member| roster_entry(Team::Red, member)))
+        .collect();
+
+    let (map_id, map_name, game_mode) = match map {
+        Some(map) => (map.id, map.name, map.game_mode),
+        None => (None, None, None),
+    };
+
+    Roster {
+        map_id,
+        map_name,
+        game_mode,
+        players,
+    }
+}
@@ -2,12 +2,13 @@ use std::time::Duration;
 
 use shaco::rest::LcuRestClient;
 use tauri::async_runtime::{self, JoinHandle, Mutex};
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 use tokio::time::{sleep, timeout};
 use tokio_util::sync::CancellationToken;
 
 use super::game_listener::{ApiCtx, GameListener};
 use crate::cancellable;
+use crate::state::StaticData;
 
 pub struct LeagueRecorder {
     cancel_token: CancellationToken,
@@ -31,6 +32,10 @@ impl LeagueRecorder {
                         let lcu_rest_client = LcuRestClient::from(&credentials);
 
                         if let Ok(platform_id) = lcu_rest_client.get::<String>(Self::PLATFORM_ID).await {
+                            if let Err(e) = app_handle.state::<StaticData>().update(&lcu_rest_client).await {
+                                log::warn!("failed to update static data: {e}");
+                            }
+
                             let ctx = ApiCtx {
                                 app_handle: app_handle.clone(),
                                 credentials,
@@ -1,107 +1,58 @@
 use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
-use riot_datatypes::lcu::{Game, Player};
-use riot_datatypes::{Champion, MatchId, Queue, Timeline};
+use riot_datatypes::lcu::{ChampionSummary, Game, Player};
+use riot_datatypes::{MatchId, Queue, QueueId, Timeline};
 use riot_local_auth::Credentials;
 use shaco::rest::LcuRestClient;
 use tokio::{time::sleep, try_join};
 use tokio_util::sync::CancellationToken;
 
-use super::{GameEvent, GameMetadata};
+use super::{Annotations, Deferred, GameEvent, GameMetadata, MetadataFile, ReviewState, RosterEntry};
 use crate::cancellable;
+use crate::rofl::Replay;
+use crate::state::StaticData;
+use crate::util;
 
-pub async fn process_data(ingame_time_rec_start_offset: f64, match_id: MatchId) -> Result<GameMetadata> {
+/// replays don't contain the queue of the game (only ranked games are known by the rank before the game)
+const UNKNOWN_QUEUE_ID: QueueId = -2;
+
+pub async fn process_data(
+    ingame_time_rec_start_offset: f64,
+    match_id: MatchId,
+    player: Option<Player>,
+    static_data: &StaticData,
+) -> Result<GameMetadata> {
     let lcu_rest_client = LcuRestClient::new()?;
 
-    let (player, game) = try_join!(
+    let (current_player, game) = try_join!(
         lcu_rest_client.get::<Player>("/lol-summoner/v1/current-summoner"),
         lcu_rest_client.get::<Game>(format!("/lol-match-history/v1/games/{}", match_id.game_id)),
     )?;
+    // the account that recorded the game doesn't have to be the one that is currently logged in
+    let player = player.unwrap_or(current_player);
     let timeline = lcu_rest_client
         .get::<Timeline>(format!("/lol-match-history/v1/game-timelines/{}", match_id.game_id))
         .await
         .unwrap_or_default();
 
-    let queue = match game.queue_id {
-        -1 => Queue {
-            id: -1,
-            name: "Practicetool".into(),
-            is_ranked: false,
-        },
-        0 => Queue {
-            id: 0,
-            name: "Custom Game".into(),
-            is_ranked: false,
-        },
-        id => {
-            lcu_rest_client
-                .get::<Queue>(format!("/lol-game-queues/v1/queues/{id}"))
-                .await?
-        }
-    };
-
-    let participant_id = game
-        .participant_identities
-        .iter()
-        .find(|pi| pi.player == player)
-        .map(|pi| pi.participant_id)
-        .context("player not found in game info")?;
-
-    let participant = game
-        .participants
-        .into_iter()
-        .find(|p| p.participant_id == participant_id)
-        .context("player participant_id not found in game info")?;
-
-    // manually fill data for swarm champions because the client somehow doesn't have info on them
-    // https://raw.communitydragon.org/latest/plugins/rcp-be-lol-game-data/global/default/v1/champion-summary.json
-    let champion_name = match participant.champion_id {
-        3147 => "Riven".into(),
-        3151 => "Jinx".into(),
-        3152 => "Leona".into(),
-        3153 => "Seraphine".into(),
-        3156 => "Briar".into(),
-        3157 => "Yasuo".into(),
-        3159 => "Aurora".into(),
-        3678 => "Illaoi".into(),
-        3947 => "Xayah".into(),
-        _ => {
-            lcu_rest_client
-                .get::<Champion>(format!(
-                    "/lol-champions/v1/inventories/{}/champions/{}",
-                    player.summoner_id.unwrap(),
-                    participant.champion_id
-                ))
-                .await?
-                .name
-        }
-    };
-
-    let events: Vec<GameEvent> = timeline
-        .frames
-        .into_iter()
-        .flat_map(|frame| frame.events.into_iter().filter_map(|event| event.try_into().ok()))
-        .collect();
-
-    Ok(GameMetadata {
-        favorite: false,
-        match_id,
+    collect_metadata(
+        &lcu_rest_client,
+        static_data,
         ingame_time_rec_start_offset,
-        highlights: vec![],
-        queue,
+        match_id,
         player,
-        champion_name,
-        stats: participant.stats,
-        participant_id,
-        events,
-    })
+        game,
+        timeline,
+    )
+    .await
 }
 
 pub async fn process_data_with_retry(
     ingame_time_rec_start_offset: f64,
     match_id: MatchId,
     credentials: &Credentials,
+    static_data: &StaticData,
     cancel_token: &CancellationToken,
 ) -> Result<GameMetadata> {
     let lcu_rest_client = LcuRestClient::from(credentials);
@@ -133,6 +84,27 @@ pub async fn process_data_with_retry(
     let Some((player, game)) = player_info else { bail!("unable to collect game data") };
     let timeline = timeline_data.unwrap_or_default();
 
+    collect_metadata(
+        &lcu_rest_client,
+        static_data,
+        ingame_time_rec_start_offset,
+        match_id,
+        player,
+        game,
+        timeline,
+    )
+    .await
+}
+
+async fn collect_metadata(
+    lcu_rest_client: &LcuRestClient,
+    static_data: &StaticData,
+    ingame_time_rec_start_offset: f64,
+    match_id: MatchId,
+    player: Player,
+    game: Game,
+    timeline: Timeline,
+) -> Result<GameMetadata> {
     let queue = match game.queue_id {
         -1 => Queue {
             id: -1,
@@ -144,46 +116,70 @@ pub async fn process_data_with_retry(
             name: "Custom Game".into(),
             is_ranked: false,
         },
-        id => {
-            lcu_rest_client
-                .get::<Queue>(format!("/lol-game-queues/v1/queues/{id}"))
-                .await?
-        }
+        id => match lcu_rest_client
+            .get::<Queue>(format!("/lol-game-queues/v1/queues/{id}"))
+            .await
+        {
+            Ok(queue) => queue,
+            // a ranked game saved as unranked would be missing from the LP and ranked statistics
+            Err(e) => static_data.queue(id).ok_or(e)?,
+        },
     };
 
+    // players are compared by PUUID so Riot ID changes don't break the lookup
     let participant_id = game
         .participant_identities
         .iter()
-        .find(|pi| pi.player == player)
+        .find(|pi| pi.player.is_same_account(&player))
         .map(|pi| pi.participant_id)
         .context("player not found in game info")?;
 
+    // keep the PUUID even if the current-summoner endpoint didn't deliver one
+    let player = Player {
+        puuid: player.puuid.or_else(|| {
+            game.participant_identities
+                .iter()
+                .find(|pi| pi.participant_id == participant_id)
+                .and_then(|pi| pi.player.puuid.clone())
+        }),
+        ..player
+    };
+
+    let participants = game
+        .participants
+        .iter()
+        .map(|participant| RosterEntry {
+            participant_id: Some(participant.participant_id),
+            team: participant.team_id.clone(),
+            champion_id: participant.champion_id,
+            champion_name: static_data.champion_name(participant.champion_id),
+            player: game
+                .participant_identities
+                .iter()
+                .find(|pi| pi.participant_id == participant.participant_id)
+                .map(|pi| pi.player.clone()),
+            position: None,
+        })
+        .collect();
+
     let participant = game
         .participants
         .into_iter()
         .find(|p| p.participant_id == participant_id)
         .context("player participant_id not found in game info")?;
 
-    // manually fill data for swarm champions because the client somehow doesn't have info on them
-    // https://raw.communitydragon.org/latest/plugins/rcp-be-lol-game-data/global/default/v1/champion-summary.json
-    let champion_name = match participant.champion_id {
-        3147 => "Riven".into(),
-        3151 => "Jinx".into(),
-        3152 => "Leona".into(),
-        3153 => "Seraphine".into(),
-        3156 => "Briar".into(),
-        3157 => "Yasuo".into(),
-        3159 => "Aurora".into(),
-        3678 => "Illaoi".into(),
-        3947 => "Xayah".into(),
-        _ => {
+    // the champion-summary also contains champions that only exist in special gamemodes (e.g. swarm)
+    // only ask the LCU API in case the static data isn't available or outdated
+    let champion_name = match static_data.champion_name(participant.champion_id) {
+        Some(champion_name) => champion_name,
+        None => {
             lcu_rest_client
-                .get::<Champion>(format!(
-                    "/lol-champions/v1/inventories/{}/champions/{}",
-                    player.summoner_id.unwrap(),
+                .get::<ChampionSummary>(format!(
+                    "/lol-game-data/assets/v1/champions/{}.json",
                     participant.champion_id
                 ))
-                .await?
+                .await
+                .context("failed to resolve champion name")?
                 .name
         }
     };
@@ -205,5 +201,144 @@ pub async fn process_data_with_retry(
         stats: participant.stats,
         participant_id,
         events,
+        participants,
+        patch: Some(util::patch(&game.game_version)),
+        game_version: Some(game.game_version),
+        game_duration: Some(game.game_duration),
+        game_creation: Some(game.game_creation).filter(|creation| *creation > 0),
+        map_id: Some(game.map_id),
+        game_mode: Some(game.game_mode).filter(|mode| !mode.is_empty()),
+        rank_before: None,
+        rank_after: None,
+        replay: None,
+        annotations: Annotations::default(),
+        review: ReviewState::default(),
+    })
+}
+
+/// game metadata from the end of game stats of a replay, for recordings whose game is no longer in the match history
+/// replays have no timeline and no queue, so the metadata has no events and an unknown queue
+/// the player is the account of the `Deferred` metadata or else the first of `accounts` that played in the game
+pub fn from_replay(
+    metadata_file: MetadataFile,
+    replay: Replay,
+    accounts: &[Player],
+    static_data: &StaticData,
+) -> Result<GameMetadata> {
+    let deferred = match metadata_file {
+        MetadataFile::Deferred(deferred) => {
+            if replay
+                .game_id
+                .is_some_and(|game_id| game_id != deferred.match_id.game_id)
+            {
+                bail!("replay is of a different game");
+            }
+            deferred
+        }
+        // without the ingame time comments are in the time of the video, which stays the same with an offset of 0
+        MetadataFile::NoData(no_data) => Deferred {
+            favorite: no_data.favorite,
+            match_id: MatchId {
+                game_id: replay.game_id.context("replay has no game id")?,
+                platform_id: replay.platform_id.clone().unwrap_or_default(),
+            },
+            ingame_time_rec_start_offset: 0.0,
+            highlights: Vec::new(),
+            player: None,
+            roster: None,
+            rank_before: None,
+            replay: None,
+            annotations: no_data.annotations,
+            review: no_data.review,
+        },
+        MetadataFile::Metadata(_) => bail!("recording already has game data"),
+        MetadataFile::Clip(_) | MetadataFile::Reel(_) => bail!("clips and highlight reels have no game data"),
+    };
+
+    let (index, player) = replay
+        .players
+        .iter()
+        .enumerate()
+        .find_map(|(i, entry)| {
+            let player = entry.player.as_ref()?;
+            let account = match &deferred.player {
+                Some(account) => account.is_same_account(player).then_some(account),
+                None => accounts.iter().find(|account| account.is_same_account(player)),
+            }?;
+            // keep the PUUID and summoner id of the account if the replay doesn't have them
+            Some((
+                i,
+                Player {
+                    puuid: player.puuid.clone().or_else(|| account.puuid.clone()),
+                    summoner_id: account.summoner_id,
+                    ..player.clone()
+                },
+            ))
+        })
+        .context("player not found in replay")?;
+
+    // the stats of the replay are in the order of the participant ids
+    let champion = |alias: &str| {
+        let champion_id = static_data.champion_id(alias);
+        let champion_name = champion_id
+            .and_then(|id| static_data.champion_name(id))
+            .unwrap_or_else(|| alias.to_owned());
+        (champion_id.unwrap_or_default(), champion_name)
+    };
+    let participants = replay
+        .players
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let (champion_id, champion_name) = champion(&entry.champion);
+            RosterEntry {
+                participant_id: Some(i as i64 + 1),
+                team: entry.team.clone(),
+                champion_id,
+                champion_name: Some(champion_name),
+                player: entry.player.clone(),
+                position: entry.position.clone(),
+            }
+        })
+        .collect();
+
+    let entry = &replay.players[index];
+
+    let queue = match deferred.rank_before.as_ref().and_then(|rank| rank.queue_id()) {
+        Some(id) => Queue {
+            id,
+            name: static_data.queue_name(id).unwrap_or_else(|| "Ranked".into()),
+            is_ranked: true,
+        },
+        None => Queue {
+            id: UNKNOWN_QUEUE_ID,
+            name: "Unknown Queue".into(),
+            is_ranked: false,
+        },
+    };
+
+    Ok(GameMetadata {
+        favorite: deferred.favorite,
+        match_id: deferred.match_id,
+        ingame_time_rec_start_offset: deferred.ingame_time_rec_start_offset,
+        highlights: deferred.highlights,
+        queue,
+        player,
+        champion_name: champion(&entry.champion).1,
+        stats: entry.stats.clone(),
+        participant_id: index as i64 + 1,
+        events: Vec::new(),
+        participants,
+        patch: Some(util::patch(&replay.game_version)),
+        game_version: Some(replay.game_version),
+        game_duration: Some(replay.game_length / 1000),
+        game_creation: None,
+        map_id: deferred.roster.as_ref().and_then(|roster| roster.map_id),
+        game_mode: deferred.roster.and_then(|roster| roster.game_mode),
+        rank_before: deferred.rank_before,
+        rank_after: None,
+        replay: deferred.replay,
+        annotations: deferred.annotations,
+        review: deferred.review,
     })
 }
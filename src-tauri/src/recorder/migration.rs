@@ -0,0 +1,389 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+
+use super::MetadataFile;
+
+const SCHEMA_VERSION_KEY: &str = "schemaVersion";
+
+/// each migration upgrades a metadata file by one schema version (index 0: v1 => v2, ...)
+const MIGRATIONS: &[fn(&mut Value) -> Result<()>] =
+    &[v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7, v7_to_v8];
+
+/// metadata files that were written before the schema got versioned count as version 1
+pub const SCHEMA_VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+/// version that gets written into the metadata file
+/// migrations that only added or changed some variants don't change the version of the other variants,
+/// so older versions of the app can still read them (and leave the files with the new variants alone)
+pub fn schema_version(metadata_file: &MetadataFile) -> u64 {
+    match metadata_file {
+        MetadataFile::Metadata(_) | MetadataFile::Deferred(_) => 8,
+        MetadataFile::NoData(_) => 4,
+        MetadataFile::Clip(_) => 5,
+        MetadataFile::Reel(_) => 6,
+    }
+}
+
+/// the metadata file was written by a newer version of the app and can't be read (but also must not be touched)
+#[derive(Debug, Clone, Copy)]
+pub struct UnsupportedVersion(pub u64);
+
+impl std::error::Error for UnsupportedVersion {}
+
+impl std::fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "metadata schema version {} is newer than the supported version {SCHEMA_VERSION}",
+            self.0
+        ))
+    }
+}
+
+pub enum Migrated {
+    /// the file was already up to date
+    No(MetadataFile),
+    /// the file was upgraded and should be written back
+    Yes(MetadataFile),
+}
+
+pub fn to_json(metadata_file: &MetadataFile) -> Result<Value> {
+    let mut value = serde_json::to_value(metadata_file)?;
+    let object = value.as_object_mut().context("metadata is not a json object")?;
+    object.insert(SCHEMA_VERSION_KEY.into(), json!(schema_version(metadata_file)));
+    Ok(value)
+}
+
+pub fn from_json(mut value: Value) -> Result<Migrated> {
+    let object = value.as_object_mut().context("metadata is not a json object")?;
+    let version = match object.remove(SCHEMA_VERSION_KEY) {
+        Some(version) => version.as_u64().context("invalid schema version")?,
+        None => 1,
+    };
+
+    if version == 0 {
+        bail!("invalid schema version 0");
+    }
+    if version > SCHEMA_VERSION {
+        return Err(UnsupportedVersion(version).into());
+    }
+
+    for (from_version, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migration(&mut value).with_context(|| format!("failed to migrate from schema version {}", from_version + 1))?;
+    }
+
+    let metadata_file = serde_json::from_value::<MetadataFile>(value)?;
+    if version == schema_version(&metadata_file) {
+        Ok(Migrated::No(metadata_file))
+    } else {
+        Ok(Migrated::Yes(metadata_file))
+    }
+}
+
+/// the name of the variant and its data
+fn variant_data(value: &mut Value) -> Result<(&str, &mut Map<String, Value>)> {
+    let object = value.as_object_mut().context("metadata is not a json object")?;
+    let Some((variant, data)) = object.iter_mut().next() else { bail!("metadata is empty") };
+    let data = data.as_object_mut().context("metadata variant is not a json object")?;
+    Ok((variant, data))
+}
+
+/// v2 added the `schemaVersion` field
+/// fields that were added to v1 over time only worked through `#[serde(default)]` and are now always written
+fn v1_to_v2(value: &mut Value) -> Result<()> {
+    let (variant, data) = variant_data(value)?;
+
+    data.entry("favorite").or_insert(json!(false));
+    match variant {
+        "Metadata" => {
+            data.entry("highlights").or_insert(json!([]));
+            data.entry("participants").or_insert(json!([]));
+            data.entry("gameVersion").or_insert(Value::Null);
+            data.entry("patch").or_insert(Value::Null);
+            data.entry("gameDuration").or_insert(Value::Null);
+            data.entry("gameCreation").or_insert(Value::Null);
+            data.entry("mapId").or_insert(Value::Null);
+            data.entry("gameMode").or_insert(Value::Null);
+        }
+        "Deferred" => {
+            data.entry("highlights").or_insert(json!([]));
+            data.entry("player").or_insert(Value::Null);
+            data.entry("roster").or_insert(Value::Null);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// v3 added user `annotations` (notes, tags and comments) to every variant
+fn v2_to_v3(value: &mut Value) -> Result<()> {
+    let (_, data) = variant_data(value)?;
+    data.entry("annotations")
+        .or_insert(json!({ "notes": "", "tags": [], "comments": [] }));
+    Ok(())
+}
+
+/// v4 added the `review` state (watched, reviewed and playback position) to every variant
+fn v3_to_v4(value: &mut Value) -> Result<()> {
+    let (_, data) = variant_data(value)?;
+    data.entry("review")
+        .or_insert(json!({ "watched": false, "reviewed": false, "position": null }));
+    Ok(())
+}
+
+/// v5 added the `Clip` variant, existing files don't change
+/// only clips get written as v5 so older versions of the app don't quarantine clips they can't read
+fn v4_to_v5(_value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+/// v6 added the `Reel` variant for highlight reels, existing files don't change
+fn v5_to_v6(_value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+/// v7 added the rank before the game to `Deferred` and `Metadata` and the rank after the game to `Metadata`
+/// (files of older versions can already contain them because they were added without a new version)
+fn v6_to_v7(value: &mut Value) -> Result<()> {
+    let (variant, data) = variant_data(value)?;
+    match variant {
+        "Metadata" => {
+            data.entry("rankBefore").or_insert(Value::Null);
+            data.entry("rankAfter").or_insert(Value::Null);
+        }
+        "Deferred" => {
+            data.entry("rankBefore").or_insert(Value::Null);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// v8 added the download state of the client `replay` to `Deferred` and `Metadata`
+/// (same as for v7, files of older versions can already contain it)
+fn v7_to_v8(value: &mut Value) -> Result<()> {
+    let (variant, data) = variant_data(value)?;
+    if variant == "Metadata" || variant == "Deferred" {
+        data.entry("replay").or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Annotations, NoData, ReviewState};
+    use super::*;
+
+    fn migrate(fixture: &str) -> Result<Migrated> {
+        from_json(serde_json::from_str(fixture)?)
+    }
+
+    #[test]
+    fn v1_metadata_without_highlights() -> Result<()> {
+        let Migrated::Yes(MetadataFile::Metadata(metadata)) = migrate(include_str!(
+            "../../fixtures/metadata/v1_metadata_without_highlights.json"
+        ))?
+        else {
+            bail!("expected migrated Metadata");
+        };
+        assert!(metadata.highlights.is_empty());
+        assert_eq!(metadata.champion_name, "Ahri");
+        assert!(metadata.player.puuid.is_none());
+        assert!(metadata.participants.is_empty());
+        assert!(metadata.game_version.is_none() && metadata.game_creation.is_none());
+        assert!(metadata.rank_before.is_none() && metadata.rank_after.is_none());
+        assert!(metadata.replay.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn v1_metadata_with_highlights() -> Result<()> {
+        let Migrated::Yes(MetadataFile::Metadata(metadata)) =
+            migrate(include_str!("../../fixtures/metadata/v1_metadata_with_highlights.json"))?
+        else {
+            bail!("expected migrated Metadata");
+        };
+        assert_eq!(metadata.highlights, vec![61000.0, 754500.5]);
+        assert!(metadata.favorite);
+        Ok(())
+    }
+
+    #[test]
+    fn v1_deferred_without_highlights() -> Result<()> {
+        let Migrated::Yes(MetadataFile::Deferred(deferred)) = migrate(include_str!(
+            "../../fixtures/metadata/v1_deferred_without_highlights.json"
+        ))?
+        else {
+            bail!("expected migrated Deferred");
+        };
+        assert!(deferred.highlights.is_empty());
+        assert_eq!(deferred.match_id.game_id, 7012345678);
+        assert!(deferred.player.is_none() && deferred.roster.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn v1_nodata() -> Result<()> {
+        let Migrated::Yes(MetadataFile::NoData(no_data)) =
+            migrate(include_str!("../../fixtures/metadata/v1_nodata.json"))?
+        else {
+            bail!("expected migrated NoData");
+        };
+        assert!(!no_data.favorite);
+        Ok(())
+    }
+
+    #[test]
+    fn v2_nodata_gets_annotations() -> Result<()> {
+        let Migrated::Yes(MetadataFile::NoData(no_data)) =
+            migrate(include_str!("../../fixtures/metadata/v2_nodata.json"))?
+        else {
+            bail!("expected migrated NoData");
+        };
+        assert!(no_data.favorite);
+        assert!(no_data.annotations.notes.is_empty());
+        assert!(no_data.annotations.tags.is_empty());
+        assert!(no_data.annotations.comments.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn v3_deferred_gets_review_state() -> Result<()> {
+        let Migrated::Yes(MetadataFile::Deferred(deferred)) =
+            migrate(include_str!("../../fixtures/metadata/v3_deferred.json"))?
+        else {
+            bail!("expected migrated Deferred");
+        };
+        assert_eq!(deferred.annotations.tags, vec!["laning"]);
+        assert!(!deferred.review.watched);
+        assert!(!deferred.review.reviewed);
+        assert!(deferred.review.position.is_none());
+        assert!(deferred.rank_before.is_none() && deferred.replay.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn v4_deferred_keeps_rank() -> Result<()> {
+        let Migrated::Yes(MetadataFile::Deferred(deferred)) =
+            migrate(include_str!("../../fixtures/metadata/v4_deferred_with_rank.json"))?
+        else {
+            bail!("expected migrated Deferred");
+        };
+        let rank_before = deferred.rank_before.context("rank before the game got lost")?;
+        assert_eq!(rank_before.tier, "GOLD");
+        assert_eq!(rank_before.league_points, 42);
+        assert!(deferred.replay.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn v5_clip_is_current() -> Result<()> {
+        let Migrated::No(MetadataFile::Clip(clip)) = migrate(include_str!("../../fixtures/metadata/v5_clip.json"))?
+        else {
+            bail!("expected up to date Clip");
+        };
+        assert_eq!(clip.source_video_id, "2024-12-01_12-30.mp4");
+        assert_eq!((clip.start, clip.end), (400.0, 430.0));
+        assert_eq!(clip.events.len(), 1);
+        assert_eq!(clip.annotations.notes, "first blood");
+        Ok(())
+    }
+
+    #[test]
+    fn v6_reel_is_current() -> Result<()> {
+        let Migrated::No(MetadataFile::Reel(reel)) = migrate(include_str!("../../fixtures/metadata/v6_reel.json"))?
+        else {
+            bail!("expected up to date Reel");
+        };
+        assert_eq!(reel.segments.len(), 2);
+        assert_eq!(reel.segments[1].reel_start, 30.0);
+        assert!(reel.segments[1].match_id.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn v6_deferred_gets_rank_and_replay() -> Result<()> {
+        let Migrated::Yes(MetadataFile::Deferred(deferred)) =
+            migrate(include_str!("../../fixtures/metadata/v6_deferred.json"))?
+        else {
+            bail!("expected migrated Deferred");
+        };
+        assert_eq!(deferred.highlights, [95000.0]);
+        assert!(deferred.rank_before.is_none() && deferred.replay.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn v7_metadata_keeps_rank() -> Result<()> {
+        let Migrated::Yes(MetadataFile::Metadata(metadata)) =
+            migrate(include_str!("../../fixtures/metadata/v7_metadata_with_rank.json"))?
+        else {
+            bail!("expected migrated Metadata");
+        };
+        let rank_before = metadata.rank_before.context("rank before the game got lost")?;
+        let rank_after = metadata.rank_after.context("rank after the game got lost")?;
+        assert_eq!(rank_after.league_points - rank_before.league_points, 19);
+        assert_eq!(metadata.annotations.comments[0].game_time, 412345.0);
+        assert_eq!(metadata.review.position, Some(312.5));
+        assert!(metadata.replay.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn missing_fields_of_current_version() {
+        // fields of the current version don't have defaults, migrations have to add them
+        let deferred = json!({
+            "schemaVersion": SCHEMA_VERSION,
+            "Deferred": {
+                "favorite": false,
+                "matchId": { "gameId": 7012345680u64, "platformId": "EUW1" },
+                "ingameTimeRecStartOffset": 1.0,
+                "highlights": [],
+                "player": null,
+                "roster": null,
+                "rankBefore": null,
+                "replay": null,
+                "annotations": { "notes": "", "tags": [], "comments": [] }
+            }
+        });
+        assert!(from_json(deferred).is_err());
+    }
+
+    #[test]
+    fn current_version_roundtrip() -> Result<()> {
+        let Migrated::Yes(metadata_file) =
+            migrate(include_str!("../../fixtures/metadata/v1_metadata_with_highlights.json"))?
+        else {
+            bail!("expected migrated Metadata");
+        };
+
+        let value = to_json(&metadata_file)?;
+        assert_eq!(value[SCHEMA_VERSION_KEY], json!(schema_version(&metadata_file)));
+        assert!(matches!(from_json(value)?, Migrated::No(MetadataFile::Metadata(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn new_variants_keep_old_files_readable() -> Result<()> {
+        // the app before the `Clip` variant supports up to v4
+        let value = to_json(&MetadataFile::NoData(NoData {
+            favorite: false,
+            annotations: Annotations::default(),
+            review: ReviewState::default(),
+        }))?;
+        assert_eq!(value[SCHEMA_VERSION_KEY], json!(4));
+
+        // files that got written as the latest version before are written back as v4
+        let mut latest = value;
+        latest[SCHEMA_VERSION_KEY] = json!(SCHEMA_VERSION);
+        assert!(matches!(from_json(latest)?, Migrated::Yes(MetadataFile::NoData(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_files() {
+        assert!(migrate(include_str!("../../fixtures/metadata/invalid.json")).is_err());
+        let newer = migrate(r#"{ "schemaVersion": 999, "NoData": { "favorite": false } }"#);
+        assert!(newer.is_err_and(|e| e.is::<UnsupportedVersion>()));
+    }
+}
@@ -3,10 +3,13 @@ mod game_listener;
 mod highlight_task;
 mod league_recorder;
 mod metadata;
+pub mod migration;
 mod recording_task;
+mod replay;
 #[cfg(target_os = "windows")]
 mod window;
 
 pub use data::*;
 pub use league_recorder::LeagueRecorder;
-pub use metadata::process_data;
+pub use metadata::{from_replay, process_data};
+pub use replay::watch_replay;
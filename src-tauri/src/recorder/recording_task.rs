@@ -1,4 +1,4 @@
-use std::{fmt::Display, path::PathBuf, time::Duration};
+use std::{fmt::Display, fs, path::PathBuf, time::Duration};
 
 use anyhow::{bail, Result};
 use libobs_recorder::settings::{RateControl, RecorderSettings, Resolution, StdResolution, Window};
@@ -10,12 +10,15 @@ use tauri::{AppHandle, Manager};
 use tokio::time::{interval, sleep};
 use tokio_util::sync::CancellationToken;
 
+use riot_datatypes::lcu::{Player, RankedQueueStats};
 use riot_datatypes::MatchId;
 
-use crate::app::{action, AppEvent, EventManager, RecordingManager, SystemTrayManager};
+use crate::app::{
+    account_folder_name, action, free_video_id, AppEvent, EventManager, RecordingManager, SystemTrayManager,
+};
 use crate::cancellable;
-use crate::recorder::Deferred;
-use crate::state::{CurrentlyRecording, SettingsWrapper};
+use crate::recorder::{Annotations, Deferred, ReviewState, Roster};
+use crate::state::{CurrentlyRecording, RecordingsIndex, SettingsWrapper};
 
 use super::window::{self, WINDOW_CLASS, WINDOW_PROCESS, WINDOW_TITLE};
 use super::MetadataFile;
@@ -24,6 +27,9 @@ use super::MetadataFile;
 pub struct GameCtx {
     pub app_handle: AppHandle,
     pub match_id: MatchId,
+    pub player: Option<Player>,
+    pub roster: Roster,
+    pub rank: Option<RankedQueueStats>,
     pub cancel_token: CancellationToken,
 }
 
@@ -68,6 +74,11 @@ impl RecordingTask {
             self.ctx.app_handle.cleanup_recordings();
             self.ctx.app_handle.state::<CurrentlyRecording>().set(None);
             self.ctx.app_handle.set_tray_menu_recording(false);
+            // the filewatcher ignores the video while it is being recorded
+            self.ctx
+                .app_handle
+                .state::<RecordingsIndex>()
+                .update(&metadata.output_filepath);
             if let Err(e) = self
                 .ctx
                 .app_handle
@@ -126,6 +137,12 @@ impl RecordingTask {
             match_id: ctx.match_id.clone(),
             ingame_time_rec_start_offset,
             highlights: vec![],
+            player: ctx.player.clone(),
+            roster: Some(ctx.roster.clone()),
+            rank_before: ctx.rank.clone(),
+            replay: None,
+            annotations: Annotations::default(),
+            review: ReviewState::default(),
         });
         if let Err(e) = action::save_recording_metadata(&output_filepath, &metadata_file) {
             log::info!("failed to save MetadataFile: {e}")
@@ -150,13 +167,19 @@ impl RecordingTask {
 
         log::info!("Using resolution ({output_resolution:?}) for window ({window_size:?})");
 
-        let mut filename = settings_state.get_filename_format();
-        if !filename.ends_with(".mp4") {
-            filename.push_str(".mp4");
+        let filename_format = settings_state.get_filename_format();
+        let filename = chrono::Local::now().format(&filename_format).to_string();
+        let recordings_path = settings_state.get_recordings_path();
+        let mut folder = recordings_path.clone();
+        if settings_state.account_subfolders() {
+            if let Some(player) = &ctx.player {
+                folder.push(account_folder_name(player));
+                fs::create_dir_all(&folder)?;
+            }
         }
-        let filename_path = settings_state
-            .get_recordings_path()
-            .join(format!("{}", chrono::Local::now().format(&filename)));
+        // video ids have to be unique across the recordings folder and all account subfolders
+        let video_id = free_video_id(&recordings_path, filename.strip_suffix(".mp4").unwrap_or(&filename))?;
+        let filename_path = folder.join(video_id);
 
         let mut settings = RecorderSettings::new(
             Window::new(WINDOW_TITLE, Some(WINDOW_CLASS.into()), Some(WINDOW_PROCESS.into())),
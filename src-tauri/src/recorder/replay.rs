@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use riot_datatypes::MatchId;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use shaco::rest::LcuRestClient;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+use crate::cancellable;
+use crate::rofl;
+
+/// the replay is usually available a few seconds after the game ended but the download can take a while
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_POLLS: u32 = 120;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplayMetadata {
+    /// e.g. "checking", "download", "downloading", "watch", "incompatible", "missingOrExpired"
+    state: String,
+    #[serde(default)]
+    download_progress: u32,
+}
+
+/// downloads the replay of the game via the League client and returns the path of the `.rofl` file
+/// the client only downloads replays of games that were played on the current patch
+pub async fn download_replay(
+    lcu_rest_client: &LcuRestClient,
+    match_id: &MatchId,
+    cancel_token: &CancellationToken,
+) -> Result<PathBuf> {
+    let game_id = match_id.game_id;
+
+    let mut download_requested = false;
+    for _ in 0..MAX_POLLS {
+        let metadata = lcu_rest_client
+            .get::<ReplayMetadata>(format!("/lol-replays/v1/metadata/{game_id}"))
+            .await;
+
+        match metadata {
+            Ok(ReplayMetadata { state, .. }) if state == "watch" => {
+                return replay_path(lcu_rest_client, match_id).await;
+            }
+            Ok(ReplayMetadata { state, .. }) if state == "download" && !download_requested => {
+                // the response has no body, so an error here doesn't mean that the download didn't start
+                let result = lcu_rest_client
+                    .post::<Value>(
+                        format!("/lol-replays/v1/rofls/{game_id}/download"),
+                        json!({ "componentType": "replay-button_match-history" }),
+                    )
+                    .await;
+                log::info!("requested replay download for game {game_id}: {result:?}");
+                download_requested = true;
+            }
+            Ok(ReplayMetadata { state, .. })
+                if matches!(state.as_str(), "incompatible" | "missingOrExpired" | "error" | "lost") =>
+            {
+                bail!("replay can't be downloaded ({state})");
+            }
+            Ok(ReplayMetadata { state, download_progress }) => {
+                log::info!("replay of game {game_id}: {state} ({download_progress}%)")
+            }
+            Err(e) => log::warn!("failed to get replay metadata of game {game_id}: {e}"),
+        }
+
+        let cancelled = cancellable!(sleep(POLL_INTERVAL), cancel_token, ());
+        if cancelled {
+            bail!("task cancelled (download_replay)");
+        }
+    }
+
+    bail!("timed out while downloading the replay")
+}
+
+/// starts the replay of the game in the League client, the replay has to be downloaded already
+pub async fn watch_replay(lcu_rest_client: &LcuRestClient, match_id: &MatchId) -> Result<()> {
+    let game_id = match_id.game_id;
+    let metadata = lcu_rest_client
+        .get::<ReplayMetadata>(format!("/lol-replays/v1/metadata/{game_id}"))
+        .await?;
+    if metadata.state != "watch" {
+        bail!("replay can't be watched ({})", metadata.state);
+    }
+
+    // the response has no body, so an error here doesn't mean that the replay didn't start
+    let result = lcu_rest_client
+        .post::<Value>(
+            format!("/lol-replays/v1/rofls/{game_id}/watch"),
+            json!({ "componentType": "replay-button_match-history" }),
+        )
+        .await;
+    log::info!("started replay of game {game_id}: {result:?}");
+    Ok(())
+}
+
+/// the client saves replays as `{platform id}-{game id}.rofl` in its replays folder
+async fn replay_path(lcu_rest_client: &LcuRestClient, match_id: &MatchId) -> Result<PathBuf> {
+    let folder = lcu_rest_client.get::<PathBuf>("/lol-replays/v1/rofls/path").await?;
+    Ok(folder.join(format!(
+        "{}-{}.{}",
+        match_id.platform_id,
+        match_id.game_id,
+        rofl::EXTENSION
+    )))
+}
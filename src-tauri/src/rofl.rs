@@ -0,0 +1,289 @@
+//! reader for the metadata of the `.rofl` replay files that the League client saves
+//! only the game info and the end of game stats of every player are read, the replay data itself is encrypted
+//!
+//! there are two versions of the format:
+//! - the old one starts with `RIOT\0\0`, a signature and a header with the offsets of the metadata json and the
+//!   payload header (which contains the game id)
+//! - the new one (since patch 14.x) has the metadata json at the end of the file, followed by its length
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use riot_datatypes::{lcu, GameId, Team, Timestamp};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+pub const EXTENSION: &str = "rofl";
+
+const MAGIC: &[u8; 4] = b"RIOT";
+const SIGNATURE_LENGTH: u64 = 256;
+/// the metadata json is only a few KB, anything bigger means the file isn't a replay
+const MAX_METADATA_LENGTH: u64 = 10 * 1024 * 1024;
+
+/// game info of a replay file
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// only the old format has the game id inside of the file, otherwise it's taken from the filename
+    pub game_id: Option<GameId>,
+    /// only known from the filename (e.g. `EUW1-1234567890.rofl`)
+    pub platform_id: Option<String>,
+    pub game_version: String,
+    /// in milliseconds
+    pub game_length: Timestamp,
+    /// in the order of their participant ids
+    pub players: Vec<ReplayPlayer>,
+}
+
+/// end of game stats of a player in a replay
+#[derive(Debug, Clone)]
+pub struct ReplayPlayer {
+    /// missing for bots
+    pub player: Option<lcu::Player>,
+    /// internal name of the champion (e.g. "MonkeyKing" for Wukong)
+    pub champion: String,
+    pub team: Option<Team>,
+    pub position: Option<String>,
+    pub stats: lcu::Stats,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+    game_length: Timestamp,
+    game_version: String,
+    /// json array with one object of stats per player, every value is a string
+    stats_json: String,
+}
+
+pub fn read(path: &Path) -> Result<Replay> {
+    let file_name = path.file_stem().unwrap_or_default().to_string_lossy();
+    parse(&mut BufReader::new(File::open(path)?), &file_name)
+}
+
+fn parse(reader: &mut (impl Read + Seek), file_name: &str) -> Result<Replay> {
+    let mut magic = [0; 6];
+    reader.read_exact(&mut magic).context("not a replay file")?;
+    if &magic[..4] != MAGIC {
+        bail!("not a replay file");
+    }
+
+    let (metadata, game_id) = if magic[4..] == [0, 0] {
+        read_v1(reader)?
+    } else {
+        (read_v2(reader)?, None)
+    };
+    let metadata = serde_json::from_slice::<Metadata>(&metadata).context("invalid replay metadata")?;
+    let stats =
+        serde_json::from_str::<Vec<Map<String, Value>>>(&metadata.stats_json).context("invalid replay stats")?;
+
+    // e.g. `EUW1-1234567890`
+    let (platform_id, file_game_id) = match file_name.split_once('-') {
+        Some((platform_id, game_id)) => (Some(platform_id.to_uppercase()), game_id.parse().ok()),
+        None => (None, None),
+    };
+
+    Ok(Replay {
+        game_id: game_id.or(file_game_id),
+        platform_id: platform_id.filter(|_| file_game_id.is_some()),
+        game_version: metadata.game_version,
+        game_length: metadata.game_length,
+        players: stats.iter().map(ReplayPlayer::new).collect(),
+    })
+}
+
+/// returns the metadata json and the game id of the payload header
+fn read_v1(reader: &mut (impl Read + Seek)) -> Result<(Vec<u8>, Option<GameId>)> {
+    reader.seek(SeekFrom::Current(SIGNATURE_LENGTH as i64))?;
+
+    // header length (u16) and file length (u32) aren't needed
+    let mut header = [0; 26];
+    reader.read_exact(&mut header)?;
+    let field = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap()) as u64;
+    let (metadata_offset, metadata_length) = (field(6), field(10));
+    let (payload_header_offset, payload_header_length) = (field(14), field(18));
+
+    let metadata = read_at(reader, metadata_offset, metadata_length)?;
+    let game_id = (payload_header_length >= 8)
+        .then(|| read_at(reader, payload_header_offset, 8))
+        .transpose()?
+        .map(|id| u64::from_le_bytes(id.try_into().unwrap()) as GameId);
+
+    Ok((metadata, game_id))
+}
+
+/// returns the metadata json
+fn read_v2(reader: &mut (impl Read + Seek)) -> Result<Vec<u8>> {
+    let file_length = reader.seek(SeekFrom::End(0))?;
+    if file_length < 4 {
+        bail!("replay file is too short");
+    }
+    let length = read_at(reader, file_length - 4, 4)?;
+    let length = u32::from_le_bytes(length.try_into().unwrap()) as u64;
+    if length + 4 > file_length {
+        bail!("invalid metadata length");
+    }
+    read_at(reader, file_length - 4 - length, length)
+}
+
+fn read_at(reader: &mut (impl Read + Seek), offset: u64, length: u64) -> Result<Vec<u8>> {
+    if length > MAX_METADATA_LENGTH {
+        bail!("invalid metadata length");
+    }
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut buffer = vec![0; length as usize];
+    reader.read_exact(&mut buffer).context("replay file is truncated")?;
+    Ok(buffer)
+}
+
+impl ReplayPlayer {
+    fn new(stats: &Map<String, Value>) -> Self {
+        // numbers are strings but be lenient in case that changes
+        let text = |key: &str| match stats.get(key) {
+            Some(Value::String(text)) => Some(text.clone()).filter(|text| !text.is_empty()),
+            Some(Value::Number(number)) => Some(number.to_string()),
+            _ => None,
+        };
+        let int = |key: &str| text(key).and_then(|text| text.parse::<i64>().ok()).unwrap_or_default();
+        let flag = |key: &str| int(key) != 0;
+
+        // replays of older patches only have the summoner name
+        let player = match (text("RIOT_ID_GAME_NAME").or_else(|| text("NAME")), text("PUUID")) {
+            (Some(game_name), puuid) => Some(lcu::Player {
+                game_name,
+                tag_line: text("RIOT_ID_TAG_LINE").unwrap_or_default(),
+                summoner_id: None,
+                puuid,
+            }),
+            (None, _) => None,
+        };
+        let team = match text("TEAM").as_deref() {
+            Some("100") => Some(Team::Blue),
+            Some("200") => Some(Team::Red),
+            _ => None,
+        };
+
+        ReplayPlayer {
+            player,
+            champion: text("SKIN").unwrap_or_default(),
+            team,
+            position: text("TEAM_POSITION")
+                .or_else(|| text("INDIVIDUAL_POSITION"))
+                .filter(|position| position != "Invalid"),
+            stats: lcu::Stats {
+                kills: int("CHAMPIONS_KILLED"),
+                deaths: int("NUM_DEATHS"),
+                assists: int("ASSISTS"),
+                largest_multi_kill: int("LARGEST_MULTI_KILL"),
+                neutral_minions_killed: int("NEUTRAL_MINIONS_KILLED"),
+                neutral_minions_killed_enemy_jungle: int("NEUTRAL_MINIONS_KILLED_ENEMY_JUNGLE"),
+                neutral_minions_killed_team_jungle: int("NEUTRAL_MINIONS_KILLED_YOUR_JUNGLE"),
+                total_minions_killed: int("MINIONS_KILLED"),
+                vision_score: text("VISION_SCORE")
+                    .and_then(|text| text.parse().ok())
+                    .unwrap_or_default(),
+                vision_wards_bought_in_game: int("VISION_WARDS_BOUGHT_IN_GAME"),
+                wards_placed: int("WARD_PLACED"),
+                wards_killed: int("WARD_KILLED"),
+                game_ended_in_early_surrender: flag("GAME_ENDED_IN_EARLY_SURRENDER"),
+                game_ended_in_surrender: flag("GAME_ENDED_IN_SURRENDER"),
+                win: text("WIN").as_deref() == Some("Win"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn metadata() -> Vec<u8> {
+        let stats = serde_json::json!([
+            {
+                "RIOT_ID_GAME_NAME": "Player", "RIOT_ID_TAG_LINE": "EUW", "PUUID": "abc", "SKIN": "MonkeyKing",
+                "TEAM": "100", "TEAM_POSITION": "JUNGLE", "CHAMPIONS_KILLED": "7", "NUM_DEATHS": "2", "ASSISTS": "9",
+                "MINIONS_KILLED": "40", "NEUTRAL_MINIONS_KILLED": "150", "VISION_SCORE": "31", "WIN": "Win",
+                "GAME_ENDED_IN_EARLY_SURRENDER": "0"
+            },
+            { "NAME": "", "SKIN": "Annie", "TEAM": "200", "WIN": "Fail" }
+        ]);
+        serde_json::to_vec(&serde_json::json!({
+            "gameLength": 1_800_000,
+            "gameVersion": "14.1.553.1234",
+            "lastGameChunkId": 30,
+            "lastKeyFrameId": 15,
+            "statsJson": stats.to_string(),
+        }))
+        .unwrap()
+    }
+
+    fn check(replay: &Replay) {
+        assert_eq!(replay.game_version, "14.1.553.1234");
+        assert_eq!(replay.game_length, 1_800_000);
+        assert_eq!(replay.players.len(), 2);
+
+        let player = &replay.players[0];
+        assert_eq!(player.player.as_ref().map(|player| player.puuid()), Some(Some("abc")));
+        assert_eq!(player.champion, "MonkeyKing");
+        assert_eq!(player.team, Some(Team::Blue));
+        assert_eq!(player.position.as_deref(), Some("JUNGLE"));
+        assert_eq!(
+            (player.stats.kills, player.stats.deaths, player.stats.assists),
+            (7, 2, 9)
+        );
+        assert_eq!(
+            player.stats.total_minions_killed + player.stats.neutral_minions_killed,
+            190
+        );
+        assert_eq!(player.stats.vision_score, 31.0);
+        assert!(player.stats.win);
+
+        let bot = &replay.players[1];
+        assert!(bot.player.is_none());
+        assert_eq!(bot.team, Some(Team::Red));
+        assert!(!bot.stats.win);
+    }
+
+    #[test]
+    fn old_format() -> Result<()> {
+        let metadata = metadata();
+        let metadata_offset = 6 + SIGNATURE_LENGTH as u32 + 26;
+        let payload_header_offset = metadata_offset + metadata.len() as u32;
+
+        let mut file = b"RIOT\0\0".to_vec();
+        file.extend_from_slice(&[0; SIGNATURE_LENGTH as usize]);
+        file.extend_from_slice(&288u16.to_le_bytes());
+        file.extend_from_slice(&0u32.to_le_bytes());
+        for field in [metadata_offset, metadata.len() as u32, payload_header_offset, 8, 0] {
+            file.extend_from_slice(&field.to_le_bytes());
+        }
+        file.extend_from_slice(&metadata);
+        file.extend_from_slice(&7_123_456_789u64.to_le_bytes());
+
+        let replay = parse(&mut Cursor::new(file), "replay")?;
+        assert_eq!(replay.game_id, Some(7_123_456_789));
+        assert_eq!(replay.platform_id, None);
+        check(&replay);
+        Ok(())
+    }
+
+    #[test]
+    fn new_format() -> Result<()> {
+        let metadata = metadata();
+        let mut file = b"RIOT\x02\x00".to_vec();
+        file.extend_from_slice(&[1; 1000]);
+        file.extend_from_slice(&metadata);
+        file.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+
+        let replay = parse(&mut Cursor::new(file), "euw1-7123456789")?;
+        assert_eq!(replay.game_id, Some(7_123_456_789));
+        assert_eq!(replay.platform_id.as_deref(), Some("EUW1"));
+        check(&replay);
+
+        assert!(parse(&mut Cursor::new(b"RIFF\0\0".to_vec()), "x").is_err());
+        Ok(())
+    }
+}
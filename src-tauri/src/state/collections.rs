@@ -0,0 +1,356 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::app::write_atomic;
+
+/// named list of whole recordings or time ranges inside of recordings that can be played back-to-back
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: u32,
+    pub name: String,
+    /// in playback order
+    pub items: Vec<CollectionItem>,
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionItem {
+    /// unique inside of a collection
+    pub id: u32,
+    pub video_id: String,
+    /// `None` plays the whole recording
+    pub range: Option<TimeRange>,
+}
+
+/// part of a recording that gets copied into a highlight reel
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReelPart {
+    pub video_id: String,
+    /// `None` copies the whole recording
+    pub range: Option<TimeRange>,
+}
+
+/// start and end in seconds of the video (same as the time of the player)
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TimeRange {
+    pub fn is_valid(&self) -> bool {
+        self.start >= 0.0 && self.start < self.end
+    }
+}
+
+impl Collection {
+    pub fn add_item(&mut self, video_id: String, range: Option<TimeRange>) {
+        let id = self.items.iter().map(|item| item.id + 1).max().unwrap_or_default();
+        self.items.push(CollectionItem { id, video_id, range });
+    }
+
+    pub fn remove_item(&mut self, item_id: u32) -> bool {
+        let len = self.items.len();
+        self.items.retain(|item| item.id != item_id);
+        self.items.len() != len
+    }
+
+    pub fn move_item(&mut self, item_id: u32, index: usize) -> bool {
+        move_to(&mut self.items, |item| item.id == item_id, index)
+    }
+}
+
+/// moves the first element that matches `predicate` to `index` (clamped to the end of `list`)
+fn move_to<T>(list: &mut Vec<T>, predicate: impl Fn(&T) -> bool, index: usize) -> bool {
+    let Some(position) = list.iter().position(predicate) else { return false };
+    let element = list.remove(position);
+    list.insert(index.min(list.len()), element);
+    true
+}
+
+/// the collections of the recordings folder
+/// they are stored inside of the recordings folder so they move with the recordings they reference
+#[derive(Debug)]
+pub struct Collections {
+    file: RwLock<PathBuf>,
+    collections: RwLock<Vec<Collection>>,
+}
+
+impl Collections {
+    /// hidden so it doesn't show up next to the recordings
+    pub const FILE_NAME: &str = ".collections.json";
+
+    pub fn new(recordings_path: &Path) -> Self {
+        let collections = Self {
+            file: RwLock::new(PathBuf::new()),
+            collections: RwLock::new(Vec::new()),
+        };
+        collections.load(recordings_path);
+        collections
+    }
+
+    /// (re-)load the collections of the recordings folder at `recordings_path`
+    pub fn load(&self, recordings_path: &Path) {
+        let file = recordings_path.join(Self::FILE_NAME);
+        let collections = match Self::read_file(&file) {
+            Ok(collections) => collections,
+            Err(e) => {
+                log::info!("no collections loaded ({}): {e}", file.display());
+                Vec::new()
+            }
+        };
+
+        *self.file.write().unwrap() = file;
+        *self.collections.write().unwrap() = collections;
+    }
+
+    pub fn file(&self) -> PathBuf {
+        self.file.read().unwrap().clone()
+    }
+
+    pub fn get(&self) -> Vec<Collection> {
+        self.collections.read().unwrap().clone()
+    }
+
+    pub fn get_collection(&self, collection_id: u32) -> Option<Collection> {
+        self.collections
+            .read()
+            .unwrap()
+            .iter()
+            .find(|collection| collection.id == collection_id)
+            .cloned()
+    }
+
+    pub fn create(&self, name: String) -> Result<Collection> {
+        self.update(|collections| {
+            let id = collections
+                .iter()
+                .map(|collection| collection.id + 1)
+                .max()
+                .unwrap_or_default();
+            let collection = Collection { id, name, items: Vec::new() };
+            collections.push(collection.clone());
+            Some(collection)
+        })?
+        .context("failed to create collection")
+    }
+
+    pub fn delete(&self, collection_id: u32) -> Result<bool> {
+        self.update(|collections| {
+            let len = collections.len();
+            collections.retain(|collection| collection.id != collection_id);
+            (collections.len() != len).then_some(())
+        })
+        .map(|deleted| deleted.is_some())
+    }
+
+    pub fn move_collection(&self, collection_id: u32, index: usize) -> Result<bool> {
+        self.update(|collections| {
+            move_to(collections, |collection| collection.id == collection_id, index).then_some(())
+        })
+        .map(|moved| moved.is_some())
+    }
+
+    /// applies `update` to the collection and saves it if `update` returns true
+    pub fn update_collection(
+        &self,
+        collection_id: u32,
+        update: impl FnOnce(&mut Collection) -> bool,
+    ) -> Result<Option<Collection>> {
+        self.update(|collections| {
+            let collection = collections
+                .iter_mut()
+                .find(|collection| collection.id == collection_id)?;
+            update(collection).then(|| collection.clone())
+        })
+    }
+
+    /// changes every item of `video_id` to `new_video_id`
+    /// the collections file gets written by `write` (e.g. as part of a transaction) while the collections are locked
+    /// nothing gets written if no collection contains `video_id`
+    pub fn rename_video(
+        &self,
+        video_id: &str,
+        new_video_id: &str,
+        write: impl FnOnce(&Path, &[u8]) -> Result<()>,
+    ) -> Result<bool> {
+        let renamed = self.update_with(
+            |collections| {
+                let mut renamed = false;
+                for item in collections
+                    .iter_mut()
+                    .flat_map(|collection| collection.items.iter_mut())
+                {
+                    if item.video_id == video_id {
+                        item.video_id = new_video_id.to_owned();
+                        renamed = true;
+                    }
+                }
+                renamed.then_some(())
+            },
+            write,
+        )?;
+        Ok(renamed.is_some())
+    }
+
+    fn to_json(collections: &[Collection]) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(collections)?)
+    }
+
+    /// the collections only get changed and saved if `update` returns `Some`
+    fn update<T>(&self, update: impl FnOnce(&mut Vec<Collection>) -> Option<T>) -> Result<Option<T>> {
+        self.update_with(update, write_atomic)
+    }
+
+    fn update_with<T>(
+        &self,
+        update: impl FnOnce(&mut Vec<Collection>) -> Option<T>,
+        write: impl FnOnce(&Path, &[u8]) -> Result<()>,
+    ) -> Result<Option<T>> {
+        let mut collections = self.collections.write().unwrap();
+
+        let mut updated = collections.clone();
+        let Some(result) = update(&mut updated) else { return Ok(None) };
+
+        write(&self.file(), &Self::to_json(&updated)?)?;
+        *collections = updated;
+        Ok(Some(result))
+    }
+
+    fn read_file(file: &Path) -> Result<Vec<Collection>> {
+        let reader = BufReader::new(File::open(file)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::bail;
+
+    use super::*;
+
+    fn item_ids(collection: &Collection) -> Vec<u32> {
+        collection.items.iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn move_clamped() {
+        let mut list = vec![1, 2, 3];
+        assert!(move_to(&mut list, |&n| n == 1, 10));
+        assert_eq!(list, [2, 3, 1]);
+        assert!(move_to(&mut list, |&n| n == 1, 0));
+        assert_eq!(list, [1, 2, 3]);
+        assert!(move_to(&mut list, |&n| n == 2, 2));
+        assert_eq!(list, [1, 3, 2]);
+        assert!(!move_to(&mut list, |&n| n == 4, 0));
+        assert_eq!(list, [1, 3, 2]);
+    }
+
+    #[test]
+    fn item_ids_after_remove() {
+        let mut collection = Collection {
+            id: 0,
+            name: "test".into(),
+            items: Vec::new(),
+        };
+        for video_id in ["a.mp4", "b.mp4", "c.mp4"] {
+            collection.add_item(video_id.into(), None);
+        }
+        assert_eq!(item_ids(&collection), [0, 1, 2]);
+
+        // ids of removed items in the middle don't get reused
+        assert!(collection.remove_item(1));
+        assert!(!collection.remove_item(1));
+        collection.add_item("d.mp4".into(), None);
+        assert_eq!(item_ids(&collection), [0, 2, 3]);
+
+        assert!(collection.move_item(3, 0));
+        assert_eq!(item_ids(&collection), [3, 0, 2]);
+        collection.add_item("e.mp4".into(), None);
+        assert_eq!(item_ids(&collection), [3, 0, 2, 4]);
+    }
+
+    #[test]
+    fn collections_file() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("collections_test_file_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+
+        let collections = Collections::new(&dir);
+        assert!(collections.get().is_empty());
+        assert_eq!(collections.create("first".into())?.id, 0);
+        assert_eq!(collections.create("second".into())?.id, 1);
+        assert_eq!(collections.create("third".into())?.id, 2);
+
+        // ids after the highest one that is left
+        assert!(collections.delete(1)?);
+        assert!(!collections.delete(1)?);
+        assert_eq!(collections.create("fourth".into())?.id, 3);
+
+        assert!(collections.move_collection(3, 0)?);
+        let ids = |collections: &Collections| {
+            collections
+                .get()
+                .iter()
+                .map(|collection| collection.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&collections), [3, 0, 2]);
+
+        // saved in the recordings folder
+        assert_eq!(ids(&Collections::new(&dir)), [3, 0, 2]);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn rename_video_rollback() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("collections_test_rename_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+
+        let collections = Collections::new(&dir);
+        let collection = collections.create("games".into())?;
+        collections.update_collection(collection.id, |collection| {
+            collection.add_item("game.mp4".into(), None);
+            collection.add_item("other.mp4".into(), Some(TimeRange { start: 1.0, end: 2.0 }));
+            true
+        })?;
+        let saved = fs::read(collections.file())?;
+
+        // e.g. the transaction of the rename failed
+        let result = collections.rename_video("game.mp4", "renamed.mp4", |_, _| bail!("write failed"));
+        assert!(result.is_err());
+        let video_ids = |collections: &Collections| {
+            collections.get()[0]
+                .items
+                .iter()
+                .map(|item| item.video_id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(video_ids(&collections), ["game.mp4", "other.mp4"]);
+        assert_eq!(fs::read(collections.file())?, saved);
+
+        assert!(collections.rename_video("game.mp4", "renamed.mp4", write_atomic)?);
+        assert_eq!(video_ids(&collections), ["renamed.mp4", "other.mp4"]);
+        assert_eq!(video_ids(&Collections::new(&dir)), ["renamed.mp4", "other.mp4"]);
+        // nothing to rename
+        assert!(!collections.rename_video("missing.mp4", "renamed.mp4", |_, _| bail!("nothing to write"))?);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
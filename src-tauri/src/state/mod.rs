@@ -1,13 +1,19 @@
+mod collections;
 mod currently_recording;
 mod filewatcher;
+mod recordings_index;
 mod settings;
 mod shutdown;
+mod static_data;
 mod tray_state;
 mod window_state;
 
+pub use collections::*;
 pub use currently_recording::*;
 pub use filewatcher::*;
+pub use recordings_index::*;
 pub use settings::*;
 pub use shutdown::*;
+pub use static_data::*;
 pub use tray_state::*;
 pub use window_state::*;
@@ -0,0 +1,746 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use riot_datatypes::{lcu, MatchId, Queue, QueueId, Timestamp};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime;
+use tokio::time::sleep;
+
+use crate::app::{account_id, action, write_atomic};
+use crate::recorder::{Annotations, GameResult, MetadataFile, ReviewState};
+use crate::util;
+
+/// the cached fields of a recording and its metadata file that are needed to list, sort and filter recordings
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingSummary {
+    pub video_id: String,
+    pub path: PathBuf,
+    /// in bytes
+    pub size: u64,
+    /// last modified time of the video file (unix timestamp in milliseconds)
+    pub modified: Timestamp,
+    /// last modified time of the metadata file (unix timestamp in milliseconds)
+    pub metadata_modified: Option<Timestamp>,
+    /// time of the game, falls back to the time the video file was created (unix timestamp in milliseconds)
+    pub timestamp: Timestamp,
+    pub favorite: bool,
+    pub account_id: Option<String>,
+    pub account: Option<lcu::Player>,
+    pub match_id: Option<MatchId>,
+    pub queue: Option<Queue>,
+    pub champion_name: Option<String>,
+    pub result: Option<GameResult>,
+    pub patch: Option<String>,
+    /// in seconds
+    pub game_duration: Option<Timestamp>,
+    pub stats: Option<lcu::Stats>,
+    /// champion kills of the team of the player
+    pub team_kills: Option<i64>,
+    /// LP won or lost in the game (only ranked games)
+    pub lp_delta: Option<i64>,
+    pub highlights: u32,
+    pub annotations: Annotations,
+    pub review: ReviewState,
+    /// the recording is a clip or highlight reel that got exported from other recordings
+    pub clip: bool,
+    /// the game data hasn't been collected yet (e.g. because the game wasn't in the match history yet)
+    pub deferred: bool,
+}
+
+impl RecordingSummary {
+    fn new(path: &Path) -> Result<Self> {
+        let video_id = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let file_metadata = path.metadata()?;
+        let modified = millis(file_metadata.modified()?);
+        let metadata_modified = metadata_modified(path);
+
+        let metadata_file = action::find_recording_metadata(path).unwrap_or_else(|e| {
+            log::warn!("failed to read metadata of {path:?}: {e}");
+            None
+        });
+
+        let mut summary = Self {
+            video_id,
+            path: path.to_owned(),
+            size: file_metadata.len(),
+            modified,
+            metadata_modified,
+            timestamp: util::created_timestamp(path).unwrap_or(modified),
+            favorite: false,
+            account_id: None,
+            account: None,
+            match_id: None,
+            queue: None,
+            champion_name: None,
+            result: None,
+            patch: None,
+            game_duration: None,
+            stats: None,
+            team_kills: None,
+            lp_delta: None,
+            highlights: 0,
+            annotations: Annotations::default(),
+            review: ReviewState::default(),
+            clip: false,
+            deferred: false,
+        };
+
+        let Some(metadata_file) = metadata_file else { return Ok(summary) };
+
+        summary.favorite = metadata_file.is_favorite();
+        summary.account_id = metadata_file.account().map(account_id);
+        summary.account = metadata_file.account().cloned();
+        summary.annotations = metadata_file.annotations().clone();
+        summary.review = metadata_file.review().clone();
+        summary.clip = metadata_file.is_clip();
+        if let Some(game_creation) = metadata_file.game_creation() {
+            summary.timestamp = game_creation;
+        }
+
+        match metadata_file {
+            MetadataFile::Metadata(metadata) => {
+                summary.result = Some(metadata.result());
+                summary.team_kills = metadata.team_kills();
+                summary.lp_delta = metadata.lp_delta();
+                summary.match_id = Some(metadata.match_id);
+                summary.queue = Some(metadata.queue);
+                summary.champion_name = Some(metadata.champion_name);
+                summary.patch = metadata.patch;
+                summary.game_duration = metadata.game_duration;
+                summary.stats = Some(metadata.stats);
+                summary.highlights = metadata.highlights.len() as u32;
+            }
+            MetadataFile::Deferred(deferred) => {
+                summary.deferred = true;
+                summary.match_id = Some(deferred.match_id);
+                summary.highlights = deferred.highlights.len() as u32;
+            }
+            MetadataFile::NoData(_) => {}
+            MetadataFile::Clip(clip) => {
+                summary.match_id = clip.match_id;
+                summary.queue = clip.queue;
+                summary.champion_name = clip.champion_name;
+                summary.highlights = clip.highlights.len() as u32;
+            }
+            MetadataFile::Reel(reel) => {
+                // only if every segment shows the same champion
+                let mut champions = reel.segments.into_iter().map(|segment| segment.champion_name);
+                let first = champions.next().flatten();
+                if champions.all(|champion| champion == first) {
+                    summary.champion_name = first;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// a recording of a whole game with known result (not a clip, highlight reel or recording without game data)
+    pub fn is_game(&self) -> bool {
+        !self.clip && self.result.is_some()
+    }
+
+    /// (kills + assists) / deaths, deaths count as at least 1
+    pub fn kda(&self) -> Option<f64> {
+        self.stats
+            .as_ref()
+            .map(|stats| (stats.kills + stats.assists) as f64 / stats.deaths.max(1) as f64)
+    }
+
+    /// text that the free text search of `RecordingsQuery` looks at
+    /// without the Riot ID of the account if `hide_account` is set (streamer mode)
+    fn search_text(&self, hide_account: bool) -> Vec<&str> {
+        let mut text = vec![self.video_id.as_str()];
+        text.extend(self.champion_name.as_deref());
+        text.extend(self.queue.as_ref().map(|queue| queue.name.as_str()));
+        if let Some(account) = self.account.as_ref().filter(|_| !hide_account) {
+            text.extend([account.game_name.as_str(), account.tag_line.as_str()]);
+        }
+        text.push(self.annotations.notes.as_str());
+        text.extend(self.annotations.tags.iter().map(String::as_str));
+        text.extend(self.annotations.comments.iter().map(|comment| comment.text.as_str()));
+        text
+    }
+
+    /// both summaries were created from the same state of the video and metadata file
+    fn same_files(&self, other: &Self) -> bool {
+        self.size == other.size && self.modified == other.modified && self.metadata_modified == other.metadata_modified
+    }
+
+    /// the video or metadata file changed since the summary was created
+    fn is_outdated(&self, file_metadata: &fs::Metadata) -> bool {
+        let modified = file_metadata.modified().map(millis).ok();
+        self.size != file_metadata.len()
+            || modified != Some(self.modified)
+            || metadata_modified(&self.path) != self.metadata_modified
+    }
+}
+
+/// filter for `search_recordings`, every field that is set has to match
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingsQuery {
+    /// champion name (case-insensitive)
+    pub champion: Option<String>,
+    pub queue_id: Option<QueueId>,
+    pub result: Option<GameResult>,
+    /// unix timestamp in milliseconds (inclusive)
+    pub from: Option<Timestamp>,
+    /// unix timestamp in milliseconds (inclusive)
+    pub to: Option<Timestamp>,
+    pub patch: Option<String>,
+    pub account_id: Option<String>,
+    pub favorite: Option<bool>,
+    pub min_kda: Option<f64>,
+    pub has_highlights: Option<bool>,
+    pub watched: Option<bool>,
+    pub reviewed: Option<bool>,
+    /// user tag (case-insensitive)
+    pub tag: Option<String>,
+    /// searches the name of the recording, champion, queue, Riot ID, notes, tags and comments (case-insensitive)
+    pub text: Option<String>,
+    /// number of results to skip
+    pub offset: u32,
+    /// max number of results, `None` returns all remaining results
+    pub limit: Option<u32>,
+    /// the free text search doesn't look at the Riot IDs (streamer mode)
+    #[serde(skip)]
+    pub hide_accounts: bool,
+}
+
+impl RecordingsQuery {
+    pub fn matches(&self, summary: &RecordingSummary) -> bool {
+        fn eq_ignore_case(a: Option<&str>, b: &str) -> bool {
+            a.is_some_and(|a| a.eq_ignore_ascii_case(b))
+        }
+
+        let text = self.text.as_deref().map(str::trim).filter(|text| !text.is_empty());
+
+        self.champion
+            .as_deref()
+            .is_none_or(|champion| eq_ignore_case(summary.champion_name.as_deref(), champion))
+            && self
+                .queue_id
+                .is_none_or(|queue_id| summary.queue.as_ref().is_some_and(|queue| queue.id == queue_id))
+            && self.result.is_none_or(|result| summary.result == Some(result))
+            && self.from.is_none_or(|from| summary.timestamp >= from)
+            && self.to.is_none_or(|to| summary.timestamp <= to)
+            && self
+                .patch
+                .as_deref()
+                .is_none_or(|patch| summary.patch.as_deref() == Some(patch))
+            && self
+                .account_id
+                .as_deref()
+                .is_none_or(|account_id| summary.account_id.as_deref() == Some(account_id))
+            && self.favorite.is_none_or(|favorite| summary.favorite == favorite)
+            && self
+                .min_kda
+                .is_none_or(|min_kda| summary.kda().is_some_and(|kda| kda >= min_kda))
+            && self
+                .has_highlights
+                .is_none_or(|has_highlights| (summary.highlights > 0) == has_highlights)
+            && self.watched.is_none_or(|watched| summary.review.watched == watched)
+            && self.reviewed.is_none_or(|reviewed| summary.review.reviewed == reviewed)
+            && self.tag.as_deref().is_none_or(|tag| {
+                summary
+                    .annotations
+                    .tags
+                    .iter()
+                    .any(|summary_tag| summary_tag.eq_ignore_ascii_case(tag))
+            })
+            && text.is_none_or(|text| {
+                let text = text.to_lowercase();
+                summary
+                    .search_text(self.hide_accounts)
+                    .into_iter()
+                    .any(|field| field.to_lowercase().contains(&text))
+            })
+    }
+}
+
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    /// the requested page of matching recordings sorted by time of the game (index 0 is newest)
+    pub recordings: Vec<RecordingSummary>,
+    /// number of all matching recordings
+    pub total: u32,
+    pub offset: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    recordings: Vec<RecordingSummary>,
+}
+
+/// persistent cache of the `RecordingSummary` of every recording, so listing recordings doesn't have to open every
+/// metadata file
+/// the index gets kept up to date by the filewatcher
+#[derive(Debug)]
+pub struct RecordingsIndex {
+    index_file: PathBuf,
+    recordings: Arc<RwLock<HashMap<PathBuf, RecordingSummary>>>,
+    /// `update(...)` changed the index and it hasn't been saved yet
+    save_pending: Arc<AtomicBool>,
+}
+
+impl RecordingsIndex {
+    /// increase when the `RecordingSummary` changes so old index files get rebuilt
+    const VERSION: u32 = 8;
+    /// how long `update(...)` waits before saving the index file
+    const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+    pub fn new(index_file: PathBuf) -> Self {
+        let recordings = match Self::read_index_file(&index_file) {
+            Ok(index) if index.version == Self::VERSION => index.recordings,
+            Ok(_) => {
+                log::info!("recordings index has an old version and gets rebuilt");
+                Vec::new()
+            }
+            Err(e) => {
+                log::info!("no recordings index loaded ({}): {e}", index_file.display());
+                Vec::new()
+            }
+        };
+
+        let recordings = recordings
+            .into_iter()
+            .map(|summary| (summary.path.clone(), summary))
+            .collect();
+
+        Self {
+            index_file,
+            recordings: Arc::new(RwLock::new(recordings)),
+            save_pending: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// compares the index to the `recordings` that actually exist
+    /// only recordings whose video or metadata file changed since they were last indexed get re-read
+    pub fn sync(&self, recordings: Vec<PathBuf>) {
+        // re-reading metadata files takes a while, so the lock is only held to merge the result into the index
+        let indexed = self.recordings.read().unwrap().clone();
+
+        let mut synced = HashMap::with_capacity(recordings.len());
+        for path in recordings {
+            let Ok(file_metadata) = path.metadata() else { continue };
+
+            let summary = match indexed.get(&path) {
+                Some(summary) if !summary.is_outdated(&file_metadata) => summary.clone(),
+                _ => match RecordingSummary::new(&path) {
+                    Ok(summary) => summary,
+                    Err(e) => {
+                        log::warn!("failed to index recording {path:?}: {e}");
+                        continue;
+                    }
+                },
+            };
+            synced.insert(path, summary);
+        }
+
+        {
+            let mut index = self.recordings.write().unwrap();
+            // `update(...)` calls of the filewatcher in the meantime are newer than what got synced
+            for path in indexed.keys() {
+                if !index.contains_key(path) {
+                    synced.remove(path);
+                }
+            }
+            for (path, summary) in index.drain() {
+                if !indexed.get(&path).is_some_and(|old| old.same_files(&summary)) {
+                    synced.insert(path, summary);
+                }
+            }
+            *index = synced;
+        }
+        self.save();
+    }
+
+    /// re-indexes a single recording, `path` can be the video or the metadata file
+    /// the index file gets saved a few seconds later, so a burst of filewatcher events only writes it once
+    pub fn update(&self, path: &Path) {
+        let mut video_path = path.to_owned();
+        video_path.set_extension("mp4");
+
+        let summary = if video_path.is_file() {
+            RecordingSummary::new(&video_path)
+                .inspect_err(|e| log::warn!("failed to index recording {video_path:?}: {e}"))
+                .ok()
+        } else {
+            None
+        };
+
+        {
+            let mut recordings = self.recordings.write().unwrap();
+            match summary {
+                Some(summary) => _ = recordings.insert(video_path, summary),
+                None => _ = recordings.remove(&video_path),
+            }
+        }
+        self.save_later();
+    }
+
+    /// all indexed recordings sorted by time of the game (index 0 is newest)
+    pub fn recordings(&self) -> Vec<RecordingSummary> {
+        let mut recordings = self.recordings.read().unwrap().values().cloned().collect::<Vec<_>>();
+        recordings.sort_by_key(|summary| Reverse(summary.timestamp));
+        recordings
+    }
+
+    pub fn search(&self, query: &RecordingsQuery) -> SearchResults {
+        let matching = self
+            .recordings()
+            .into_iter()
+            .filter(|summary| query.matches(summary))
+            .collect::<Vec<_>>();
+
+        let total = matching.len() as u32;
+        let recordings = matching
+            .into_iter()
+            .skip(query.offset as usize)
+            .take(query.limit.map_or(usize::MAX, |limit| limit as usize))
+            .collect();
+
+        SearchResults {
+            recordings,
+            total,
+            offset: query.offset,
+        }
+    }
+
+    /// `None` if several account subfolders contain a recording named `video_id` (there is no way to tell which one
+    /// is meant)
+    pub fn get(&self, video_id: &str) -> Option<RecordingSummary> {
+        let recordings = self.recordings.read().unwrap();
+        let mut matches = recordings.values().filter(|summary| summary.video_id == video_id);
+        match (matches.next(), matches.next()) {
+            (Some(summary), None) => Some(summary.clone()),
+            (Some(_), Some(_)) => {
+                log::warn!("found several recordings named {video_id}");
+                None
+            }
+            (None, _) => None,
+        }
+    }
+
+    /// total size of all recordings in bytes
+    pub fn total_size(&self) -> u64 {
+        self.recordings
+            .read()
+            .unwrap()
+            .values()
+            .map(|summary| summary.size)
+            .sum()
+    }
+
+    /// writes changes that are waiting for `save_later()` right away (e.g. before the app exits)
+    pub fn flush(&self) {
+        if self.save_pending.swap(false, Ordering::AcqRel) {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        save(&self.index_file, &self.recordings);
+    }
+
+    fn save_later(&self) {
+        if self.save_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let index_file = self.index_file.clone();
+        let recordings = Arc::clone(&self.recordings);
+        let save_pending = Arc::clone(&self.save_pending);
+        async_runtime::spawn(async move {
+            sleep(Self::SAVE_DELAY).await;
+            // `flush()` already saved the index in the meantime
+            if save_pending.swap(false, Ordering::AcqRel) {
+                save(&index_file, &recordings);
+            }
+        });
+    }
+
+    fn read_index_file(index_file: &Path) -> Result<IndexFile> {
+        let reader = BufReader::new(File::open(index_file)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+fn save(index_file: &Path, recordings: &RwLock<HashMap<PathBuf, RecordingSummary>>) {
+    let index = IndexFile {
+        version: RecordingsIndex::VERSION,
+        recordings: recordings.read().unwrap().values().cloned().collect(),
+    };
+
+    let result = serde_json::to_vec(&index)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| {
+            if let Some(parent) = index_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(index_file, &json)
+        });
+    if let Err(e) = result {
+        log::error!("failed to save recordings index: {e}");
+    }
+}
+
+fn metadata_modified(video_path: &Path) -> Option<Timestamp> {
+    let mut metadata_path = video_path.to_owned();
+    metadata_path.set_extension("json");
+    metadata_path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(millis)
+}
+
+fn millis(time: SystemTime) -> Timestamp {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as Timestamp
+}
+
+#[cfg(test)]
+impl RecordingSummary {
+    /// recording without metadata file (for tests of everything that works with the recordings index)
+    pub fn without_metadata(video_id: &str, timestamp: Timestamp) -> Self {
+        Self {
+            video_id: video_id.to_owned(),
+            path: PathBuf::from(video_id),
+            size: 0,
+            modified: timestamp,
+            metadata_modified: None,
+            timestamp,
+            favorite: false,
+            account_id: None,
+            account: None,
+            match_id: None,
+            queue: None,
+            champion_name: None,
+            result: None,
+            patch: None,
+            game_duration: None,
+            stats: None,
+            team_kills: None,
+            lp_delta: None,
+            highlights: 0,
+            annotations: Annotations::default(),
+            review: ReviewState::default(),
+            clip: false,
+            deferred: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(name: &str, recordings: Vec<RecordingSummary>) -> RecordingsIndex {
+        let index_file = std::env::temp_dir().join(format!("recordings_index_test_{name}_{}.json", std::process::id()));
+        let index = RecordingsIndex::new(index_file);
+        *index.recordings.write().unwrap() = recordings
+            .into_iter()
+            .map(|summary| (summary.path.clone(), summary))
+            .collect();
+        index
+    }
+
+    #[test]
+    fn query_filters() {
+        let mut ranked = RecordingSummary::without_metadata("ranked.mp4", 2_000);
+        ranked.champion_name = Some("Ahri".into());
+        ranked.queue = Some(Queue {
+            id: 420,
+            name: "Ranked Solo/Duo".into(),
+            is_ranked: true,
+        });
+        ranked.account_id = Some("puuid-1".into());
+        ranked.result = Some(GameResult::Win);
+
+        let everything = RecordingsQuery::default();
+        assert!(everything.matches(&ranked));
+        assert!(everything.matches(&RecordingSummary::without_metadata("no_data.mp4", 0)));
+
+        // dates are inclusive
+        let from_to = RecordingsQuery {
+            from: Some(2_000),
+            to: Some(2_000),
+            ..Default::default()
+        };
+        assert!(from_to.matches(&ranked));
+        let too_late = RecordingsQuery {
+            from: Some(2_001),
+            ..Default::default()
+        };
+        assert!(!too_late.matches(&ranked));
+        let too_early = RecordingsQuery {
+            to: Some(1_999),
+            ..Default::default()
+        };
+        assert!(!too_early.matches(&ranked));
+
+        let queue = |queue_id| RecordingsQuery {
+            queue_id: Some(queue_id),
+            ..Default::default()
+        };
+        assert!(queue(420).matches(&ranked));
+        assert!(!queue(440).matches(&ranked));
+        // recordings without game data don't match any queue
+        assert!(!queue(420).matches(&RecordingSummary::without_metadata("no_data.mp4", 0)));
+
+        let champion = |champion: &str| RecordingsQuery {
+            champion: Some(champion.into()),
+            ..Default::default()
+        };
+        assert!(champion("ahri").matches(&ranked));
+        assert!(!champion("Annie").matches(&ranked));
+
+        let account = |account_id: &str| RecordingsQuery {
+            account_id: Some(account_id.into()),
+            ..Default::default()
+        };
+        assert!(account("puuid-1").matches(&ranked));
+        assert!(!account("puuid-2").matches(&ranked));
+
+        // every field that is set has to match
+        let combined = RecordingsQuery {
+            champion: Some("Ahri".into()),
+            queue_id: Some(420),
+            result: Some(GameResult::Loss),
+            ..Default::default()
+        };
+        assert!(!combined.matches(&ranked));
+
+        ranked.account = Some(lcu::Player {
+            game_name: "Streamer".into(),
+            tag_line: "EUW".into(),
+            summoner_id: None,
+            puuid: Some("puuid-1".into()),
+        });
+        let text = |text: &str, hide_accounts| RecordingsQuery {
+            text: Some(text.into()),
+            hide_accounts,
+            ..Default::default()
+        };
+        assert!(text("streamer", false).matches(&ranked));
+        // in streamer mode the Riot ID can't be found by searching for it
+        assert!(!text("streamer", true).matches(&ranked));
+        assert!(text("ahri", true).matches(&ranked));
+    }
+
+    #[test]
+    fn search_pages() {
+        let index = index(
+            "search",
+            (0..5)
+                .map(|i| RecordingSummary::without_metadata(&format!("{i}.mp4"), i * 1_000))
+                .collect(),
+        );
+
+        let page = |offset, limit| {
+            let results = index.search(&RecordingsQuery {
+                offset,
+                limit,
+                ..Default::default()
+            });
+            let video_ids = results
+                .recordings
+                .into_iter()
+                .map(|summary| summary.video_id)
+                .collect::<Vec<_>>();
+            (video_ids, results.total, results.offset)
+        };
+
+        // newest first
+        assert_eq!(page(0, Some(2)), (vec!["4.mp4".to_owned(), "3.mp4".to_owned()], 5, 0));
+        assert_eq!(page(2, Some(2)), (vec!["2.mp4".to_owned(), "1.mp4".to_owned()], 5, 2));
+        assert_eq!(page(4, Some(2)), (vec!["0.mp4".to_owned()], 5, 4));
+        assert_eq!(page(5, Some(2)), (vec![], 5, 5));
+        assert_eq!(page(3, None).0.len(), 2);
+
+        // the total only counts matching recordings
+        let results = index.search(&RecordingsQuery {
+            from: Some(3_000),
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(results.total, 2);
+        assert_eq!(results.recordings[0].video_id, "4.mp4");
+    }
+
+    #[test]
+    fn get_ambiguous_video_id() {
+        let mut account_1 = RecordingSummary::without_metadata("game.mp4", 0);
+        account_1.path = PathBuf::from("Player#1").join("game.mp4");
+        let mut account_2 = RecordingSummary::without_metadata("game.mp4", 0);
+        account_2.path = PathBuf::from("Player#2").join("game.mp4");
+        let other = RecordingSummary::without_metadata("other.mp4", 0);
+
+        let index = index("ambiguous", vec![account_1, account_2, other]);
+        assert!(index.get("game.mp4").is_none());
+        assert!(index.get("other.mp4").is_some());
+    }
+
+    #[test]
+    fn sync_and_update() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("recordings_index_test_sync_{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let index_file = dir.join(".index.json");
+        let a = dir.join("a.mp4");
+        let b = dir.join("b.mp4");
+        fs::write(&a, "video a")?;
+        fs::write(&b, "video b")?;
+
+        let index = RecordingsIndex::new(index_file.clone());
+        index.sync(vec![a.clone(), b.clone()]);
+        assert_eq!(index.recordings().len(), 2);
+        assert_eq!(index.total_size(), 14);
+
+        // changed recordings get re-read, deleted ones removed from the index
+        fs::write(&a, "longer video a")?;
+        fs::remove_file(&b)?;
+        index.sync(vec![a.clone(), b.clone()]);
+        assert_eq!(index.recordings().len(), 1);
+        assert_eq!(index.get("a.mp4").map(|summary| summary.size), Some(14));
+
+        // the index gets persisted
+        assert_eq!(RecordingsIndex::new(index_file.clone()).recordings().len(), 1);
+
+        // the path of the metadata file updates the recording as well
+        let c = dir.join("c.mp4");
+        fs::write(&c, "video c")?;
+        index.update(&dir.join("c.json"));
+        assert!(index.get("c.mp4").is_some());
+        // updates only get saved after a delay or on flush
+        assert_eq!(RecordingsIndex::new(index_file.clone()).recordings().len(), 1);
+        index.flush();
+        assert_eq!(RecordingsIndex::new(index_file.clone()).recordings().len(), 2);
+
+        fs::write(&c, "edited video c")?;
+        index.update(&c);
+        assert_eq!(index.get("c.mp4").map(|summary| summary.size), Some(14));
+
+        fs::remove_file(&c)?;
+        index.update(&c);
+        assert!(index.get("c.mp4").is_none());
+        index.flush();
+        assert_eq!(RecordingsIndex::new(index_file).recordings().len(), 1);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -6,12 +6,15 @@ use std::{fmt, fs};
 
 use anyhow::Result;
 use libobs_recorder::settings::{AudioSource, Framerate, StdResolution};
+use riot_datatypes::{QueueId, Timestamp};
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use tauri::{async_runtime, AppHandle, Manager};
 
 use crate::app::{AppEvent, AppManager, EventManager, RecordingManager};
 use crate::filewatcher;
+use crate::recorder::GameResult;
+use crate::state::{Collections, RecordingSummary, RecordingsIndex};
 
 #[derive(Debug)]
 pub struct SettingsFile(PathBuf);
@@ -118,9 +121,12 @@ impl SettingsWrapper {
                 let recordings_path = settings.get_recordings_path();
                 if recordings_path != old_recordings_path {
                     filewatcher::replace(&app_handle, &recordings_path);
+                    app_handle.state::<RecordingsIndex>().sync(app_handle.get_recordings());
+                    app_handle.state::<Collections>().load(&recordings_path);
                     if let Err(e) = app_handle.send_event(AppEvent::RecordingsChanged { payload: () }) {
                         log::error!("failed to emit 'recordings_changed' event: {e}");
                     }
+                    app_handle.process_deferred_recordings();
                 }
 
                 let marker_flags = settings.get_marker_flags();
@@ -192,6 +198,51 @@ impl SettingsWrapper {
         self.0.read().unwrap().max_recordings_size_gb
     }
 
+    pub fn account_subfolders(&self) -> bool {
+        self.0.read().unwrap().account_subfolders
+    }
+
+    pub fn retention_per_account(&self) -> bool {
+        self.0.read().unwrap().retention_per_account
+    }
+
+    pub fn cleanup_clips(&self) -> bool {
+        self.0.read().unwrap().cleanup_clips
+    }
+
+    pub fn embed_chapters(&self) -> bool {
+        self.0.read().unwrap().embed_chapters
+    }
+
+    /// the replays folder of the League client if no folder is set
+    pub fn get_replays_path(&self, app_handle: &AppHandle) -> Option<PathBuf> {
+        let replays_folder = self.0.read().unwrap().replays_folder.clone();
+        replays_folder.or_else(|| {
+            let documents = app_handle.path().document_dir().ok()?;
+            Some(documents.join("League of Legends").join("Replays"))
+        })
+    }
+
+    pub fn download_replays(&self) -> bool {
+        self.0.read().unwrap().download_replays
+    }
+
+    pub fn streamer_mode(&self) -> bool {
+        self.0.read().unwrap().streamer_mode
+    }
+
+    pub fn session_gap_minutes(&self) -> u64 {
+        self.0.read().unwrap().session_gap_minutes
+    }
+
+    pub fn review_queue(&self) -> ReviewRule {
+        self.0.read().unwrap().review_queue.clone()
+    }
+
+    pub fn death_review_window(&self) -> DeathReviewWindow {
+        self.0.read().unwrap().death_review_window.clone()
+    }
+
     pub fn debug_log(&self) -> bool {
         self.0.read().unwrap().debug_log || std::env::args().any(|e| e == "-d" || e == "--debug")
     }
@@ -244,6 +295,16 @@ pub struct Settings {
     autostart: bool,
     max_recording_age_days: Option<u64>,
     max_recordings_size_gb: Option<u64>,
+    account_subfolders: bool,
+    retention_per_account: bool,
+    cleanup_clips: bool,
+    embed_chapters: bool,
+    streamer_mode: bool,
+    replays_folder: Option<PathBuf>,
+    download_replays: bool,
+    session_gap_minutes: u64,
+    review_queue: ReviewRule,
+    death_review_window: DeathReviewWindow,
     confirm_delete: bool,
     hightlight_hotkey: Option<String>,
 }
@@ -256,6 +317,13 @@ const DEFAULT_ONLY_RECORD_RANKED: bool = false;
 const DEFAULT_AUTOSTART: bool = false;
 const DEFAULT_MAX_RECORDING_AGE_DAYS: Option<u64> = None;
 const DEFAULT_MAX_RECORDINGS_SIZE_GB: Option<u64> = None;
+const DEFAULT_ACCOUNT_SUBFOLDERS: bool = false;
+const DEFAULT_RETENTION_PER_ACCOUNT: bool = false;
+const DEFAULT_CLEANUP_CLIPS: bool = false;
+const DEFAULT_EMBED_CHAPTERS: bool = false;
+const DEFAULT_STREAMER_MODE: bool = false;
+const DEFAULT_DOWNLOAD_REPLAYS: bool = false;
+const DEFAULT_SESSION_GAP_MINUTES: u64 = 60;
 const DEFAULT_CONFIRM_DELETE: bool = true;
 
 #[inline]
@@ -289,6 +357,16 @@ impl Default for Settings {
             autostart: DEFAULT_AUTOSTART,
             max_recording_age_days: DEFAULT_MAX_RECORDING_AGE_DAYS,
             max_recordings_size_gb: DEFAULT_MAX_RECORDINGS_SIZE_GB,
+            account_subfolders: DEFAULT_ACCOUNT_SUBFOLDERS,
+            retention_per_account: DEFAULT_RETENTION_PER_ACCOUNT,
+            cleanup_clips: DEFAULT_CLEANUP_CLIPS,
+            embed_chapters: DEFAULT_EMBED_CHAPTERS,
+            streamer_mode: DEFAULT_STREAMER_MODE,
+            replays_folder: None,
+            download_replays: DEFAULT_DOWNLOAD_REPLAYS,
+            session_gap_minutes: DEFAULT_SESSION_GAP_MINUTES,
+            review_queue: ReviewRule::default(),
+            death_review_window: DeathReviewWindow::default(),
             confirm_delete: DEFAULT_CONFIRM_DELETE,
             hightlight_hotkey: None,
         }
@@ -357,6 +435,36 @@ impl<'de> Deserialize<'de> for Settings {
                             settings.max_recordings_size_gb =
                                 map.next_value().unwrap_or(DEFAULT_MAX_RECORDINGS_SIZE_GB);
                         }
+                        "accountSubfolders" => {
+                            settings.account_subfolders = map.next_value().unwrap_or(DEFAULT_ACCOUNT_SUBFOLDERS);
+                        }
+                        "retentionPerAccount" => {
+                            settings.retention_per_account = map.next_value().unwrap_or(DEFAULT_RETENTION_PER_ACCOUNT);
+                        }
+                        "cleanupClips" => {
+                            settings.cleanup_clips = map.next_value().unwrap_or(DEFAULT_CLEANUP_CLIPS);
+                        }
+                        "embedChapters" => {
+                            settings.embed_chapters = map.next_value().unwrap_or(DEFAULT_EMBED_CHAPTERS);
+                        }
+                        "streamerMode" => {
+                            settings.streamer_mode = map.next_value().unwrap_or(DEFAULT_STREAMER_MODE);
+                        }
+                        "replaysFolder" => {
+                            settings.replays_folder = map.next_value().unwrap_or(None);
+                        }
+                        "downloadReplays" => {
+                            settings.download_replays = map.next_value().unwrap_or(DEFAULT_DOWNLOAD_REPLAYS);
+                        }
+                        "sessionGapMinutes" => {
+                            settings.session_gap_minutes = map.next_value().unwrap_or(DEFAULT_SESSION_GAP_MINUTES);
+                        }
+                        "reviewQueue" => {
+                            settings.review_queue = map.next_value().unwrap_or_default();
+                        }
+                        "deathReviewWindow" => {
+                            settings.death_review_window = map.next_value().unwrap_or_default();
+                        }
                         "confirmDelete" => {
                             settings.confirm_delete = map.next_value().unwrap_or(DEFAULT_CONFIRM_DELETE);
                         }
@@ -447,6 +555,61 @@ impl<'de> Deserialize<'de> for MarkerFlags {
     }
 }
 
+/// which recordings show up in the review queue (all set conditions have to match)
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReviewRule {
+    /// empty for all queues
+    queue_ids: Vec<QueueId>,
+    result: Option<GameResult>,
+    max_age_days: Option<u64>,
+}
+
+impl ReviewRule {
+    /// `now` as unix timestamp in milliseconds
+    pub fn matches(&self, summary: &RecordingSummary, now: Timestamp) -> bool {
+        (self.queue_ids.is_empty()
+            || summary
+                .queue
+                .as_ref()
+                .is_some_and(|queue| self.queue_ids.contains(&queue.id)))
+            && self.result.is_none_or(|result| summary.result == Some(result))
+            && self
+                .max_age_days
+                .is_none_or(|days| now - summary.timestamp <= days as Timestamp * 24 * 60 * 60 * 1000)
+    }
+}
+
+impl Default for ReviewRule {
+    /// ranked losses of the last 7 days
+    fn default() -> Self {
+        ReviewRule {
+            queue_ids: vec![420, 440],
+            result: Some(GameResult::Loss),
+            max_age_days: Some(7),
+        }
+    }
+}
+
+/// how much of the recording around a death is part of the death review
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeathReviewWindow {
+    pub seconds_before: u32,
+    pub seconds_after: u32,
+}
+
+impl Default for DeathReviewWindow {
+    fn default() -> Self {
+        DeathReviewWindow {
+            seconds_before: 15,
+            seconds_after: 5,
+        }
+    }
+}
+
 impl Default for MarkerFlags {
     fn default() -> Self {
         MarkerFlags {
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+use riot_datatypes::lcu::{
+    ChampionSummary, ItemSummary, PerkSummary, QueueSummaries, QueueSummary, SummonerSpellSummary,
+};
+use riot_datatypes::{ChampionId, ItemId, PerkId, Queue, QueueId, SpellId};
+use serde::{Deserialize, Serialize};
+use shaco::rest::LcuRestClient;
+use tokio::try_join;
+
+use crate::app::write_atomic;
+use crate::util;
+
+/// champion, queue, summoner-spell, item and perk names of a single patch
+#[cfg_attr(test, derive(specta::Type))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDataSummary {
+    pub patch: String,
+    pub champions: HashMap<ChampionId, String>,
+    /// internal names of the champions (e.g. "MonkeyKing" for Wukong), missing in cache files of older versions
+    #[serde(default)]
+    pub champion_aliases: HashMap<String, ChampionId>,
+    pub queues: HashMap<QueueId, String>,
+    /// whether the queues are ranked, missing in cache files of older versions
+    #[serde(default)]
+    pub ranked_queues: HashMap<QueueId, bool>,
+    pub summoner_spells: HashMap<SpellId, String>,
+    pub items: HashMap<ItemId, String>,
+    pub perks: HashMap<PerkId, String>,
+}
+
+/// offline cache of the LCU 'lol-game-data' assets so ids can be resolved to names without the client running
+/// the data gets stored on disk once per patch
+#[derive(Debug)]
+pub struct StaticData {
+    cache_dir: PathBuf,
+    data: RwLock<Arc<GameDataSummary>>,
+}
+
+impl StaticData {
+    const GAME_VERSION: &'static str = "/lol-patch/v1/game-version";
+    const CHAMPIONS: &'static str = "/lol-game-data/assets/v1/champion-summary.json";
+    const QUEUES: &'static str = "/lol-game-data/assets/v1/queues.json";
+    const GAME_QUEUES: &'static str = "/lol-game-queues/v1/queues";
+    const SUMMONER_SPELLS: &'static str = "/lol-game-data/assets/v1/summoner-spells.json";
+    const ITEMS: &'static str = "/lol-game-data/assets/v1/items.json";
+    const PERKS: &'static str = "/lol-game-data/assets/v1/perks.json";
+
+    pub fn new(cache_dir: PathBuf) -> Self {
+        // use the newest cached patch until the LCU API is available
+        let data = Self::newest_cache_file(&cache_dir)
+            .and_then(|cache_file| match Self::read_cache_file(&cache_file) {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    log::warn!("failed to read static data cache ({}): {e}", cache_file.display());
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            cache_dir,
+            data: RwLock::new(Arc::new(data)),
+        }
+    }
+
+    /// load the data for the patch the client is currently on
+    /// only downloads the data from the LCU API in case there is no cache file for the current patch
+    pub async fn update(&self, lcu_rest_client: &LcuRestClient) -> Result<()> {
+        let game_version = lcu_rest_client.get::<String>(Self::GAME_VERSION).await?;
+        let patch = util::patch(&game_version);
+
+        // cache files of older versions don't know which queues are ranked => download again
+        let is_current = |data: &GameDataSummary| data.patch == patch && !data.ranked_queues.is_empty();
+
+        if is_current(&self.get()) {
+            return Ok(());
+        }
+
+        let cache_file = self.cache_dir.join(format!("{patch}.json"));
+        if let Ok(data) = Self::read_cache_file(&cache_file) {
+            if is_current(&data) {
+                *self.data.write().unwrap() = Arc::new(data);
+                return Ok(());
+            }
+        }
+
+        let (champions, queues, game_queues, summoner_spells, items, perks) = try_join!(
+            lcu_rest_client.get::<Vec<ChampionSummary>>(Self::CHAMPIONS),
+            lcu_rest_client.get::<QueueSummaries>(Self::QUEUES),
+            lcu_rest_client.get::<Vec<Queue>>(Self::GAME_QUEUES),
+            lcu_rest_client.get::<Vec<SummonerSpellSummary>>(Self::SUMMONER_SPELLS),
+            lcu_rest_client.get::<Vec<ItemSummary>>(Self::ITEMS),
+            lcu_rest_client.get::<Vec<PerkSummary>>(Self::PERKS),
+        )?;
+
+        let data = GameDataSummary {
+            patch,
+            champion_aliases: champions.iter().map(|c| (c.alias.to_lowercase(), c.id)).collect(),
+            champions: champions.into_iter().map(|c| (c.id, c.name)).collect(),
+            queues: Vec::<QueueSummary>::from(queues)
+                .into_iter()
+                .map(|q| (q.id, q.name))
+                .collect(),
+            ranked_queues: game_queues.into_iter().map(|q| (q.id, q.is_ranked)).collect(),
+            summoner_spells: summoner_spells.into_iter().map(|s| (s.id, s.name)).collect(),
+            items: items.into_iter().map(|i| (i.id, i.name)).collect(),
+            perks: perks.into_iter().map(|p| (p.id, p.name)).collect(),
+        };
+
+        if let Err(e) = self.write_cache_file(&cache_file, &data) {
+            log::warn!("failed to write static data cache ({}): {e}", cache_file.display());
+        }
+        log::info!("updated static data to patch {}", data.patch);
+
+        *self.data.write().unwrap() = Arc::new(data);
+        Ok(())
+    }
+
+    pub fn get(&self) -> Arc<GameDataSummary> {
+        self.data.read().unwrap().clone()
+    }
+
+    pub fn champion_name(&self, id: ChampionId) -> Option<String> {
+        self.data.read().unwrap().champions.get(&id).cloned()
+    }
+
+    /// id of the champion with the internal name `alias` (e.g. in replay files)
+    pub fn champion_id(&self, alias: &str) -> Option<ChampionId> {
+        let data = self.data.read().unwrap();
+        if let Some(&id) = data.champion_aliases.get(&alias.to_lowercase()) {
+            return Some(id);
+        }
+        // the alias is the name without spaces and special characters for almost all champions (e.g. "KSante")
+        let simplify = |name: &str| {
+            name.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        };
+        let alias = simplify(alias);
+        data.champions
+            .iter()
+            .find(|(_, name)| simplify(name) == alias)
+            .map(|(&id, _)| id)
+    }
+
+    pub fn queue_name(&self, id: QueueId) -> Option<String> {
+        self.data.read().unwrap().queues.get(&id).cloned()
+    }
+
+    /// `None` if the name or whether the queue is ranked is unknown
+    pub fn queue(&self, id: QueueId) -> Option<Queue> {
+        let data = self.data.read().unwrap();
+        Some(Queue {
+            id,
+            name: data.queues.get(&id)?.clone(),
+            is_ranked: *data.ranked_queues.get(&id)?,
+        })
+    }
+
+    pub fn summoner_spell_name(&self, id: SpellId) -> Option<String> {
+        self.data.read().unwrap().summoner_spells.get(&id).cloned()
+    }
+
+    pub fn item_name(&self, id: ItemId) -> Option<String> {
+        self.data.read().unwrap().items.get(&id).cloned()
+    }
+
+    pub fn perk_name(&self, id: PerkId) -> Option<String> {
+        self.data.read().unwrap().perks.get(&id).cloned()
+    }
+
+    fn newest_cache_file(cache_dir: &Path) -> Option<PathBuf> {
+        cache_dir
+            .read_dir()
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .max_by_key(|path| path.metadata().and_then(|m| m.modified()).ok())
+    }
+
+    fn read_cache_file(cache_file: &Path) -> Result<GameDataSummary> {
+        let reader = BufReader::new(File::open(cache_file)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    fn write_cache_file(&self, cache_file: &Path, data: &GameDataSummary) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        write_atomic(cache_file, &serde_json::to_vec(data)?)?;
+
+        // only keep the data of the current patch (the old data stays until the new data is written)
+        for entry in self.cache_dir.read_dir()?.flatten() {
+            let path = entry.path();
+            if path != cache_file && path.extension().is_some_and(|ext| ext == "json") {
+                _ = fs::remove_file(path);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 
 use anyhow::Result;
 
@@ -25,6 +27,26 @@ macro_rules! cancellable {
     };
 }
 
+/// major.minor part of a game version (e.g. "15.14.695.3589" => "15.14")
+pub fn patch(game_version: &str) -> String {
+    game_version.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+/// quotes `text` if it contains a separator, quote or line break (RFC 4180)
+pub fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\"")).into()
+    } else {
+        text.into()
+    }
+}
+
+/// time the file was created as unix timestamp in milliseconds
+pub fn created_timestamp(path: &Path) -> Result<i64> {
+    let created = path.metadata()?.created()?;
+    Ok(created.duration_since(UNIX_EPOCH)?.as_millis() as i64)
+}
+
 pub fn compare_time(a: &Path, b: &Path) -> Result<Ordering> {
     let a_time = a.metadata()?.created()?;
     let b_time = b.metadata()?.created()?;
@@ -17,9 +17,30 @@ async getRecordingsPath() : Promise<string> {
 async getRecordingsSize() : Promise<number> {
     return await TAURI_INVOKE("get_recordings_size");
 },
-async getRecordingsList() : Promise<Recording[]> {
+async getRecordingsList() : Promise<RecordingSummary[]> {
     return await TAURI_INVOKE("get_recordings_list");
 },
+async searchRecordings(query: RecordingsQuery) : Promise<SearchResults> {
+    return await TAURI_INVOKE("search_recordings", { query });
+},
+async getStatistics(filter: StatisticsFilter) : Promise<Statistics> {
+    return await TAURI_INVOKE("get_statistics", { filter });
+},
+/**
+ * play sessions of all accounts or only of the given account (index 0 is newest)
+ */
+async getSessions(accountId: string | null) : Promise<Session[]> {
+    return await TAURI_INVOKE("get_sessions", { accountId });
+},
+async getAccounts() : Promise<Account[]> {
+    return await TAURI_INVOKE("get_accounts");
+},
+async getAccountRecordings(accountId: string) : Promise<RecordingSummary[]> {
+    return await TAURI_INVOKE("get_account_recordings", { accountId });
+},
+async getVideoPath(videoId: string) : Promise<string> {
+    return await TAURI_INVOKE("get_video_path", { videoId });
+},
 async openRecordingsFolder() : Promise<void> {
     await TAURI_INVOKE("open_recordings_folder");
 },
@@ -35,11 +56,183 @@ async getMetadata(videoId: string) : Promise<MetadataFile | null> {
 async toggleFavorite(videoId: string) : Promise<boolean | null> {
     return await TAURI_INVOKE("toggle_favorite", { videoId });
 },
+async setNotes(videoId: string, notes: string) : Promise<Annotations | null> {
+    return await TAURI_INVOKE("set_notes", { videoId, notes });
+},
+async addTag(videoId: string, tag: string) : Promise<Annotations | null> {
+    return await TAURI_INVOKE("add_tag", { videoId, tag });
+},
+async removeTag(videoId: string, tag: string) : Promise<Annotations | null> {
+    return await TAURI_INVOKE("remove_tag", { videoId, tag });
+},
+/**
+ * `game_time` in milliseconds (for recordings without game data it's the time in the video instead)
+ */
+async addComment(videoId: string, gameTime: number, text: string) : Promise<Annotations | null> {
+    return await TAURI_INVOKE("add_comment", { videoId, gameTime, text });
+},
+async editComment(videoId: string, commentId: number, text: string) : Promise<Annotations | null> {
+    return await TAURI_INVOKE("edit_comment", { videoId, commentId, text });
+},
+async deleteComment(videoId: string, commentId: number) : Promise<Annotations | null> {
+    return await TAURI_INVOKE("delete_comment", { videoId, commentId });
+},
+async setWatched(videoId: string, watched: boolean) : Promise<ReviewState | null> {
+    return await TAURI_INVOKE("set_watched", { videoId, watched });
+},
+/**
+ * a reviewed recording also counts as watched
+ */
+async setReviewed(videoId: string, reviewed: boolean) : Promise<ReviewState | null> {
+    return await TAURI_INVOKE("set_reviewed", { videoId, reviewed });
+},
+/**
+ * `position` in seconds of the video, `None` if the recording was watched until the end
+ * doesn't create or re-process a metadata file (this gets called every time the playback pauses)
+ */
+async setPlaybackPosition(videoId: string, position: number | null) : Promise<ReviewState | null> {
+    return await TAURI_INVOKE("set_playback_position", { videoId, position });
+},
+/**
+ * recordings that haven't been reviewed yet and match the `reviewQueue` rule of the settings (oldest first)
+ */
+async getReviewQueue() : Promise<RecordingSummary[]> {
+    return await TAURI_INVOKE("get_review_queue");
+},
+/**
+ * playlist of every death of the player in the recording
+ * `window` defaults to the `deathReviewWindow` of the settings
+ */
+async getRecordingDeaths(videoId: string, window: DeathReviewWindow | null) : Promise<DeathWindow[]> {
+    return await TAURI_INVOKE("get_recording_deaths", { videoId, window });
+},
+/**
+ * playlist of every death of the player across all recordings that match the query (e.g. all deaths on a champion
+ * in the last week), sorted the same way as the recordings of `search_recordings`
+ * `window` defaults to the `deathReviewWindow` of the settings
+ */
+async getDeathReview(query: RecordingsQuery, window: DeathReviewWindow | null) : Promise<DeathWindow[]> {
+    return await TAURI_INVOKE("get_death_review", { query, window });
+},
+/**
+ * cuts the part between `start` and `end` (in seconds of the video) out of the recording into the clips folder
+ * the clip starts at the last keyframe before `start`, returns the video id of the clip
+ */
+async exportClip(videoId: string, start: number, end: number) : Promise<string | null> {
+    return await TAURI_INVOKE("export_clip", { videoId, start, end });
+},
+/**
+ * joins the parts (e.g. the items of a collection or the deaths of a death review) into one video in the clips
+ * folder without re-encoding, all recordings need the same encoder settings
+ * returns the video id of the highlight reel
+ */
+async exportHighlightReel(name: string, parts: ReelPart[]) : Promise<string | null> {
+    return await TAURI_INVOKE("export_highlight_reel", { name, parts });
+},
+/**
+ * writes the markers of the recording as a sidecar file in the markers folder, returns the path of the file
+ */
+async exportMarkers(videoId: string, format: MarkerFormat) : Promise<string | null> {
+    return await TAURI_INVOKE("export_markers", { videoId, format });
+},
+/**
+ * writes one row per recording that matches `query` (oldest first) with the selected `columns` (all if empty) into
+ * the recordings folder, returns the path of the ledger
+ */
+async exportLedger(query: RecordingsQuery, columns: LedgerColumn[], format: LedgerFormat) : Promise<string | null> {
+    return await TAURI_INVOKE("export_ledger", { query, columns, format });
+},
+/**
+ * post-game report of the recording (e.g. to copy it to the clipboard), `None` if the recording has no game data
+ */
+async getReport(videoId: string, format: ReportFormat) : Promise<string | null> {
+    return await TAURI_INVOKE("get_report", { videoId, format });
+},
+/**
+ * writes the post-game report next to the recording, returns the path of the report
+ * (only the file name in streamer mode because the account subfolder can contain the Riot ID)
+ */
+async saveReport(videoId: string, format: ReportFormat) : Promise<string | null> {
+    return await TAURI_INVOKE("save_report", { videoId, format });
+},
+/**
+ * packages the recording with its metadata into a zip archive in the bundles folder, returns the path of the bundle
+ * if `anonymize` is true all players in the metadata of the bundle are replaced by pseudonyms
+ */
+async exportBundle(videoId: string, anonymize: boolean) : Promise<string | null> {
+    return await TAURI_INVOKE("export_bundle", { videoId, anonymize });
+},
+/**
+ * adds the recording of the bundle (e.g. sent back by a coach) to the recordings, returns the video id of the
+ * imported recording
+ */
+async importBundle(path: string) : Promise<string | null> {
+    return await TAURI_INVOKE("import_bundle", { path });
+},
+/**
+ * all replays in the replays folder of the League client, linked to the recordings of the same games
+ */
+async scanReplays() : Promise<ReplayFile[]> {
+    return await TAURI_INVOKE("scan_replays");
+},
+/**
+ * fills in the game data of a recording without game data (e.g. because the game is no longer in the match history)
+ * from the replay of the game
+ */
+async backfillFromReplay(videoId: string, replayPath: string) : Promise<boolean> {
+    return await TAURI_INVOKE("backfill_from_replay", { videoId, replayPath });
+},
+/**
+ * starts the replay of the recorded game in the League client
+ * the client has to be running and the replay has to be downloaded (only possible for games of the current patch)
+ */
+async openReplay(videoId: string) : Promise<boolean> {
+    return await TAURI_INVOKE("open_replay", { videoId });
+},
+async getCollections() : Promise<Collection[]> {
+    return await TAURI_INVOKE("get_collections");
+},
+async createCollection(name: string) : Promise<Collection | null> {
+    return await TAURI_INVOKE("create_collection", { name });
+},
+async deleteCollection(collectionId: number) : Promise<boolean> {
+    return await TAURI_INVOKE("delete_collection", { collectionId });
+},
+/**
+ * moves the collection to `index` in the list of collections
+ */
+async moveCollection(collectionId: number, index: number) : Promise<boolean> {
+    return await TAURI_INVOKE("move_collection", { collectionId, index });
+},
+/**
+ * `range` in seconds of the video, `None` adds the whole recording
+ */
+async addToCollection(collectionId: number, videoId: string, range: TimeRange | null) : Promise<Collection | null> {
+    return await TAURI_INVOKE("add_to_collection", { collectionId, videoId, range });
+},
+async removeFromCollection(collectionId: number, itemId: number) : Promise<Collection | null> {
+    return await TAURI_INVOKE("remove_from_collection", { collectionId, itemId });
+},
+/**
+ * moves the item to `index` inside of the collection
+ */
+async moveCollectionItem(collectionId: number, itemId: number, index: number) : Promise<Collection | null> {
+    return await TAURI_INVOKE("move_collection_item", { collectionId, itemId, index });
+},
+/**
+ * the items of the collection in playback order, items of recordings that don't exist (anymore) are skipped
+ */
+async getCollectionPlaylist(collectionId: number) : Promise<CollectionItem[]> {
+    return await TAURI_INVOKE("get_collection_playlist", { collectionId });
+},
 async confirmDelete() : Promise<boolean> {
     return await TAURI_INVOKE("confirm_delete");
 },
 async disableConfirmDelete() : Promise<void> {
     await TAURI_INVOKE("disable_confirm_delete");
+},
+async getStaticData() : Promise<GameDataSummary> {
+    return await TAURI_INVOKE("get_static_data");
 }
 }
 
@@ -58,22 +251,414 @@ appEvent: "app-event"
 
 /** user-defined types **/
 
-export type AppEvent = { type: "RecordingsChanged"; payload: null } | { type: "MetadataChanged"; payload: string[] } | { type: "MarkerflagsChanged"; payload: null }
+export type Account = { accountId: string; gameName: string; tagLine: string; recordings: number }
+/**
+ * user-editable notes, tags and comments
+ */
+export type Annotations = { notes: string; tags: string[]; comments: Comment[] }
+export type AppEvent = { type: "RecordingsChanged"; payload: null } | { type: "MetadataChanged"; payload: string[] } | { type: "MarkerflagsChanged"; payload: null } | { type: "SessionUpdated"; payload: Session }
 export type BuildingType = { buildingType: "INHIBITOR_BUILDING"; lane_type: LaneType } | { buildingType: "TOWER_BUILDING"; lane_type: LaneType; tower_type: TowerType }
-export type Deferred = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights?: number[] }
+/**
+ * part of a recording that got cut into its own video file
+ * keeps the data of the source recording that is needed to show the clip like a recording
+ */
+export type Clip = { favorite: boolean; 
+/**
+ * video id of the recording the clip was cut from
+ */
+sourceVideoId: string; 
+/**
+ * start of the clip in seconds of the source video
+ */
+start: number; 
+/**
+ * end of the clip in seconds of the source video
+ */
+end: number; matchId: MatchId | null; 
+/**
+ * `None` if the ingame time of the source recording is unknown
+ */
+ingameTimeRecStartOffset: number | null; queue: Queue | null; player: Player | null; championName: string | null; participantId: number | null; 
+/**
+ * the events of the source recording inside of the clip
+ */
+events: GameEvent[]; 
+/**
+ * the highlights of the source recording inside of the clip
+ */
+highlights: number[]; annotations: Annotations; review: ReviewState }
+/**
+ * named list of whole recordings or time ranges inside of recordings that can be played back-to-back
+ */
+export type Collection = { id: number; name: string; 
+/**
+ * in playback order
+ */
+items: CollectionItem[] }
+export type CollectionItem = { 
+/**
+ * unique inside of a collection
+ */
+id: number; videoId: string; 
+/**
+ * `None` plays the whole recording
+ */
+range: TimeRange | null }
+export type Comment = { 
+/**
+ * unique inside of a metadata file
+ */
+id: number; 
+/**
+ * ingame time in milliseconds (same as `highlights`)
+ * for `NoData` recordings the ingame time is unknown so this is the time in the video instead
+ */
+gameTime: number; text: string }
+/**
+ * how much of the recording around a death is part of the death review
+ */
+export type DeathReviewWindow = { secondsBefore: number; secondsAfter: number }
+/**
+ * part of a recording around a death of the player
+ */
+export type DeathWindow = { videoId: string; championName: string; 
+/**
+ * ingame time of the death in milliseconds
+ */
+gameTime: number; 
+/**
+ * in seconds of the video
+ */
+range: TimeRange; 
+/**
+ * champion of the killer, `None` if the killer wasn't a champion (e.g. turret or minions) or is unknown
+ */
+killer: string | null; 
+/**
+ * champions of the assisters (empty if unknown)
+ */
+assisters: string[] }
+export type Deferred = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights: number[]; 
+/**
+ * the account that was logged in when the recording started
+ */
+player: Player | null; 
+/**
+ * lineup from the gameflow session when the recording started
+ */
+roster: Roster | null; 
+/**
+ * rank in the queue of the game before it started (only ranked queues)
+ */
+rankBefore: RankedQueueStats | null; 
+/**
+ * download of the replay of the League client (only if replays get downloaded)
+ */
+replay: ReplayState | null; annotations: Annotations; review: ReviewState }
 export type DragonType = "FIRE_DRAGON" | "EARTH_DRAGON" | "WATER_DRAGON" | "AIR_DRAGON" | "HEXTECH_DRAGON" | "CHEMTECH_DRAGON" | "ELDER_DRAGON"
-export type GameEvent = ({ ChampionKill: { victim_id: number; killer_id: number; assisting_participant_ids: number[]; position: Position } } | { BuildingKill: { team_id: Team; killer_id: number; building_type: BuildingType; assisting_participant_ids: number[] } } | { EliteMonsterKill: { killer_id: number; monster_type: MonsterType; assisting_participant_ids: number[] } }) & { timestamp: number }
-export type GameMetadata = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights?: number[]; queue: Queue; player: Player; championName: string; stats: Stats; participantId: number; events: GameEvent[] }
+/**
+ * champion, queue, summoner-spell, item and perk names of a single patch
+ */
+export type GameDataSummary = { patch: string; champions: Partial<{ [key in number]: string }>; 
+/**
+ * internal names of the champions (e.g. "MonkeyKing" for Wukong), missing in cache files of older versions
+ */
+championAliases?: Partial<{ [key in string]: number }>; queues: Partial<{ [key in number]: string }>; 
+/**
+ * whether the queues are ranked, missing in cache files of older versions
+ */
+rankedQueues?: Partial<{ [key in number]: boolean }>; summonerSpells: Partial<{ [key in number]: string }>; items: Partial<{ [key in number]: string }>; perks: Partial<{ [key in number]: string }> }
+export type GameEvent = ({ ChampionKill: { victim_id: number; killer_id: number; assisting_participant_ids: number[]; position: Position } } | { BuildingKill: { team_id: number; killer_id: number; building_type: BuildingType; assisting_participant_ids: number[] } } | { EliteMonsterKill: { killer_id: number; monster_type: MonsterType; assisting_participant_ids: number[] } }) & { timestamp: number }
+export type GameMetadata = { favorite: boolean; matchId: MatchId; ingameTimeRecStartOffset: number; highlights: number[]; queue: Queue; player: Player; championName: string; stats: Stats; participantId: number; events: GameEvent[]; 
+/**
+ * all players of the game with their participant ids (from match history)
+ */
+participants: RosterEntry[]; gameVersion: string | null; 
+/**
+ * major.minor part of `game_version` (e.g. "15.14")
+ */
+patch: string | null; 
+/**
+ * in seconds
+ */
+gameDuration: number | null; 
+/**
+ * unix timestamp in milliseconds
+ */
+gameCreation: number | null; mapId: number | null; gameMode: string | null; 
+/**
+ * rank in the queue of the game before it started (only ranked queues)
+ */
+rankBefore: RankedQueueStats | null; 
+/**
+ * rank in the queue of the game after it ended (only ranked queues)
+ */
+rankAfter: RankedQueueStats | null; 
+/**
+ * download of the replay of the League client (only if replays get downloaded)
+ */
+replay: ReplayState | null; annotations: Annotations; review: ReviewState }
+export type GameResult = "Win" | "Loss" | "Remake"
+/**
+ * remakes only count towards `games` and `remakes`
+ * averages are `None` if there is no game with the necessary data
+ */
+export type GroupStatistics = { name: string; games: number; wins: number; losses: number; remakes: number; winrate: number | null; 
+/**
+ * (kills + assists) / deaths over all games
+ */
+kda: number | null; averageKills: number | null; averageDeaths: number | null; averageAssists: number | null; csPerMinute: number | null; visionScorePerMinute: number | null; killParticipation: number | null }
 export type LaneType = "TOP_LANE" | "MID_LANE" | "BOT_LANE"
+export type LedgerColumn = "VideoId" | "Date" | "MatchId" | "Queue" | "Champion" | "Result" | "Kills" | "Deaths" | "Assists" | "Kda" | "Cs" | "CsPerMinute" | "VisionScore" | 
+/**
+ * in seconds
+ */
+"Duration" | "Patch" | "LpDelta"
+export type LedgerFormat = "Csv" | "JsonLines"
 export type MarkerFlags = { kill: boolean; death: boolean; assist: boolean; structure: boolean; dragon: boolean; herald: boolean; atakhan: boolean; baron: boolean }
+/**
+ * sidecar file formats the markers of a recording can be exported as
+ */
+export type MarkerFormat = "WebVtt" | "Srt" | "Csv" | 
+/**
+ * CMX3600 EDL with DaVinci Resolve marker comments
+ */
+"Edl" | 
+/**
+ * Final Cut Pro 7 XML sequence with markers (e.g. for Adobe Premiere)
+ */
+"Xml"
 export type MatchId = { gameId: number; platformId: string }
-export type MetadataFile = { Metadata: GameMetadata } | { Deferred: Deferred } | { NoData: NoData }
+export type MetadataFile = { Metadata: GameMetadata } | { Deferred: Deferred } | { NoData: NoData } | { Clip: Clip } | { Reel: Reel }
 export type MonsterType = { monsterType: "HORDE" } | { monsterType: "RIFTHERALD" } | { monsterType: "ATAKHAN" } | { monsterType: "BARON_NASHOR" } | { monsterType: "DRAGON"; monsterSubType: DragonType }
-export type NoData = { favorite: boolean }
-export type Player = { gameName: string; tagLine: string; summonerId?: number | null }
+export type NoData = { favorite: boolean; annotations: Annotations; review: ReviewState }
+export type Player = { gameName: string; tagLine: string; summonerId?: number | null; 
+/**
+ * missing in metadata files that were created before the PUUID was saved
+ */
+puuid?: string | null }
 export type Position = { x: number; y: number }
 export type Queue = { id: number; name: string; isRanked: boolean }
-export type Recording = { videoId: string; metadata: MetadataFile | null }
+export type RankedQueueStats = { queueType: string; 
+/**
+ * e.g. "GOLD" (empty or "NONE" if unranked)
+ */
+tier?: string; 
+/**
+ * "I" - "IV" ("NA" for apex tiers)
+ */
+division?: string; leaguePoints?: number }
+/**
+ * the cached fields of a recording and its metadata file that are needed to list, sort and filter recordings
+ */
+export type RecordingSummary = { videoId: string; path: string; 
+/**
+ * in bytes
+ */
+size: number; 
+/**
+ * last modified time of the video file (unix timestamp in milliseconds)
+ */
+modified: number; 
+/**
+ * last modified time of the metadata file (unix timestamp in milliseconds)
+ */
+metadataModified: number | null; 
+/**
+ * time of the game, falls back to the time the video file was created (unix timestamp in milliseconds)
+ */
+timestamp: number; favorite: boolean; accountId: string | null; account: Player | null; matchId: MatchId | null; queue: Queue | null; championName: string | null; result: GameResult | null; patch: string | null; 
+/**
+ * in seconds
+ */
+gameDuration: number | null; stats: Stats | null; 
+/**
+ * champion kills of the team of the player
+ */
+teamKills: number | null; 
+/**
+ * LP won or lost in the game (only ranked games)
+ */
+lpDelta: number | null; highlights: number; annotations: Annotations; review: ReviewState; 
+/**
+ * the recording is a clip or highlight reel that got exported from other recordings
+ */
+clip: boolean; 
+/**
+ * the game data hasn't been collected yet (e.g. because the game wasn't in the match history yet)
+ */
+deferred: boolean }
+/**
+ * filter for `search_recordings`, every field that is set has to match
+ */
+export type RecordingsQuery = { 
+/**
+ * champion name (case-insensitive)
+ */
+champion: string | null; queueId: number | null; result: GameResult | null; 
+/**
+ * unix timestamp in milliseconds (inclusive)
+ */
+from: number | null; 
+/**
+ * unix timestamp in milliseconds (inclusive)
+ */
+to: number | null; patch: string | null; accountId: string | null; favorite: boolean | null; minKda: number | null; hasHighlights: boolean | null; watched: boolean | null; reviewed: boolean | null; 
+/**
+ * user tag (case-insensitive)
+ */
+tag: string | null; 
+/**
+ * searches the name of the recording, champion, queue, Riot ID, notes, tags and comments (case-insensitive)
+ */
+text: string | null; 
+/**
+ * number of results to skip
+ */
+offset: number; 
+/**
+ * max number of results, `None` returns all remaining results
+ */
+limit: number | null }
+/**
+ * parts of one or more recordings that got joined into one video (e.g. all pentakills of a season)
+ */
+export type Reel = { favorite: boolean; 
+/**
+ * in the order they are played in the reel
+ */
+segments: ReelSegment[]; annotations: Annotations; review: ReviewState }
+/**
+ * part of a recording that gets copied into a highlight reel
+ */
+export type ReelPart = { videoId: string; 
+/**
+ * `None` copies the whole recording
+ */
+range: TimeRange | null }
+export type ReelSegment = { 
+/**
+ * video id of the recording the segment was copied from
+ */
+sourceVideoId: string; 
+/**
+ * start of the segment in seconds of the source video
+ */
+start: number; 
+/**
+ * end of the segment in seconds of the source video
+ */
+end: number; 
+/**
+ * start of the segment in seconds of the reel
+ */
+reelStart: number; matchId: MatchId | null; championName: string | null }
+/**
+ * replay of the League client, linked to the recording of the same game
+ */
+export type ReplayFile = { path: string; gameId: number | null; patch: string; 
+/**
+ * in seconds
+ */
+gameDuration: number; 
+/**
+ * recording of the same game (if there is one)
+ */
+videoId: string | null; 
+/**
+ * the recording has no game data (e.g. because the game is no longer in the match history)
+ */
+canBackfill: boolean }
+/**
+ * the League client downloads its replay of the game (`.rofl` file) after the game ended
+ */
+export type ReplayState = "Downloading" | { Downloaded: { path: string } } | 
+/**
+ * e.g. because the replay expired or the client was closed before the replay was available
+ */
+{ Failed: { reason: string } }
+export type ReportFormat = "Markdown" | 
+/**
+ * standalone HTML page without any external resources
+ */
+"Html"
+/**
+ * progress of the user reviewing the recording
+ */
+export type ReviewState = { watched: boolean; reviewed: boolean; 
+/**
+ * time in the video in seconds where playback stopped last time
+ */
+position: number | null }
+export type Roster = { mapId: number | null; mapName: string | null; gameMode: string | null; players: RosterEntry[] }
+export type RosterEntry = { 
+/**
+ * only known after the game from match history
+ */
+participantId?: number | null; 
+/**
+ * missing if the match history has no (valid) team id for the participant
+ */
+team?: number | null; championId: number; championName: string | null; 
+/**
+ * missing for bots
+ */
+player?: Player | null; position?: string | null }
+export type SearchResults = { 
+/**
+ * the requested page of matching recordings sorted by time of the game (index 0 is newest)
+ */
+recordings: RecordingSummary[]; 
+/**
+ * number of all matching recordings
+ */
+total: number; offset: number }
+/**
+ * consecutive games that started less than the configured gap after the previous game ended
+ */
+export type Session = { 
+/**
+ * unix timestamp in milliseconds of when the first game started
+ */
+start: number; 
+/**
+ * unix timestamp in milliseconds of when the last game ended (if the game duration is known)
+ */
+end: number; 
+/**
+ * oldest first
+ */
+videoIds: string[]; games: number; wins: number; losses: number; remakes: number; 
+/**
+ * sum of the LP changes of all ranked games with a known LP change
+ */
+lpDelta: number | null; averageKda: number | null; 
+/**
+ * the KDA dropped from loss to loss over at least `TILT_LOSSES` consecutive losses
+ */
+tilt: boolean }
+export type Statistics = { overall: GroupStatistics; 
+/**
+ * sorted by number of games
+ */
+champions: GroupStatistics[]; 
+/**
+ * sorted by number of games
+ */
+queues: GroupStatistics[]; longestWinStreak: number; longestLossStreak: number; 
+/**
+ * positive for a win streak, negative for a loss streak
+ */
+currentStreak: number }
+export type StatisticsFilter = { 
+/**
+ * unix timestamp in milliseconds (inclusive)
+ */
+from: number | null; 
+/**
+ * unix timestamp in milliseconds (inclusive)
+ */
+to: number | null; accountId: string | null }
 export type Stats = { kills: number; deaths: number; assists: number; largestMultiKill: number; neutralMinionsKilled: number; neutralMinionsKilledEnemyJungle: number; neutralMinionsKilledTeamJungle: number; totalMinionsKilled: number; visionScore: number; visionWardsBoughtInGame: number; wardsPlaced: number; wardsKilled: number; 
 /**
  * remake
@@ -81,7 +666,10 @@ export type Stats = { kills: number; deaths: number; assists: number; largestMul
  * surrenders pre minute 20 count as a normal surrender (field `game_ended_in_surrender`)
  */
 gameEndedInEarlySurrender: boolean; gameEndedInSurrender: boolean; win: boolean }
-export type Team = "BLUE" | "RED"
+/**
+ * start and end in seconds of the video (same as the time of the player)
+ */
+export type TimeRange = { start: number; end: number }
 export type TowerType = "OUTER_TURRET" | "INNER_TURRET" | "BASE_TURRET" | "NEXUS_TURRET"
 
 /** tauri-specta globals **/
@@ -3,9 +3,26 @@ import type Player from "video.js/dist/types/player";
 import { type MarkerOptions, MarkersPlugin, type Settings } from "@fffffffxxxxxxx/videojs-markers";
 
 import { convertFileSrc } from "@tauri-apps/api/core";
-import { join, sep } from "@tauri-apps/api/path";
-
-import { commands, type GameEvent, type MarkerFlags } from "./bindings";
+import { sep } from "@tauri-apps/api/path";
+import * as clipboard from "@tauri-apps/plugin-clipboard-manager";
+import { open } from "@tauri-apps/plugin-dialog";
+
+import {
+    type Comment,
+    type ReelSegment,
+    commands,
+    type DeathWindow,
+    type GameEvent,
+    type LedgerColumn,
+    type LedgerFormat,
+    type MarkerFlags,
+    type MarkerFormat,
+    type MetadataFile,
+    type RecordingsQuery,
+    type ReportFormat,
+    type ReviewState,
+    type TimeRange,
+} from "./bindings";
 import ListenerManager from "./listeners";
 import UI from "./ui";
 import { splitRight, UnreachableError } from "./util";
@@ -13,6 +30,8 @@ import { splitRight, UnreachableError } from "./util";
 // sets the time a marker jumps to before the actual event happens
 // jumps to (eventTime - EVENT_DELAY) when a marker is clicked
 const EVENT_DELAY = 2;
+// length in seconds of a clip that ends at the current position of the player
+const CLIP_DURATION = 20;
 
 const ui = new UI(videojs);
 
@@ -30,6 +49,35 @@ type HighlightEvents = {
 let currentEvents: RecordingEvents | null = null;
 let highlightEvents: HighlightEvents | null = null;
 
+type CommentEvents = {
+    recordingOffset: number;
+    comments: Array<Comment>;
+};
+
+let commentEvents: CommentEvents | null = null;
+
+// start of every segment of a highlight reel in seconds of the video
+let reelSegments: Array<ReelSegment> | null = null;
+
+// only recordings with game data have markers that can be exported
+let hasGameData = false;
+
+type PlaylistItem = {
+    videoId: string;
+    // null plays the whole recording
+    range: TimeRange | null;
+};
+
+type Playlist = {
+    items: ReadonlyArray<PlaylistItem>;
+    index: number;
+    // the next item is still loading, so the time of the player still belongs to the previous item
+    loading: boolean;
+};
+
+// the collection or death review that is currently played back-to-back
+let playlist: Playlist | null = null;
+
 const VIDEO_JS_OPTIONS = {
     aspectRatio: "16:9",
     playbackRates: [0.5, 1, 1.5, 2],
@@ -82,6 +130,9 @@ async function main() {
             const videoId = splitRight(videoPath, sep());
             ui.setActiveVideoId(videoId);
             setMetadata(videoId);
+            if (playlist === null) {
+                void resumePlayback(videoId);
+            }
 
             // re-show the bigplaybutton and controlbar when a new video src is set
             ui.showBigPlayButton(true);
@@ -96,6 +147,42 @@ async function main() {
         commands.setMarkerFlags(ui.getMarkerFlags());
     });
     ui.setShowTimestampsOnClickHandler(showTimestamps);
+    ui.setShowCollectionsOnClickHandler(showCollections);
+    ui.setShowDeathsOnClickHandler(showDeathReview);
+    ui.setExportClipOnClickHandler(exportClip);
+    ui.setExportBundleOnClickHandler(exportBundle);
+    ui.setImportBundleBtnOnClickHandler(importBundle);
+    ui.setExportLedgerBtnOnClickHandler(exportLedger);
+    ui.setReplaysBtnOnClickHandler(showReplays);
+    ui.setShowReportOnClickHandler(showReport);
+    ui.setOpenReplayOnClickHandler(openReplay);
+
+    // continue with the next item of the playlist when the current item is over
+    player.on("timeupdate", () => {
+        if (playlist === null || playlist.loading) {
+            return;
+        }
+        const range = playlist.items[playlist.index]?.range;
+        if (range && player.currentTime()! >= range.end) {
+            nextPlaylistItem();
+        }
+    });
+    player.on("ended", nextPlaylistItem);
+
+    // remember where the playback stopped and whether the recording was watched until the end
+    player.on("pause", () => {
+        const videoId = ui.getActiveVideoId();
+        if (videoId !== null && !player.ended()) {
+            void commands.setPlaybackPosition(videoId, player.currentTime()!);
+        }
+    });
+    player.on("ended", () => {
+        const videoId = ui.getActiveVideoId();
+        if (videoId !== null) {
+            void commands.setPlaybackPosition(videoId, null);
+            void commands.setWatched(videoId, true);
+        }
+    });
 
     // listen if the videojs player fills the whole window
     // and keep the tauri fullscreen setting in sync
@@ -141,7 +228,7 @@ async function updateSidebar() {
         commands.getRecordingsList(),
         commands.getRecordingsSize(),
     ]);
-    ui.updateSideBar(recordingsSize, recordings, setVideo, commands.toggleFavorite, showRenameModal, showDeleteModal);
+    ui.updateSideBar(recordingsSize, recordings, selectVideo, commands.toggleFavorite, showRenameModal, showDeleteModal);
 
     if (!ui.setActiveVideoId(activeVideoId)) {
         void setVideo(null);
@@ -156,16 +243,333 @@ async function setVideo(videoId: string | null) {
         return;
     }
 
+    const activeVideoId = ui.getActiveVideoId();
+    if (activeVideoId !== null && !player.paused()) {
+        void commands.setPlaybackPosition(activeVideoId, player.currentTime()!);
+    }
+
     if (videoId === null) {
         player.src("");
     } else {
-        const recordingsPath = await commands.getRecordingsPath();
-        player.src({ type: "video/mp4", src: convertFileSrc(await join(recordingsPath, videoId)) });
+        // recordings can be in account subfolders of the recordings folder
+        // in streamer mode the path is only the video id and the recording gets played through the 'recording' protocol
+        const videoPath = await commands.getVideoPath(videoId);
+        const src = videoPath === videoId ? convertFileSrc(videoId, "recording") : convertFileSrc(videoPath);
+        player.src({ type: "video/mp4", src });
+    }
+}
+
+function reviewState(data: MetadataFile): ReviewState {
+    if ("Metadata" in data) {
+        return data.Metadata.review;
+    } else if ("Deferred" in data) {
+        return data.Deferred.review;
+    } else if ("Clip" in data) {
+        return data.Clip.review;
+    } else if ("Reel" in data) {
+        return data.Reel.review;
+    } else {
+        return data.NoData.review;
+    }
+}
+
+// continue where the playback of the recording stopped last time
+async function resumePlayback(videoId: string) {
+    const data = await commands.getMetadata(videoId);
+    const position = data ? reviewState(data).position : null;
+    if (position === null || videoId !== ui.getActiveVideoId()) {
+        return;
+    }
+
+    // HAVE_METADATA
+    if (player.readyState() >= 1) {
+        player.currentTime(position);
+    } else {
+        player.one("loadedmetadata", () => player.currentTime(position));
+    }
+}
+
+// selecting a video manually stops the playlist
+function selectVideo(videoId: string) {
+    playlist = null;
+    void setVideo(videoId);
+}
+
+async function showCollections() {
+    ui.showCollectionsModal(await commands.getCollections(), playCollection, exportCollection);
+}
+
+// joins all items of the collection into a highlight reel and switches to it
+async function exportCollection(collectionId: number, name: string) {
+    const items = await commands.getCollectionPlaylist(collectionId);
+    if (items.length === 0) {
+        ui.showErrorModal("This collection contains no recordings!");
+        return;
+    }
+
+    const reelId = await commands.exportHighlightReel(name, items);
+    if (reelId === null) {
+        ui.showErrorModal("Failed to export the highlight reel! All recordings need the same recording settings.");
+        return;
+    }
+
+    await updateSidebar();
+    selectVideo(reelId);
+}
+
+async function playCollection(collectionId: number) {
+    const items = await commands.getCollectionPlaylist(collectionId);
+    if (items.length === 0) {
+        ui.showErrorModal("This collection contains no recordings!");
+        return;
+    }
+
+    await playPlaylist(items, 0);
+}
+
+// saves the last seconds before the current position of the player as a clip and switches to the clip
+async function exportClip() {
+    const videoId = ui.getActiveVideoId();
+    if (videoId === null) {
+        return;
+    }
+
+    player.pause();
+    const end = player.currentTime()!;
+    const clipId = await commands.exportClip(videoId, Math.max(end - CLIP_DURATION, 0), end);
+    if (clipId === null) {
+        ui.showErrorModal("Failed to export the clip!");
+        return;
+    }
+
+    await updateSidebar();
+    selectVideo(clipId);
+}
+
+// packages the recording with its metadata (notes, highlights and comments) into a zip archive to share it
+function exportBundle() {
+    const videoId = ui.getActiveVideoId();
+    if (videoId === null) {
+        return;
+    }
+
+    ui.showExportBundleModal((anonymize) => void saveBundle(videoId, anonymize));
+}
+
+async function saveBundle(videoId: string, anonymize: boolean) {
+    const path = await commands.exportBundle(videoId, anonymize);
+    if (path === null) {
+        ui.showErrorModal("Failed to export the recording!");
+        return;
+    }
+
+    ui.showMessageModal(`Saved the recording bundle to ${path}`);
+}
+
+async function importBundle() {
+    const path = await open({
+        multiple: false,
+        directory: false,
+        filters: [{ name: "Recording Bundle", extensions: ["zip"] }],
+    });
+    if (path === null) {
+        return;
+    }
+
+    const videoId = await commands.importBundle(path);
+    if (videoId === null) {
+        ui.showErrorModal("Failed to import the recording! The bundle is invalid or corrupted.");
+        return;
+    }
+
+    await updateSidebar();
+    selectVideo(videoId);
+}
+
+// one row per game for spreadsheets, clips and highlight reels are left out by the backend
+function exportLedger() {
+    ui.showExportLedgerModal((columns, format) => void saveLedger(columns, format));
+}
+
+async function saveLedger(columns: LedgerColumn[], format: LedgerFormat) {
+    const allRecordings: RecordingsQuery = {
+        champion: null,
+        queueId: null,
+        result: null,
+        from: null,
+        to: null,
+        patch: null,
+        accountId: null,
+        favorite: null,
+        minKda: null,
+        hasHighlights: null,
+        watched: null,
+        reviewed: null,
+        tag: null,
+        text: null,
+        offset: 0,
+        limit: null,
+    };
+    const path = await commands.exportLedger(allRecordings, columns, format);
+    if (path === null) {
+        ui.showErrorModal("Failed to export the ledger!");
+        return;
+    }
+
+    ui.showMessageModal(`Saved the ledger to ${path}`);
+}
+
+async function showReplays() {
+    const replays = await commands.scanReplays();
+    const videoId = ui.getActiveVideoId();
+    ui.showReplaysModal(replays, videoId !== null && !hasGameData ? videoId : null, backfillFromReplay);
+}
+
+// fills in the game data of recordings whose game is no longer in the match history
+async function backfillFromReplay(videoId: string, replayPath: string) {
+    if (!(await commands.backfillFromReplay(videoId, replayPath))) {
+        ui.showErrorModal("Failed to read the game data from the replay!");
+        return;
+    }
+
+    await updateSidebar();
+    selectVideo(videoId);
+}
+
+function showReport() {
+    const videoId = ui.getActiveVideoId();
+    if (videoId === null) {
+        return;
+    }
+    if (!hasGameData) {
+        ui.showErrorModal("Reports need the game data of the recording!");
+        return;
+    }
+
+    ui.showReportModal(
+        (format) => void copyReport(videoId, format),
+        (format) => void saveReport(videoId, format),
+    );
+}
+
+async function copyReport(videoId: string, format: ReportFormat) {
+    const report = await commands.getReport(videoId, format);
+    if (report === null) {
+        ui.showErrorModal("Failed to create the report!");
+        return;
+    }
+
+    await clipboard.writeText(report);
+}
+
+async function saveReport(videoId: string, format: ReportFormat) {
+    const path = await commands.saveReport(videoId, format);
+    if (path === null) {
+        ui.showErrorModal("Failed to save the report!");
+        return;
+    }
+
+    ui.showMessageModal(`Saved the report to ${path}`);
+}
+
+async function openReplay() {
+    const videoId = ui.getActiveVideoId();
+    if (videoId === null) {
+        return;
+    }
+
+    if (await commands.openReplay(videoId)) {
+        return;
+    }
+
+    const data = await commands.getMetadata(videoId);
+    const replay =
+        data !== null && "Metadata" in data
+            ? data.Metadata.replay
+            : data !== null && "Deferred" in data
+              ? data.Deferred.replay
+              : null;
+    if (replay === "Downloading") {
+        ui.showErrorModal("The replay is still being downloaded by the League client!");
+    } else if (replay && typeof replay === "object" && "Failed" in replay) {
+        ui.showErrorModal(`Failed to download the replay: ${replay.Failed.reason}`);
+    } else {
+        ui.showErrorModal(
+            "Failed to open the replay! The League client has to be running and the replay has to be of the current patch.",
+        );
     }
 }
 
+async function showDeathReview() {
+    const videoId = ui.getActiveVideoId();
+    if (videoId === null) {
+        return;
+    }
+
+    const deaths = await commands.getRecordingDeaths(videoId, null);
+    if (deaths.length === 0) {
+        ui.showErrorModal("No deaths found in this recording!");
+        return;
+    }
+
+    ui.showTimelineModal(
+        deaths.map((death) => ({ timestamp: death.gameTime, text: deathText(death) })),
+        (gameTime) => {
+            const index = deaths.findIndex((death) => death.gameTime === gameTime);
+            void playPlaylist(deaths, Math.max(index, 0));
+        },
+    );
+}
+
+function deathText(death: DeathWindow): string {
+    const killer = death.killer ?? "Executed";
+    const assisters = death.assisters.length > 0 ? ` + ${death.assisters.join(", ")}` : "";
+    return `${formatTimestamp(death.gameTime)} ${killer}${assisters}`;
+}
+
+async function playPlaylist(items: ReadonlyArray<PlaylistItem>, index: number) {
+    playlist = { items, index, loading: true };
+    await playPlaylistItem();
+}
+
+async function playPlaylistItem() {
+    const item = playlist?.items[playlist.index];
+    if (item === undefined) {
+        playlist = null;
+        return;
+    }
+
+    const start = () => {
+        if (playlist !== null) {
+            playlist.loading = false;
+        }
+        player.currentTime(item.range?.start ?? 0);
+        void player.play();
+    };
+
+    // consecutive items can be different ranges of the same recording
+    if (item.videoId === ui.getActiveVideoId()) {
+        start();
+    } else {
+        player.one("loadedmetadata", start);
+        await setVideo(item.videoId);
+    }
+}
+
+function nextPlaylistItem() {
+    if (playlist === null) {
+        return;
+    }
+
+    playlist.index += 1;
+    playlist.loading = true;
+    void playPlaylistItem();
+}
+
 async function setMetadata(videoId: string) {
     const data = await commands.getMetadata(videoId);
+    reelSegments = null;
+    hasGameData = data !== null && "Metadata" in data;
     if (data && "Metadata" in data) {
         ui.showMarkerFlags(true);
         ui.setVideoDescriptionMetadata(data.Metadata);
@@ -178,19 +582,50 @@ async function setMetadata(videoId: string) {
             recordingOffset: data.Metadata.ingameTimeRecStartOffset,
             events: data.Metadata.highlights,
         };
+        commentEvents = {
+            recordingOffset: data.Metadata.ingameTimeRecStartOffset,
+            comments: data.Metadata.annotations.comments,
+        };
     } else if (data && "Deferred" in data) {
         ui.showMarkerFlags(false);
-        ui.setVideoDescription("", "No Data");
+        ui.setVideoDescriptionDeferred(data.Deferred);
         currentEvents = null;
         highlightEvents = {
             recordingOffset: data.Deferred.ingameTimeRecStartOffset,
             events: data.Deferred.highlights,
         };
+        commentEvents = {
+            recordingOffset: data.Deferred.ingameTimeRecStartOffset,
+            comments: data.Deferred.annotations.comments,
+        };
+    } else if (data && "Reel" in data) {
+        ui.showMarkerFlags(false);
+        ui.setVideoDescription("", `Highlight Reel (${data.Reel.segments.length} segments)`);
+        currentEvents = null;
+        highlightEvents = null;
+        // reels have no ingame time, so comments use the time in the video
+        commentEvents = { recordingOffset: 0, comments: data.Reel.annotations.comments };
+        reelSegments = data.Reel.segments;
+    } else if (data && "Clip" in data) {
+        const clip = data.Clip;
+        // without the ingame time of the source recording the comments use the time in the video
+        const recordingOffset = clip.ingameTimeRecStartOffset ?? 0;
+        ui.showMarkerFlags(clip.participantId !== null);
+        ui.setVideoDescription(clip.championName ?? "", `Clip of ${clip.sourceVideoId}`);
+        currentEvents =
+            clip.participantId !== null
+                ? { participantId: clip.participantId, recordingOffset, events: clip.events }
+                : null;
+        highlightEvents = { recordingOffset, events: clip.highlights };
+        commentEvents = { recordingOffset, comments: clip.annotations.comments };
     } else {
         ui.showMarkerFlags(false);
         ui.setVideoDescription("", "No Data");
         currentEvents = null;
         highlightEvents = null;
+        // without game data the comment time is the time in the video
+        commentEvents =
+            data && "NoData" in data ? { recordingOffset: 0, comments: data.NoData.annotations.comments } : null;
     }
 
     changeMarkers();
@@ -205,6 +640,26 @@ function changeMarkers() {
         }
     }
 
+    if (commentEvents !== null) {
+        for (const comment of commentEvents.comments) {
+            markers.push({
+                ...createMarker(comment.gameTime, commentEvents.recordingOffset, "Comment"),
+                text: comment.text,
+            });
+        }
+    }
+
+    if (reelSegments !== null) {
+        for (const segment of reelSegments) {
+            markers.push({
+                time: segment.reelStart,
+                text: segment.championName ?? segment.sourceVideoId,
+                class: "segment",
+                duration: 1,
+            });
+        }
+    }
+
     if (currentEvents !== null) {
         const checkbox = ui.getMarkerFlags();
         const { participantId, recordingOffset } = currentEvents;
@@ -239,7 +694,8 @@ type EventType =
     | "Hextech-Dragon"
     | "Chemtech-Dragon"
     | "Elder-Dragon"
-    | "Highlight";
+    | "Highlight"
+    | "Comment";
 
 function eventName(gameEvent: GameEvent, participantId: number, checkbox: MarkerFlags | null): EventType | null {
     if ("ChampionKill" in gameEvent) {
@@ -362,6 +818,13 @@ function showTimestamps() {
         }
     }
 
+    if (commentEvents !== null) {
+        for (const comment of commentEvents.comments) {
+            const text = `${formatTimestamp(comment.gameTime)} ${comment.text}`;
+            timelineEvents.push({ timestamp: comment.gameTime, text });
+        }
+    }
+
     if (currentEvents !== null) {
         for (const event of currentEvents.events) {
             const name = eventName(event, currentEvents.participantId, null);
@@ -373,12 +836,24 @@ function showTimestamps() {
         }
     }
 
+    const videoId = ui.getActiveVideoId();
     ui.showTimelineModal(
         timelineEvents.sort((a, b) => a.timestamp - b.timestamp),
         (secs) => player.currentTime(secs / 1000 - EVENT_DELAY),
+        videoId !== null && hasGameData ? (format) => void exportMarkers(videoId, format) : null,
     );
 }
 
+async function exportMarkers(videoId: string, format: MarkerFormat) {
+    const path = await commands.exportMarkers(videoId, format);
+    if (path === null) {
+        ui.showErrorModal("Failed to export the markers!");
+        return;
+    }
+
+    ui.showMessageModal(`Saved the markers to ${path}`);
+}
+
 function formatTimestamp(timestamp: number): string {
     let secs = timestamp / 1000;
 
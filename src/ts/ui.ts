@@ -3,8 +3,20 @@ import type { ContentDescriptor } from "video.js/dist/types/utils/dom";
 import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import * as clipboard from "@tauri-apps/plugin-clipboard-manager";
 
-import { commands, type GameMetadata, type MarkerFlags, type Recording } from "./bindings";
-import { toVideoId, toVideoName, isFavorite } from "./util";
+import {
+    type Collection,
+    commands,
+    type Deferred,
+    type GameMetadata,
+    type LedgerColumn,
+    type LedgerFormat,
+    type MarkerFlags,
+    type MarkerFormat,
+    type RecordingSummary,
+    type ReplayFile,
+    type ReportFormat,
+} from "./bindings";
+import { toVideoId, toVideoName } from "./util";
 
 const appWindow = getCurrentWebviewWindow();
 
@@ -13,6 +25,9 @@ export default class UI {
     private readonly modalContent;
     private readonly sidebar;
     private readonly videoFolderBtn;
+    private readonly importBundleBtn;
+    private readonly exportLedgerBtn;
+    private readonly replaysBtn;
     private readonly recordingsSize;
     private readonly descriptionLeft;
     private readonly descriptionCenter;
@@ -28,6 +43,12 @@ export default class UI {
     private readonly checkboxBaron;
 
     private readonly showTimestampsButton;
+    private readonly showCollectionsButton;
+    private readonly showDeathsButton;
+    private readonly exportClipButton;
+    private readonly exportBundleButton;
+    private readonly showReportButton;
+    private readonly openReplayButton;
 
     private readonly vjs: typeof videojs;
 
@@ -38,6 +59,9 @@ export default class UI {
         this.modalContent = document.querySelector<HTMLDivElement>("#modal-content")!;
         this.sidebar = document.querySelector<HTMLUListElement>("#sidebar-content")!;
         this.videoFolderBtn = document.querySelector<HTMLButtonElement>("#vid-folder-btn")!;
+        this.importBundleBtn = document.querySelector<HTMLButtonElement>("#import-bundle-btn")!;
+        this.exportLedgerBtn = document.querySelector<HTMLButtonElement>("#export-ledger-btn")!;
+        this.replaysBtn = document.querySelector<HTMLButtonElement>("#replays-btn")!;
         this.recordingsSize = document.querySelector<HTMLSpanElement>("#size-inner")!;
         this.descriptionLeft = document.querySelector<HTMLDivElement>("#description-left")!;
         this.descriptionCenter = document.querySelector<HTMLDivElement>("#description-center")!;
@@ -53,6 +77,12 @@ export default class UI {
         this.checkboxBaron = document.querySelector<HTMLInputElement>("#baron")!;
 
         this.showTimestampsButton = document.querySelector<HTMLButtonElement>("#copy-timestamps-btn")!;
+        this.showCollectionsButton = document.querySelector<HTMLButtonElement>("#collections-btn")!;
+        this.showDeathsButton = document.querySelector<HTMLButtonElement>("#deaths-btn")!;
+        this.exportClipButton = document.querySelector<HTMLButtonElement>("#clip-btn")!;
+        this.exportBundleButton = document.querySelector<HTMLButtonElement>("#bundle-btn")!;
+        this.showReportButton = document.querySelector<HTMLButtonElement>("#report-btn")!;
+        this.openReplayButton = document.querySelector<HTMLButtonElement>("#replay-btn")!;
     }
 
     public showWindow = () => {
@@ -67,6 +97,18 @@ export default class UI {
         this.videoFolderBtn.addEventListener("click", handler);
     };
 
+    public setImportBundleBtnOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.importBundleBtn.addEventListener("click", handler);
+    };
+
+    public setExportLedgerBtnOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.exportLedgerBtn.addEventListener("click", handler);
+    };
+
+    public setReplaysBtnOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.replaysBtn.addEventListener("click", handler);
+    };
+
     public setCheckboxOnClickHandler = (handler: (e: MouseEvent) => void) => {
         this.checkboxKill.addEventListener("click", handler);
         this.checkboxDeath.addEventListener("click", handler);
@@ -82,9 +124,33 @@ export default class UI {
         this.showTimestampsButton.addEventListener("click", handler);
     };
 
+    public setShowCollectionsOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.showCollectionsButton.addEventListener("click", handler);
+    };
+
+    public setShowDeathsOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.showDeathsButton.addEventListener("click", handler);
+    };
+
+    public setExportClipOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.exportClipButton.addEventListener("click", handler);
+    };
+
+    public setExportBundleOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.exportBundleButton.addEventListener("click", handler);
+    };
+
+    public setShowReportOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.showReportButton.addEventListener("click", handler);
+    };
+
+    public setOpenReplayOnClickHandler = (handler: (e: MouseEvent) => void) => {
+        this.openReplayButton.addEventListener("click", handler);
+    };
+
     public updateSideBar = (
         recordingsSizeGb: number,
-        recordings: ReadonlyArray<Recording>,
+        recordings: ReadonlyArray<RecordingSummary>,
         onVideo: (videoId: string) => void,
         onFavorite: (videoId: string) => Promise<boolean | null>,
         onRename: (videoId: string) => void,
@@ -94,7 +160,7 @@ export default class UI {
             const videoName = toVideoName(recording.videoId);
 
             // call event.stopPropagation(); to stop the onclick event from also effecting the element under the clicked X button
-            const favorite = isFavorite(recording.metadata);
+            const favorite = recording.favorite;
             const favoriteBtn = this.vjs.dom.createEl(
                 "span",
                 {
@@ -169,6 +235,10 @@ export default class UI {
     };
 
     public showErrorModal = (text: string) => {
+        this.showMessageModal(text);
+    };
+
+    public showMessageModal = (text: string) => {
         this.showModal([
             this.vjs.dom.createEl("p", {}, {}, text),
             this.vjs.dom.createEl(
@@ -180,6 +250,127 @@ export default class UI {
         ]);
     };
 
+    public showExportBundleModal = (exportBundle: (anonymize: boolean) => void) => {
+        const exportButton = (text: string, anonymize: boolean) =>
+            this.vjs.dom.createEl(
+                "button",
+                {
+                    onclick: () => {
+                        this.hideModal();
+                        exportBundle(anonymize);
+                    },
+                },
+                { class: "btn" },
+                text,
+            );
+
+        this.showModal([
+            this.vjs.dom.createEl(
+                "p",
+                {},
+                {},
+                "Anonymized bundles contain pseudonyms (e.g. \"Player 3\") instead of the Riot IDs of all players.",
+            ),
+            this.vjs.dom.createEl("p", {}, {}, [
+                exportButton("Export", false),
+                exportButton("Export Anonymized", true),
+                this.vjs.dom.createEl("button", { onclick: this.hideModal }, { class: "btn" }, "Cancel"),
+            ]),
+        ]);
+    };
+
+    public showExportLedgerModal = (exportLedger: (columns: LedgerColumn[], format: LedgerFormat) => void) => {
+        const columns: Array<[LedgerColumn, string]> = [
+            ["VideoId", "Recording"],
+            ["Date", "Date"],
+            ["MatchId", "Match ID"],
+            ["Queue", "Queue"],
+            ["Champion", "Champion"],
+            ["Result", "Result"],
+            ["Kills", "Kills"],
+            ["Deaths", "Deaths"],
+            ["Assists", "Assists"],
+            ["Kda", "KDA"],
+            ["Cs", "CS"],
+            ["CsPerMinute", "CS/min"],
+            ["VisionScore", "Vision"],
+            ["Duration", "Duration"],
+            ["Patch", "Patch"],
+            ["LpDelta", "LP"],
+        ];
+        const checkboxes = columns.map(([column, label]) => {
+            const checkbox = this.vjs.dom.createEl("input", {}, { type: "checkbox", checked: "" }) as HTMLInputElement;
+            return {
+                column,
+                checkbox,
+                label: this.vjs.dom.createEl("label", {}, {}, [checkbox, label]),
+            };
+        });
+
+        const exportButton = (text: string, format: LedgerFormat) =>
+            this.vjs.dom.createEl(
+                "button",
+                {
+                    onclick: () => {
+                        const selected = checkboxes.filter(({ checkbox }) => checkbox.checked);
+                        if (selected.length === 0) {
+                            return;
+                        }
+                        this.hideModal();
+                        exportLedger(selected.map(({ column }) => column), format);
+                    },
+                },
+                { class: "btn" },
+                text,
+            );
+
+        this.showModal([
+            this.vjs.dom.createEl("p", {}, {}, "One row per game with the selected columns:"),
+            this.vjs.dom.createEl("p", {}, {}, checkboxes.map(({ label }) => label)),
+            this.vjs.dom.createEl("p", {}, {}, [
+                exportButton("Export CSV", "Csv"),
+                exportButton("Export JSON Lines", "JsonLines"),
+                this.vjs.dom.createEl("button", { onclick: this.hideModal }, { class: "btn" }, "Cancel"),
+            ]),
+        ]);
+    };
+
+    public showReportModal = (
+        copyReport: (format: ReportFormat) => void,
+        saveReport: (format: ReportFormat) => void,
+    ) => {
+        const button = (text: string, onclick: () => void) =>
+            this.vjs.dom.createEl(
+                "button",
+                {
+                    onclick: () => {
+                        this.hideModal();
+                        onclick();
+                    },
+                },
+                { class: "btn" },
+                text,
+            );
+
+        this.showModal([
+            this.vjs.dom.createEl("p", {}, {}, "Post-game report with stats, timeline, highlights and notes."),
+            this.vjs.dom.createEl("p", {}, {}, [
+                button("Copy Markdown", () => copyReport("Markdown")),
+                button("Copy HTML", () => copyReport("Html")),
+            ]),
+            this.vjs.dom.createEl("p", {}, {}, [
+                button("Save Markdown", () => saveReport("Markdown")),
+                button("Save HTML", () => saveReport("Html")),
+            ]),
+            this.vjs.dom.createEl(
+                "p",
+                {},
+                {},
+                this.vjs.dom.createEl("button", { onclick: this.hideModal }, { class: "btn" }, "Cancel"),
+            ),
+        ]);
+    };
+
     public showRenameModal = (
         videoId: string,
         videoIds: ReadonlyArray<string>,
@@ -305,6 +496,7 @@ export default class UI {
     public showTimelineModal = (
         timelineEvents: Array<{ timestamp: number; text: string }>,
         setTime: (secs: number) => void,
+        exportMarkers: ((format: MarkerFormat) => void) | null,
     ) => {
         const closeButton = this.vjs.dom.createEl(
             "span",
@@ -339,7 +531,136 @@ export default class UI {
             "Copy to Clipboard",
         );
 
-        this.showModal([closeButton, timelineList, copyToClipboardButton]);
+        const formats: Array<[MarkerFormat, string]> = [
+            ["WebVtt", "WebVTT"],
+            ["Srt", "SRT"],
+            ["Csv", "CSV"],
+            ["Edl", "EDL (Resolve)"],
+            ["Xml", "XML (Premiere)"],
+        ];
+        const exportButtons =
+            exportMarkers === null
+                ? []
+                : [
+                      this.vjs.dom.createEl(
+                          "p",
+                          {},
+                          {},
+                          formats.map(([format, label]) =>
+                              this.vjs.dom.createEl(
+                                  "button",
+                                  { onclick: () => exportMarkers(format) },
+                                  { class: "btn" },
+                                  `Export ${label}`,
+                              ),
+                          ),
+                      ),
+                  ];
+
+        this.showModal([closeButton, timelineList, copyToClipboardButton, ...exportButtons]);
+    };
+
+    public showCollectionsModal = (
+        collections: ReadonlyArray<Collection>,
+        play: (collectionId: number) => void,
+        exportReel: (collectionId: number, name: string) => void,
+    ) => {
+        const closeButton = this.vjs.dom.createEl(
+            "span",
+            { onclick: this.hideModal },
+            { class: "timeline-event-close-button" },
+            "×",
+        );
+
+        const collectionList = this.vjs.dom.createEl(
+            "ul",
+            {},
+            { class: "timeline-event-list" },
+            collections.map(({ id, name, items }) =>
+                this.vjs.dom.createEl(
+                    "li",
+                    {
+                        onclick: () => {
+                            play(id);
+                            this.hideModal();
+                        },
+                    },
+                    { class: "timeline-event-list-item" },
+                    [
+                        `${name} (${items.length}) `,
+                        this.vjs.dom.createEl(
+                            "button",
+                            {
+                                onclick: (e: MouseEvent) => {
+                                    // don't also play the collection
+                                    e.stopPropagation();
+                                    exportReel(id, name);
+                                    this.hideModal();
+                                },
+                            },
+                            { class: "btn" },
+                            "Export Reel",
+                        ),
+                    ],
+                ),
+            ),
+        );
+
+        this.showModal([closeButton, collectionList]);
+    };
+
+    // `linkVideoId` is the selected recording if it has no game data, so unlinked replays can be linked to it
+    public showReplaysModal = (
+        replays: ReadonlyArray<ReplayFile>,
+        linkVideoId: string | null,
+        backfill: (videoId: string, replayPath: string) => void,
+    ) => {
+        const closeButton = this.vjs.dom.createEl(
+            "span",
+            { onclick: this.hideModal },
+            { class: "timeline-event-close-button" },
+            "×",
+        );
+
+        const backfillButton = (text: string, videoId: string, replayPath: string) =>
+            this.vjs.dom.createEl(
+                "button",
+                {
+                    onclick: () => {
+                        this.hideModal();
+                        backfill(videoId, replayPath);
+                    },
+                },
+                { class: "btn" },
+                text,
+            );
+
+        const replayList = this.vjs.dom.createEl(
+            "ul",
+            {},
+            { class: "timeline-event-list" },
+            replays.map(({ path, gameId, patch, gameDuration, videoId, canBackfill }) => {
+                const duration = `${Math.floor(gameDuration / 60)}:${(gameDuration % 60).toString().padStart(2, "0")}`;
+                const recording = videoId === null ? "no recording" : toVideoName(videoId);
+                const button =
+                    videoId !== null
+                        ? canBackfill
+                            ? backfillButton("Fill in Game Data", videoId, path)
+                            : ""
+                        : linkVideoId !== null
+                          ? backfillButton("Link to Selected Recording", linkVideoId, path)
+                          : "";
+                return this.vjs.dom.createEl("li", {}, { class: "timeline-event-list-item" }, [
+                    `Game ${gameId ?? "?"} - Patch ${patch} - ${duration} - ${recording} `,
+                    button,
+                ]);
+            }),
+        );
+
+        this.showModal([
+            closeButton,
+            replays.length === 0 ? this.vjs.dom.createEl("p", {}, {}, "No replays found.") : replayList,
+        ]);
     };
 
     public getActiveVideoId = (): string | null => {
@@ -380,6 +701,26 @@ export default class UI {
         );
     };
 
+    public setVideoDescriptionDeferred = (data: Deferred) => {
+        const roster = data.roster;
+        if (!roster) {
+            this.setVideoDescription("", "No Data");
+            return;
+        }
+
+        // 100 => blue side, 200 => red side
+        const team = (teamId: number) =>
+            roster.players
+                .filter((p) => p.team === teamId)
+                .map((p) => p.championName ?? "Unknown")
+                .join(", ");
+
+        this.setVideoDescription(
+            [`Blue: ${team(100)}`, this.vjs.dom.createEl("br"), `Red: ${team(200)}`],
+            [`Game Mode: ${roster.gameMode ?? "Unknown"} `, this.vjs.dom.createEl("br"), "No Data"],
+        );
+    };
+
     public showBigPlayButton = (show: boolean) => {
         const bpb = document.querySelector<HTMLButtonElement>(".vjs-big-play-button");
         if (bpb !== null) {